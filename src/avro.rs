@@ -0,0 +1,504 @@
+//! Validation of Avro JSON-encoded data against an Avro schema.
+//!
+//! The [Avro JSON encoding](https://avro.apache.org/docs/1.11.1/specification/#json-encoding)
+//! differs from plain JSON in a few places: non-null union values are
+//! wrapped in a single-key object naming the branch, and `bytes` and `fixed`
+//! values are strings whose characters each encode one byte. [`Schema`]
+//! checks a payload follows these rules and reports violations with the
+//! usual codes and pointers.
+//!
+//! ```
+//! use serde_json::json;
+//! use serdify::{avro::Schema, code};
+//!
+//! let schema = Schema::parse(&json!({
+//!     "type": "record",
+//!     "name": "User",
+//!     "namespace": "com.example",
+//!     "fields": [
+//!         { "name": "id", "type": "long" },
+//!         { "name": "email", "type": ["null", "string"] },
+//!         { "name": "plan", "type": "string", "default": "free" }
+//!     ]
+//! }))
+//! .unwrap();
+//!
+//! assert!(schema.validate(&json!({"id": 1, "email": {"string": "ada@example.com"}})).is_ok());
+//!
+//! let error = schema.validate(&json!({"email": {"int": 1}})).unwrap_err();
+//! assert_eq!(error.invalid_params[0].pointer, "#");
+//! assert_eq!(error.invalid_params[0].name, "id");
+//! assert_eq!(error.invalid_params[0].code, code::FIELD_MISSING);
+//! assert_eq!(error.invalid_params[1].pointer, "#/email/int");
+//! assert_eq!(error.invalid_params[1].code, code::VARIANT_UNKNOWN);
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::pass::Pass;
+use crate::{Error, ExpectedOrActual, InvalidParam, Result, code, pointer};
+
+/// A parsed Avro schema.
+///
+/// A schema is also a [`Pass`], so it can be added to
+/// [`Options`](crate::Options).
+#[derive(Debug, Clone)]
+pub struct Schema {
+    root: Node,
+    named: HashMap<String, Node>,
+}
+
+/// Why an Avro schema could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaError {
+    /// Pointer to the offending part of the schema.
+    pub pointer: String,
+    /// What is wrong with it.
+    pub message: String,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Null,
+    Boolean,
+    Int,
+    Long,
+    Float,
+    Double,
+    Bytes,
+    String,
+    Array(Box<Node>),
+    Map(Box<Node>),
+    Union(Vec<Node>),
+    Record(String, Vec<Field>),
+    Enum(String, Vec<String>),
+    Fixed(String, usize),
+    /// Reference to a named type, by full name.
+    Named(String),
+}
+
+#[derive(Debug, Clone)]
+struct Field {
+    name: String,
+    node: Node,
+    has_default: bool,
+}
+
+impl Schema {
+    /// Parses an Avro schema from its JSON form.
+    pub fn parse(schema: &Value) -> std::result::Result<Self, SchemaError> {
+        let mut named = HashMap::new();
+        let root = Parser { named: &mut named }.parse(schema, "#", "")?;
+        Ok(Self { root, named })
+    }
+
+    /// Validates an Avro JSON-encoded value, reporting every violation.
+    pub fn validate(&self, value: &Value) -> Result<()> {
+        let mut findings = Vec::new();
+        self.run(value, &mut findings);
+        if findings.is_empty() {
+            Result::Ok(())
+        } else {
+            Result::Err(Error::new(findings))
+        }
+    }
+
+    fn check(&self, node: &Node, value: &Value, at: &str, findings: &mut Vec<InvalidParam>) {
+        let mismatch = |findings: &mut Vec<InvalidParam>| {
+            let expected = self.type_name(node);
+            findings.push(
+                InvalidParam::new(
+                    at,
+                    code::TYPE_MISMATCH,
+                    format!("Expected Avro {expected}, found {}", json_type(value)),
+                )
+                .expected(ExpectedOrActual::new(expected, self.json_format(node)))
+                .actual(ExpectedOrActual::of(value)),
+            );
+        };
+        match (node, value) {
+            (Node::Named(name), _) => self.check(&self.named[name], value, at, findings),
+            (Node::Null, Value::Null)
+            | (Node::Boolean, Value::Bool(_))
+            | (Node::String, Value::String(_)) => {}
+            (Node::Float | Node::Double, Value::Number(_)) => {}
+            (Node::Int | Node::Long, Value::Number(number)) => {
+                let (name, min, max) = match node {
+                    Node::Int => ("int", i64::from(i32::MIN), i64::from(i32::MAX)),
+                    _ => ("long", i64::MIN, i64::MAX),
+                };
+                match number.as_i64() {
+                    Some(int) if (min..=max).contains(&int) => {}
+                    _ if number.is_f64() => mismatch(findings),
+                    _ => findings.push(
+                        InvalidParam::new(
+                            at,
+                            code::RANGE_OVERFLOW,
+                            format!(
+                                "Value {number} is out of range for Avro {name}. Expected range: {min} to {max}"
+                            ),
+                        )
+                        .expected(ExpectedOrActual::new(name, "integer"))
+                        .actual(ExpectedOrActual::of(value)),
+                    ),
+                }
+            }
+            (Node::Bytes | Node::Fixed(..), Value::String(string)) => {
+                if string.chars().any(|char| u32::from(char) > 0xff) {
+                    findings.push(
+                        InvalidParam::new(
+                            at,
+                            code::VALUE_INVALID,
+                            "Expected one character per byte, found characters above U+00FF",
+                        )
+                        .expected(ExpectedOrActual::new(self.type_name(node), "string"))
+                        .actual(ExpectedOrActual::of(value)),
+                    );
+                } else if let Node::Fixed(_, size) = node {
+                    let len = string.chars().count();
+                    if len != *size {
+                        findings.push(
+                            InvalidParam::new(
+                                at,
+                                code::LENGTH_INVALID,
+                                format!("Expected {size} bytes, found {len}"),
+                            )
+                            .expected(ExpectedOrActual::new(self.type_name(node), "string"))
+                            .actual(ExpectedOrActual::of(value)),
+                        );
+                    }
+                }
+            }
+            (Node::Array(items), Value::Array(array)) => {
+                for (index, item) in array.iter().enumerate() {
+                    self.check(items, item, &format!("{at}/{index}"), findings);
+                }
+            }
+            (Node::Map(values), Value::Object(object)) => {
+                for (key, item) in object {
+                    self.check(values, item, &pointer::join(at, key), findings);
+                }
+            }
+            (Node::Enum(_, symbols), Value::String(symbol)) => {
+                if !symbols.contains(symbol) {
+                    findings.push(
+                        InvalidParam::new(
+                            at,
+                            code::VARIANT_UNKNOWN,
+                            format!(
+                                "Unknown symbol `{symbol}`, expected one of {}",
+                                one_of(symbols)
+                            ),
+                        )
+                        .expected(ExpectedOrActual::new(self.type_name(node), "string"))
+                        .actual(ExpectedOrActual::of(value)),
+                    );
+                }
+            }
+            (Node::Record(_, fields), Value::Object(object)) => {
+                for field in fields {
+                    let field_at = pointer::join(at, &field.name);
+                    match object.get(&field.name) {
+                        Some(item) => self.check(&field.node, item, &field_at, findings),
+                        None if field.has_default => {}
+                        None => findings.push(InvalidParam::missing(at, &field.name).expected(
+                            ExpectedOrActual::new(
+                                self.type_name(&field.node),
+                                self.json_format(&field.node),
+                            ),
+                        )),
+                    }
+                }
+                for (key, item) in object {
+                    if fields.iter().all(|field| field.name != *key) {
+                        findings.push(
+                            InvalidParam::new(
+                                pointer::join(at, key),
+                                code::FIELD_UNKNOWN,
+                                format!("Unknown field `{key}`"),
+                            )
+                            .expected(ExpectedOrActual::new("undefined", "missing"))
+                            .actual(ExpectedOrActual::of(item)),
+                        );
+                    }
+                }
+            }
+            (Node::Union(branches), Value::Null) => {
+                if !branches.iter().any(|branch| matches!(branch, Node::Null)) {
+                    mismatch(findings);
+                }
+            }
+            (Node::Union(branches), Value::Object(object)) if object.len() == 1 => {
+                let (name, item) = object.iter().next().expect("object has one entry");
+                let branch_at = pointer::join(at, name);
+                match branches
+                    .iter()
+                    .find(|branch| self.type_name(branch) == *name)
+                {
+                    Some(branch) => self.check(branch, item, &branch_at, findings),
+                    None => {
+                        let names: Vec<_> = branches
+                            .iter()
+                            .filter(|branch| !matches!(branch, Node::Null))
+                            .map(|branch| self.type_name(branch))
+                            .collect();
+                        findings.push(
+                            InvalidParam::new(
+                                branch_at,
+                                code::VARIANT_UNKNOWN,
+                                format!(
+                                    "Unknown union branch `{name}`, expected one of {}",
+                                    one_of(&names)
+                                ),
+                            )
+                            .expected(ExpectedOrActual::new(self.type_name(node), "object"))
+                            .actual(ExpectedOrActual::of(item)),
+                        );
+                    }
+                }
+            }
+            _ => mismatch(findings),
+        }
+    }
+
+    /// Name of a type as used to label union branches.
+    fn type_name(&self, node: &Node) -> String {
+        match node {
+            Node::Null => "null".to_owned(),
+            Node::Boolean => "boolean".to_owned(),
+            Node::Int => "int".to_owned(),
+            Node::Long => "long".to_owned(),
+            Node::Float => "float".to_owned(),
+            Node::Double => "double".to_owned(),
+            Node::Bytes => "bytes".to_owned(),
+            Node::String => "string".to_owned(),
+            Node::Array(_) => "array".to_owned(),
+            Node::Map(_) => "map".to_owned(),
+            Node::Union(branches) => {
+                let names: Vec<_> = branches.iter().map(|b| self.type_name(b)).collect();
+                format!("union of {}", names.join(", "))
+            }
+            Node::Record(name, _)
+            | Node::Enum(name, _)
+            | Node::Fixed(name, _)
+            | Node::Named(name) => name.clone(),
+        }
+    }
+
+    /// JSON format a type is encoded as.
+    fn json_format(&self, node: &Node) -> &'static str {
+        match node {
+            Node::Null => "null",
+            Node::Boolean => "boolean",
+            Node::Int | Node::Long => "integer",
+            Node::Float | Node::Double => "number",
+            Node::Bytes | Node::String | Node::Enum(..) | Node::Fixed(..) => "string",
+            Node::Array(_) => "array",
+            Node::Map(_) | Node::Record(..) | Node::Union(_) => "object",
+            Node::Named(name) => self.json_format(&self.named[name]),
+        }
+    }
+}
+
+impl Pass for Schema {
+    fn run(&self, value: &Value, findings: &mut Vec<InvalidParam>) {
+        self.check(&self.root, value, "#", findings);
+    }
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.pointer, self.message)
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+struct Parser<'a> {
+    named: &'a mut HashMap<String, Node>,
+}
+
+impl Parser<'_> {
+    fn parse(
+        &mut self,
+        schema: &Value,
+        at: &str,
+        namespace: &str,
+    ) -> std::result::Result<Node, SchemaError> {
+        match schema {
+            Value::String(name) => self.primitive(name, at, namespace),
+            Value::Array(branches) => {
+                let branches = branches
+                    .iter()
+                    .enumerate()
+                    .map(|(index, branch)| self.parse(branch, &format!("{at}/{index}"), namespace))
+                    .collect::<std::result::Result<_, _>>()?;
+                Ok(Node::Union(branches))
+            }
+            Value::Object(object) => {
+                let Some(Value::String(ty)) = object.get("type") else {
+                    return Err(invalid(at, "expected a `type` string"));
+                };
+                let items_at = |key| pointer::join(at, key);
+                match ty.as_str() {
+                    "array" => {
+                        let items = object
+                            .get("items")
+                            .ok_or_else(|| invalid(at, "array without `items`"))?;
+                        let items = self.parse(items, &items_at("items"), namespace)?;
+                        Ok(Node::Array(Box::new(items)))
+                    }
+                    "map" => {
+                        let values = object
+                            .get("values")
+                            .ok_or_else(|| invalid(at, "map without `values`"))?;
+                        let values = self.parse(values, &items_at("values"), namespace)?;
+                        Ok(Node::Map(Box::new(values)))
+                    }
+                    "record" | "error" | "enum" | "fixed" => self.named(object, at, namespace),
+                    // Logical types annotate a primitive, which defines the encoding.
+                    name => self.primitive(name, at, namespace),
+                }
+            }
+            _ => Err(invalid(at, "expected a type name, union or object")),
+        }
+    }
+
+    fn primitive(
+        &mut self,
+        name: &str,
+        at: &str,
+        namespace: &str,
+    ) -> std::result::Result<Node, SchemaError> {
+        Ok(match name {
+            "null" => Node::Null,
+            "boolean" => Node::Boolean,
+            "int" => Node::Int,
+            "long" => Node::Long,
+            "float" => Node::Float,
+            "double" => Node::Double,
+            "bytes" => Node::Bytes,
+            "string" => Node::String,
+            name => {
+                let qualified = qualify(name, namespace);
+                if self.named.contains_key(&qualified) {
+                    Node::Named(qualified)
+                } else if self.named.contains_key(name) {
+                    Node::Named(name.to_owned())
+                } else {
+                    return Err(invalid(at, &format!("unknown type `{name}`")));
+                }
+            }
+        })
+    }
+
+    fn named(
+        &mut self,
+        object: &serde_json::Map<String, Value>,
+        at: &str,
+        namespace: &str,
+    ) -> std::result::Result<Node, SchemaError> {
+        let Some(Value::String(name)) = object.get("name") else {
+            return Err(invalid(at, "named type without `name`"));
+        };
+        let namespace = match object.get("namespace") {
+            Some(Value::String(namespace)) => namespace.as_str(),
+            _ => namespace,
+        };
+        let fullname = qualify(name, namespace);
+        // Names declared with a dot carry their own namespace.
+        let namespace = fullname
+            .rsplit_once('.')
+            .map_or("", |(namespace, _)| namespace);
+        // Registered before the fields are parsed, so records can refer to
+        // themselves.
+        self.named
+            .insert(fullname.clone(), Node::Record(fullname.clone(), Vec::new()));
+        let node = match object.get("type").and_then(Value::as_str) {
+            Some("enum") => {
+                let symbols = object
+                    .get("symbols")
+                    .and_then(Value::as_array)
+                    .ok_or_else(|| invalid(at, "enum without `symbols`"))?;
+                Node::Enum(
+                    fullname.clone(),
+                    symbols
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .map(str::to_owned)
+                        .collect(),
+                )
+            }
+            Some("fixed") => {
+                let size = object
+                    .get("size")
+                    .and_then(Value::as_u64)
+                    .ok_or_else(|| invalid(at, "fixed without `size`"))?;
+                Node::Fixed(fullname.clone(), size as usize)
+            }
+            _ => {
+                let fields_at = pointer::join(at, "fields");
+                let fields = object
+                    .get("fields")
+                    .and_then(Value::as_array)
+                    .ok_or_else(|| invalid(at, "record without `fields`"))?;
+                let mut parsed = Vec::with_capacity(fields.len());
+                for (index, field) in fields.iter().enumerate() {
+                    let field_at = format!("{fields_at}/{index}");
+                    let Some(Value::String(name)) = field.get("name") else {
+                        return Err(invalid(&field_at, "field without `name`"));
+                    };
+                    let ty = field
+                        .get("type")
+                        .ok_or_else(|| invalid(&field_at, "field without `type`"))?;
+                    parsed.push(Field {
+                        name: name.clone(),
+                        node: self.parse(ty, &pointer::join(&field_at, "type"), namespace)?,
+                        has_default: field.get("default").is_some(),
+                    });
+                }
+                Node::Record(fullname.clone(), parsed)
+            }
+        };
+        self.named.insert(fullname.clone(), node);
+        Ok(Node::Named(fullname))
+    }
+}
+
+fn qualify(name: &str, namespace: &str) -> String {
+    if name.contains('.') || namespace.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{namespace}.{name}")
+    }
+}
+
+fn invalid(at: &str, message: &str) -> SchemaError {
+    SchemaError {
+        pointer: at.to_owned(),
+        message: message.to_owned(),
+    }
+}
+
+fn one_of<T: AsRef<str>>(names: &[T]) -> String {
+    let names: Vec<_> = names
+        .iter()
+        .map(|name| format!("`{}`", name.as_ref()))
+        .collect();
+    names.join(", ")
+}
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(number) if number.is_f64() => "floating point number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
@@ -0,0 +1,498 @@
+//! Validation of bincode and postcard payloads.
+//!
+//! Binary formats carry no field names, so they cannot be parsed without
+//! knowing the type. [`decode`] walks the bytes as the [`Descriptor`] of the
+//! type says and builds the equivalent JSON tree, which then goes through
+//! the usual validation. Errors are located with pointers whether they come
+//! from malformed bytes or from the type.
+//!
+//! ```
+//! use serde::Deserialize;
+//! use serdify::binary::{self, Format};
+//!
+//! #[derive(Debug, Deserialize)]
+//! struct Sensor {
+//!     id: u16,
+//!     label: String,
+//! }
+//!
+//! // postcard: `id` as a varint, `label` as a length-prefixed string.
+//! let sensor = binary::from_slice::<Sensor>(&[0xac, 0x02, 2, b'o', b'k'], Format::Postcard).unwrap();
+//! assert_eq!((sensor.id, sensor.label.as_str()), (300, "ok"));
+//!
+//! let error = binary::from_slice::<Sensor>(&[0xac, 0x02, 5, b'o', b'k'], Format::Postcard).unwrap_err();
+//! assert_eq!(error.invalid_params[0].pointer, "#/label");
+//! assert_eq!(error.invalid_params[0].code, serdify::code::BINARY_TRUNCATED);
+//! ```
+//!
+//! Integers beyond 64 bits do not fit in JSON numbers and are decoded as
+//! strings of digits. Map keys that are not strings become member names as
+//! the [`KeyPolicy`] in effect says:
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//!
+//! use serdify::binary::{self, Format};
+//! use serdify::config::{self, KeyPolicy};
+//!
+//! // postcard: a map of two entries, with keys `1` and `2`.
+//! let bytes = [2, 1, 10, 2, 20];
+//! let map = binary::from_slice::<BTreeMap<u8, u8>>(&bytes, Format::Postcard).unwrap();
+//! assert_eq!(map[&2], 20);
+//!
+//! let _scope = config::scoped(|config| config.keys = KeyPolicy::Error);
+//! let error = binary::from_slice::<BTreeMap<u8, u8>>(&bytes, Format::Postcard).unwrap_err();
+//! assert_eq!(error.invalid_params.len(), 2);
+//! assert_eq!(error.invalid_params[0].pointer, "#");
+//! assert_eq!(error.invalid_params[0].code, serdify::code::KEY_TYPE);
+//! ```
+
+use serde::Deserialize;
+use serde_json::{Map, Number, Value};
+
+use crate::config::{self, KeyPolicy};
+use crate::descriptor::{Container, Describe, Descriptor, Fields, Shape};
+use crate::{Error, ExpectedOrActual, InvalidParam, Result, code, from_value, pointer};
+
+/// Nesting limit while decoding, guarding against recursive types.
+const MAX_DEPTH: usize = 128;
+
+/// Binary encoding of a payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Format {
+    /// bincode with fixed-width little-endian integers and 64-bit lengths:
+    /// the default of bincode 1 and the legacy configuration of bincode 2.
+    Bincode,
+    /// postcard, with varint integers and lengths.
+    Postcard,
+}
+
+/// Decodes `bytes` encoded in `format` into the JSON tree of the type
+/// described by `descriptor`.
+pub fn decode(bytes: &[u8], descriptor: &Descriptor, format: Format) -> Result<Value> {
+    decode_parts(bytes, descriptor, format).and_then(|(value, findings)| {
+        if findings.is_empty() {
+            Result::Ok(value)
+        } else {
+            Result::Err(Error::new(findings))
+        }
+    })
+}
+
+/// Like [`decode`], returning the keys reported under
+/// [`KeyPolicy::Error`] alongside the tree left without them.
+fn decode_parts(
+    bytes: &[u8],
+    descriptor: &Descriptor,
+    format: Format,
+) -> Result<(Value, Vec<InvalidParam>)> {
+    let mut decoder = Decoder {
+        bytes,
+        index: 0,
+        format,
+        descriptor,
+        depth: 0,
+        keys: config::current().keys,
+        findings: Vec::new(),
+    };
+    let value = match decoder.value("#", &descriptor.root) {
+        Ok(value) => value,
+        Err(param) => return Result::Err(Error::new(vec![*param])),
+    };
+    if decoder.index < bytes.len() {
+        let param = InvalidParam::new(
+            "#",
+            code::BINARY_TRAILING,
+            format!(
+                "Found {} bytes after the end of the payload",
+                bytes.len() - decoder.index
+            ),
+        );
+        return Result::Err(Error::new(vec![param]));
+    }
+    Result::Ok((value, decoder.findings))
+}
+
+/// Decodes `bytes` encoded in `format` and deserializes `T` from them,
+/// collecting every validation error.
+///
+/// The descriptor of `T` is traced on every call; use [`decode`] with a
+/// descriptor kept around when decoding many payloads.
+pub fn from_slice<T>(bytes: &[u8], format: Format) -> Result<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    decode_parts(bytes, &T::serdify_descriptor(), format).and_then(|(value, findings)| {
+        if findings.is_empty() {
+            return from_value(&value);
+        }
+        let mut error = from_value::<T>(&value)
+            .err()
+            .unwrap_or_else(|| Error::new(Vec::new()));
+        error.invalid_params.splice(0..0, findings);
+        Result::Err(error)
+    })
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    index: usize,
+    format: Format,
+    descriptor: &'a Descriptor,
+    depth: usize,
+    keys: KeyPolicy,
+    /// Keys reported under [`KeyPolicy::Error`].
+    findings: Vec<InvalidParam>,
+}
+
+type Decoded<T> = std::result::Result<T, Box<InvalidParam>>;
+
+impl<'a> Decoder<'a> {
+    fn invalid(&self, at: &str, expected: &str, reason: String) -> Box<InvalidParam> {
+        let param = InvalidParam::new(at, code::BINARY_INVALID, reason)
+            .expected(ExpectedOrActual::new(expected, "binary"))
+            .actual(ExpectedOrActual::new("bytes", "binary"));
+        Box::new(param)
+    }
+
+    fn take(&mut self, at: &str, len: usize) -> Decoded<&'a [u8]> {
+        let remaining = self.bytes.len() - self.index;
+        if len > remaining {
+            return Err(Box::new(InvalidParam::new(
+                at,
+                code::BINARY_TRUNCATED,
+                format!(
+                    "Expected {len} more bytes at offset {}, found {remaining}",
+                    self.index
+                ),
+            )));
+        }
+        let bytes = &self.bytes[self.index..self.index + len];
+        self.index += len;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self, at: &str) -> Decoded<u8> {
+        Ok(self.take(at, 1)?[0])
+    }
+
+    /// Reads a little-endian integer of `bits` bits, zero-extended.
+    fn fixed(&mut self, at: &str, bits: u32) -> Decoded<u128> {
+        let bytes = self.take(at, bits as usize / 8)?;
+        Ok(bytes
+            .iter()
+            .rev()
+            .fold(0, |value, byte| (value << 8) | u128::from(*byte)))
+    }
+
+    /// Reads a LEB128 varint of at most `bits` bits.
+    fn varint(&mut self, at: &str, bits: u32) -> Decoded<u128> {
+        let start = self.index;
+        let mut value = 0u128;
+        for shift in (0..bits.div_ceil(7) * 7).step_by(7) {
+            let byte = self.byte(at)?;
+            value |= u128::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                if bits < 128 && value >> bits != 0 {
+                    break;
+                }
+                return Ok(value);
+            }
+        }
+        Err(self.invalid(
+            at,
+            "varint",
+            format!("Varint at offset {start} overflows {bits} bits"),
+        ))
+    }
+
+    fn unsigned(&mut self, at: &str, bits: u32) -> Decoded<u128> {
+        match self.format {
+            _ if bits == 8 => self.byte(at).map(u128::from),
+            Format::Bincode => self.fixed(at, bits),
+            Format::Postcard => self.varint(at, bits),
+        }
+    }
+
+    fn signed(&mut self, at: &str, bits: u32) -> Decoded<i128> {
+        match self.format {
+            _ if bits == 8 => self.byte(at).map(|byte| i128::from(byte as i8)),
+            Format::Bincode => {
+                // Sign-extend from `bits` bits.
+                let shift = 128 - bits;
+                Ok(((self.fixed(at, bits)? << shift) as i128) >> shift)
+            }
+            Format::Postcard => {
+                let zigzag = self.varint(at, bits)?;
+                Ok((zigzag >> 1) as i128 ^ -((zigzag & 1) as i128))
+            }
+        }
+    }
+
+    /// Reads the length of a sequence, string or map.
+    fn len(&mut self, at: &str) -> Decoded<usize> {
+        let len = match self.format {
+            Format::Bincode => self.fixed(at, 64)?,
+            Format::Postcard => self.varint(at, 64)?,
+        };
+        let remaining = self.bytes.len() - self.index;
+        match usize::try_from(len) {
+            Ok(len) if len <= remaining => Ok(len),
+            _ => Err(Box::new(InvalidParam::new(
+                at,
+                code::BINARY_TRUNCATED,
+                format!("Length {len} exceeds the {remaining} bytes left"),
+            ))),
+        }
+    }
+
+    fn tag(&mut self, at: &str, expected: &str) -> Decoded<bool> {
+        match self.byte(at)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            byte => Err(self.invalid(
+                at,
+                expected,
+                format!("Expected 0 or 1 for a {expected}, found {byte}"),
+            )),
+        }
+    }
+
+    fn string(&mut self, at: &str) -> Decoded<String> {
+        let len = self.len(at)?;
+        let start = self.index;
+        let bytes = self.take(at, len)?;
+        match std::str::from_utf8(bytes) {
+            Ok(string) => Ok(string.to_owned()),
+            Err(err) => Err(self.invalid(
+                at,
+                "string",
+                format!("Invalid UTF-8 at offset {}", start + err.valid_up_to()),
+            )),
+        }
+    }
+
+    fn char(&mut self, at: &str) -> Decoded<Value> {
+        let string = match self.format {
+            Format::Postcard => self.string(at)?,
+            Format::Bincode => {
+                let first = *self.bytes.get(self.index).unwrap_or(&0);
+                let width = match first.leading_ones() {
+                    0 => 1,
+                    width @ 2..=4 => width as usize,
+                    _ => 1,
+                };
+                let start = self.index;
+                let bytes = self.take(at, width)?;
+                std::str::from_utf8(bytes)
+                    .map_err(|_| {
+                        self.invalid(at, "char", format!("Invalid UTF-8 at offset {start}"))
+                    })?
+                    .to_owned()
+            }
+        };
+        if string.chars().count() != 1 {
+            return Err(self.invalid(
+                at,
+                "char",
+                format!("Expected a single character, found {string:?}"),
+            ));
+        }
+        Ok(Value::String(string))
+    }
+
+    fn float(&mut self, at: &str, bits: u32) -> Decoded<Value> {
+        let raw = self.fixed(at, bits)?;
+        let float = match bits {
+            32 => f64::from(f32::from_bits(raw as u32)),
+            _ => f64::from_bits(raw as u64),
+        };
+        Number::from_f64(float).map(Value::Number).ok_or_else(|| {
+            let param = InvalidParam::new(
+                at,
+                code::VALUE_INVALID,
+                format!("{float} cannot be represented in JSON"),
+            )
+            .expected(ExpectedOrActual::new("finite number", "number"))
+            .actual(ExpectedOrActual::new(format!("f{bits}"), "number"));
+            Box::new(param)
+        })
+    }
+
+    fn value(&mut self, at: &str, shape: &Shape) -> Decoded<Value> {
+        if self.depth >= MAX_DEPTH {
+            return Err(self.invalid(at, "value", "Nesting limit exceeded".to_owned()));
+        }
+        self.depth += 1;
+        let value = self.shape(at, shape);
+        self.depth -= 1;
+        value
+    }
+
+    fn shape(&mut self, at: &str, shape: &Shape) -> Decoded<Value> {
+        Ok(match shape {
+            Shape::Any => {
+                return Err(self.invalid(
+                    at,
+                    "value",
+                    "The descriptor leaves the shape of this value open".to_owned(),
+                ));
+            }
+            Shape::Unit => Value::Null,
+            Shape::Bool => Value::Bool(self.tag(at, "bool")?),
+            Shape::I8 => integer(self.signed(at, 8)?),
+            Shape::I16 => integer(self.signed(at, 16)?),
+            Shape::I32 => integer(self.signed(at, 32)?),
+            Shape::I64 => integer(self.signed(at, 64)?),
+            Shape::I128 => integer(self.signed(at, 128)?),
+            Shape::U8 => natural(self.unsigned(at, 8)?),
+            Shape::U16 => natural(self.unsigned(at, 16)?),
+            Shape::U32 => natural(self.unsigned(at, 32)?),
+            Shape::U64 => natural(self.unsigned(at, 64)?),
+            Shape::U128 => natural(self.unsigned(at, 128)?),
+            Shape::F32 => self.float(at, 32)?,
+            Shape::F64 => self.float(at, 64)?,
+            Shape::Char => self.char(at)?,
+            Shape::String => Value::String(self.string(at)?),
+            Shape::Bytes => {
+                let len = self.len(at)?;
+                let bytes = self.take(at, len)?;
+                Value::Array(bytes.iter().map(|byte| Value::from(*byte)).collect())
+            }
+            Shape::Option(inner) => match self.tag(at, "option")? {
+                true => self.value(at, inner)?,
+                false => Value::Null,
+            },
+            Shape::Seq(element) => {
+                let len = self.len(at)?;
+                let mut elements = Vec::with_capacity(len);
+                for index in 0..len {
+                    elements.push(self.value(&format!("{at}/{index}"), element)?);
+                }
+                Value::Array(elements)
+            }
+            Shape::Tuple(elements) => self.tuple(at, elements)?,
+            Shape::Map { key, value } => {
+                let len = self.len(at)?;
+                let mut map = Map::new();
+                for _ in 0..len {
+                    let key = self.value(at, key)?;
+                    let Some(key) = self.key(at, key)? else {
+                        self.value(at, value)?;
+                        continue;
+                    };
+                    let entry = self.value(&pointer::join(at, &key), value)?;
+                    map.insert(key, entry);
+                }
+                Value::Object(map)
+            }
+            Shape::Named(name) => self.named(at, name)?,
+        })
+    }
+
+    /// Returns the member name standing for `key`, a key of the map at
+    /// `at`, or `None` if its entry is left out.
+    fn key(&mut self, at: &str, key: Value) -> Decoded<Option<String>> {
+        let key = match key {
+            Value::String(key) => return Ok(Some(key)),
+            key if self.keys == KeyPolicy::Stringify => return Ok(Some(key.to_string())),
+            key => key,
+        };
+        let actual = ExpectedOrActual::of(&key);
+        let param = InvalidParam::new(
+            at,
+            code::KEY_TYPE,
+            format!("Object keys must be strings, found {}", actual.r#type),
+        )
+        .expected(ExpectedOrActual::new("string", "string"))
+        .actual(actual);
+        if self.keys == KeyPolicy::Reject {
+            return Err(Box::new(param));
+        }
+        self.findings.push(param);
+        Ok(None)
+    }
+
+    fn tuple(&mut self, at: &str, shapes: &[Shape]) -> Decoded<Value> {
+        let mut elements = Vec::with_capacity(shapes.len());
+        for (index, shape) in shapes.iter().enumerate() {
+            elements.push(self.value(&format!("{at}/{index}"), shape)?);
+        }
+        Ok(Value::Array(elements))
+    }
+
+    fn named(&mut self, at: &str, name: &str) -> Decoded<Value> {
+        let descriptor = self.descriptor;
+        match descriptor.types.get(name) {
+            Some(Container::Struct(fields)) => self.fields(at, fields),
+            Some(Container::Enum(variants)) => {
+                let index = match self.format {
+                    Format::Bincode => self.fixed(at, 32)?,
+                    Format::Postcard => self.varint(at, 32)?,
+                };
+                let Some(variant) = usize::try_from(index).ok().and_then(|i| variants.get(i))
+                else {
+                    return Err(Box::new(
+                        InvalidParam::new(
+                            at,
+                            code::VARIANT_UNKNOWN,
+                            format!("{name} has no variant with index {index}"),
+                        )
+                        .expected(ExpectedOrActual::new(name, "enum"))
+                        .actual(ExpectedOrActual::new("variant index", "integer")),
+                    ));
+                };
+                let Some(fields) = &variant.fields else {
+                    return Err(self.invalid(
+                        at,
+                        name,
+                        format!(
+                            "The descriptor has no contents for {name}::{}",
+                            variant.name
+                        ),
+                    ));
+                };
+                if let Fields::Unit = fields {
+                    return Ok(Value::String(variant.name.clone()));
+                }
+                let content = self.fields(&pointer::join(at, &variant.name), fields)?;
+                Ok(Value::Object(Map::from_iter([(
+                    variant.name.clone(),
+                    content,
+                )])))
+            }
+            None => Err(self.invalid(at, name, format!("The descriptor does not describe {name}"))),
+        }
+    }
+
+    fn fields(&mut self, at: &str, fields: &Fields) -> Decoded<Value> {
+        match fields {
+            Fields::Unit => Ok(Value::Null),
+            Fields::Newtype(inner) => self.value(at, inner),
+            Fields::Tuple(shapes) => self.tuple(at, shapes),
+            Fields::Named(fields) => {
+                let mut map = Map::new();
+                for field in fields {
+                    let value = self.value(&pointer::join(at, &field.name), &field.shape)?;
+                    map.insert(field.name.clone(), value);
+                }
+                Ok(Value::Object(map))
+            }
+        }
+    }
+}
+
+fn integer(value: i128) -> Value {
+    match i64::try_from(value) {
+        Ok(value) => Value::from(value),
+        Err(_) => Value::String(value.to_string()),
+    }
+}
+
+fn natural(value: u128) -> Value {
+    match u64::try_from(value) {
+        Ok(value) => Value::from(value),
+        Err(_) => Value::String(value.to_string()),
+    }
+}
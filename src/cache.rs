@@ -0,0 +1,217 @@
+//! Caching of validation outcomes for repeated payloads.
+//!
+//! Clients retrying a request send the same body again, and validating it
+//! again gives the same answer. With a cache set through
+//! [`Options::cache`](crate::Options::cache), the `*_cached` methods of
+//! [`Options`](crate::Options) look the exact bytes up first and return a
+//! clone of the earlier [`Outcome`](crate::Outcome) on a hit.
+//!
+//! Entries are keyed by the SHA-256 of the bytes, the target type with a
+//! fingerprint of its schema, the configuration of the options and the
+//! [`Config`] in effect, so options built differently from a shared base
+//! and calls under a [scoped](crate::config::scoped) override never see
+//! each other's results. Keys and entries only make sense within the
+//! process that made them: types, option configurations and the
+//! type-erased outcomes themselves do not survive a restart.
+//!
+//! Each lookup is reported to the [observer](crate::Options::observer) as
+//! an [`Event::CacheHit`](crate::observer::Event::CacheHit) or
+//! [`Event::CacheMiss`](crate::observer::Event::CacheMiss), for hit rate
+//! metrics.
+//!
+//! Only payload-dependent work is cached: apply per-request handling such
+//! as [`Disclosure`](crate::Disclosure) to the returned outcome, not before
+//! caching. Outcomes depending on the current time go stale in the cache:
+//! passes such as [`Temporal`](crate::temporal::Temporal) relative to the
+//! current time should not be combined with a cache, and neither should
+//! suppressions with an [expiry](crate::Suppression::until), since an
+//! outcome cached while one held keeps its findings silenced after it
+//! expires.
+//!
+//! ```
+//! use std::sync::mpsc;
+//!
+//! use serdify::cache::Lru;
+//! use serdify::observer::Event;
+//! use serdify::{Options, config};
+//!
+//! let (sender, receiver) = mpsc::channel();
+//! let options = Options::new().cache(Lru::new(1024)).observer(sender);
+//!
+//! let first = options.from_str_cached::<(u8, u8)>("[1, 300]");
+//! let retry = options.from_str_cached::<(u8, u8)>("[1, 300]");
+//! assert_eq!(first, retry);
+//!
+//! let _scope = config::scoped(|config| config.status = 422);
+//! let scoped = options.from_str_cached::<(u8, u8)>("[1, 300]");
+//! assert_eq!(scoped.result.unwrap_err().status, Some(422));
+//!
+//! let lookups: Vec<_> = receiver
+//!     .try_iter()
+//!     .filter(|event| matches!(event, Event::CacheHit { .. } | Event::CacheMiss { .. }))
+//!     .collect();
+//! assert!(matches!(
+//!     &lookups[..],
+//!     [Event::CacheMiss { .. }, Event::CacheHit { .. }, Event::CacheMiss { .. }]
+//! ));
+//! ```
+
+use std::any::{Any, TypeId};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use serde::Deserialize;
+
+use crate::config::{self, Config};
+use crate::{descriptor, digest};
+
+/// A cached outcome, type-erased so one cache serves every target type.
+pub type Entry = Arc<dyn Any + Send + Sync>;
+
+/// Identifies a payload validated as a given type with given options.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Key {
+    digest: String,
+    type_id: TypeId,
+    type_name: &'static str,
+    schema: String,
+    generation: u64,
+    config: Arc<Config>,
+}
+
+impl Key {
+    pub(crate) fn new<T>(digest: String, generation: u64) -> Self
+    where
+        T: for<'de> Deserialize<'de> + 'static,
+    {
+        Self {
+            digest,
+            type_id: TypeId::of::<T>(),
+            type_name: std::any::type_name::<T>(),
+            schema: schema::<T>(),
+            generation,
+            config: config::current(),
+        }
+    }
+
+    /// SHA-256 of the payload bytes, as lowercase hex.
+    pub fn digest(&self) -> &str {
+        &self.digest
+    }
+
+    /// Name of the target type, as given by [`std::any::type_name`].
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// SHA-256 of the [`descriptor`] of the target type,
+    /// as lowercase hex. It changes with the fields, variants and shapes
+    /// the type accepts.
+    pub fn schema(&self) -> &str {
+        &self.schema
+    }
+}
+
+/// Returns the schema fingerprint of `T`, computed once per type.
+fn schema<T>() -> String
+where
+    T: for<'de> Deserialize<'de> + 'static,
+{
+    static SCHEMAS: OnceLock<Mutex<HashMap<TypeId, String>>> = OnceLock::new();
+    let mut schemas = SCHEMAS
+        .get_or_init(Mutex::default)
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+    schemas
+        .entry(TypeId::of::<T>())
+        .or_insert_with(|| {
+            let descriptor = serde_json::to_vec(&descriptor::describe::<T>()).unwrap_or_default();
+            digest::sha256(&descriptor)
+        })
+        .clone()
+}
+
+/// Storage for cached outcomes.
+///
+/// Implement it to bound memory in a different way than [`Lru`]. Entries
+/// are type-erased values living in this process, so they cannot be
+/// persisted or sent to other processes.
+pub trait Cache: Send + Sync {
+    /// Returns the entry stored under `key`, if any.
+    fn get(&self, key: &Key) -> Option<Entry>;
+
+    /// Stores `entry` under `key`.
+    fn insert(&self, key: Key, entry: Entry);
+}
+
+/// In-memory cache evicting the least recently used entry once full.
+pub struct Lru {
+    capacity: usize,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    tick: u64,
+    entries: HashMap<Key, (u64, Entry)>,
+    recency: BTreeMap<u64, Key>,
+}
+
+impl Lru {
+    /// Creates a cache holding at most `capacity` outcomes.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::default(),
+        }
+    }
+}
+
+impl State {
+    fn touch(&mut self, key: &Key) -> Option<Entry> {
+        let (used, entry) = self.entries.get_mut(key)?;
+        self.recency.remove(used);
+        self.tick += 1;
+        *used = self.tick;
+        self.recency.insert(self.tick, key.clone());
+        Some(entry.clone())
+    }
+}
+
+impl Cache for Lru {
+    fn get(&self, key: &Key) -> Option<Entry> {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        state.touch(key)
+    }
+
+    fn insert(&self, key: Key, entry: Entry) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some((used, _)) = state.entries.remove(&key) {
+            state.recency.remove(&used);
+        }
+        while state.entries.len() >= self.capacity {
+            let Some((_, oldest)) = state.recency.pop_first() else {
+                break;
+            };
+            state.entries.remove(&oldest);
+        }
+        state.tick += 1;
+        let tick = state.tick;
+        state.recency.insert(tick, key.clone());
+        state.entries.insert(key, (tick, entry));
+    }
+}
+
+impl fmt::Debug for Lru {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let len = self.state.lock().map_or(0, |state| state.entries.len());
+        f.debug_struct("Lru")
+            .field("capacity", &self.capacity)
+            .field("len", &len)
+            .finish()
+    }
+}
@@ -0,0 +1,320 @@
+//! The JSON Canonicalization Scheme of
+//! [RFC 8785](https://datatracker.ietf.org/doc/html/rfc8785).
+//!
+//! Signatures over JSON only verify if signer and verifier serialize the
+//! payload byte for byte the same way. [`to_string`] produces the canonical
+//! serialization of a value, and [`verify`] checks that received text is
+//! already canonical, reporting every deviation with a pointer: unsorted
+//! keys, non-canonical numbers and string escapes, and whitespace.
+//!
+//! ```
+//! use serde_json::json;
+//! use serdify::{canonical, code};
+//!
+//! assert_eq!(canonical::to_string(&json!({"b": 1.50, "a": 1e21})), r#"{"a":1e+21,"b":1.5}"#);
+//!
+//! let error = canonical::verify(r#"{"b":1.50,"a":"\u0041"}"#).unwrap_err();
+//! let found: Vec<_> = error
+//!     .invalid_params
+//!     .iter()
+//!     .map(|p| (p.pointer.as_str(), p.code.as_str()))
+//!     .collect();
+//! assert_eq!(
+//!     found,
+//!     [
+//!         ("#", code::CANONICAL_ORDER),
+//!         ("#/b", code::CANONICAL_NUMBER),
+//!         ("#/a", code::CANONICAL_STRING),
+//!     ]
+//! );
+//! ```
+
+use std::cmp::Ordering;
+use std::fmt::Write;
+
+use serde_json::Value;
+
+use crate::{Error, ExpectedOrActual, InvalidParam, Result, code, pointer};
+
+/// Serializes `value` in canonical form.
+pub fn to_string(value: &Value) -> String {
+    let mut out = String::new();
+    write_value(&mut out, value);
+    out
+}
+
+/// Checks that `json` is in canonical form, reporting every deviation.
+pub fn verify(json: &str) -> Result<()> {
+    if let Err(err) = serde_json::from_str::<Value>(json) {
+        return Result::Err(Error::syntax(&err));
+    }
+    let mut verifier = Verifier {
+        json: json.as_bytes(),
+        index: 0,
+        whitespace: false,
+        findings: Vec::new(),
+    };
+    verifier.value("#");
+    if verifier.findings.is_empty() {
+        Result::Ok(())
+    } else {
+        Result::Err(Error::new(verifier.findings))
+    }
+}
+
+fn write_value(out: &mut String, value: &Value) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(bool) => out.push_str(if *bool { "true" } else { "false" }),
+        Value::Number(number) => out.push_str(&number_to_string(number.as_f64().unwrap_or(0.0))),
+        Value::String(string) => write_string(out, string),
+        Value::Array(array) => {
+            out.push('[');
+            for (index, value) in array.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_value(out, value);
+            }
+            out.push(']');
+        }
+        Value::Object(object) => {
+            let mut members: Vec<_> = object.iter().collect();
+            members.sort_by(|(a, _), (b, _)| compare_keys(a, b));
+            out.push('{');
+            for (index, (key, value)) in members.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_string(out, key);
+                out.push(':');
+                write_value(out, value);
+            }
+            out.push('}');
+        }
+    }
+}
+
+/// Orders keys by their UTF-16 code units, as RFC 8785, section 3.2.3
+/// requires.
+fn compare_keys(a: &str, b: &str) -> Ordering {
+    a.encode_utf16().cmp(b.encode_utf16())
+}
+
+fn write_string(out: &mut String, string: &str) {
+    out.push('"');
+    for char in string.chars() {
+        match char {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\0'..='\u{1f}' => {
+                let _ = write!(out, "\\u{:04x}", char as u32);
+            }
+            char => out.push(char),
+        }
+    }
+    out.push('"');
+}
+
+/// Formats a number like ECMAScript's `Number.prototype.toString`, as
+/// RFC 8785, section 3.2.2.3 requires.
+fn number_to_string(number: f64) -> String {
+    if number == 0.0 {
+        return "0".to_owned();
+    }
+    // `{:e}` yields the shortest digits that round-trip, such as `1.5e-7`.
+    let scientific = format!("{:e}", number.abs());
+    let (mantissa, exponent) = scientific.split_once('e').expect("`{:e}` has an exponent");
+    let digits: String = mantissa.chars().filter(char::is_ascii_digit).collect();
+    let exponent: i32 = exponent.parse().expect("exponent is an integer");
+    let (k, n) = (digits.len() as i32, exponent + 1);
+    let sign = if number < 0.0 { "-" } else { "" };
+    let body = if k <= n && n <= 21 {
+        format!("{digits}{}", "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{digits}", "0".repeat(-n as usize))
+    } else {
+        let exponent = n - 1;
+        let exponent_sign = if exponent < 0 { '-' } else { '+' };
+        let (first, rest) = digits.split_at(1);
+        let fraction = if rest.is_empty() {
+            String::new()
+        } else {
+            format!(".{rest}")
+        };
+        format!("{first}{fraction}e{exponent_sign}{}", exponent.abs())
+    };
+    format!("{sign}{body}")
+}
+
+/// Walks JSON text already known to be valid, comparing each token with
+/// its canonical form.
+struct Verifier<'a> {
+    json: &'a [u8],
+    index: usize,
+    whitespace: bool,
+    findings: Vec<InvalidParam>,
+}
+
+impl Verifier<'_> {
+    fn skip_whitespace(&mut self, at: &str) {
+        let start = self.index;
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.json.get(self.index) {
+            self.index += 1;
+        }
+        if self.index > start && !self.whitespace {
+            self.whitespace = true;
+            self.findings.push(
+                InvalidParam::new(
+                    at,
+                    code::CANONICAL_WHITESPACE,
+                    format!("Whitespace at byte {start}; canonical JSON has none between tokens"),
+                )
+                .expected(ExpectedOrActual::new("canonical", "json"))
+                .actual(ExpectedOrActual::new("whitespace", "json")),
+            );
+        }
+    }
+
+    fn value(&mut self, at: &str) {
+        self.skip_whitespace(at);
+        match self.json[self.index] {
+            b'{' => self.object(at),
+            b'[' => self.array(at),
+            b'"' => {
+                self.string(at);
+            }
+            b't' => self.index += 4,
+            b'f' => self.index += 5,
+            b'n' => self.index += 4,
+            _ => self.number(at),
+        }
+        self.skip_whitespace(at);
+    }
+
+    fn object(&mut self, at: &str) {
+        self.index += 1;
+        self.skip_whitespace(at);
+        // The key order is reported ahead of the members' own findings.
+        let first = self.findings.len();
+        let mut keys = Vec::new();
+        if self.json[self.index] != b'}' {
+            loop {
+                self.skip_whitespace(at);
+                let start = self.index;
+                let key = self.token_string();
+                let member_at = pointer::join(at, &key);
+                self.index = start;
+                self.string(&member_at);
+                self.skip_whitespace(&member_at);
+                self.index += 1; // `:`
+                self.value(&member_at);
+                keys.push(key);
+                if self.json[self.index] == b'}' {
+                    break;
+                }
+                self.index += 1; // `,`
+            }
+        }
+        self.index += 1;
+        let sorted = keys
+            .windows(2)
+            .all(|pair| compare_keys(&pair[0], &pair[1]) != Ordering::Greater);
+        if !sorted {
+            let mut expected = keys.clone();
+            expected.sort_by(|a, b| compare_keys(a, b));
+            self.findings.insert(
+                first,
+                InvalidParam::new(
+                    at,
+                    code::CANONICAL_ORDER,
+                    format!(
+                        "Keys must be sorted by UTF-16 code units: expected {}, found {}",
+                        expected.join(", "),
+                        keys.join(", ")
+                    ),
+                )
+                .expected(ExpectedOrActual::new("sorted keys", "object"))
+                .actual(ExpectedOrActual::new("unsorted keys", "object")),
+            );
+        }
+    }
+
+    fn array(&mut self, at: &str) {
+        self.index += 1;
+        self.skip_whitespace(at);
+        if self.json[self.index] == b']' {
+            self.index += 1;
+            return;
+        }
+        for index in 0.. {
+            self.value(&format!("{at}/{index}"));
+            let byte = self.json[self.index];
+            self.index += 1;
+            if byte == b']' {
+                break;
+            }
+        }
+    }
+
+    /// Returns the raw text of the string token at the current position.
+    fn raw_string(&mut self) -> &str {
+        let start = self.index;
+        self.index += 1;
+        while self.json[self.index] != b'"' {
+            self.index += if self.json[self.index] == b'\\' { 2 } else { 1 };
+        }
+        self.index += 1;
+        std::str::from_utf8(&self.json[start..self.index]).expect("valid JSON is UTF-8")
+    }
+
+    fn token_string(&mut self) -> String {
+        serde_json::from_str(self.raw_string()).expect("valid JSON strings decode")
+    }
+
+    fn string(&mut self, at: &str) {
+        let raw = self.raw_string().to_owned();
+        let decoded: String = serde_json::from_str(&raw).expect("valid JSON strings decode");
+        let mut canonical = String::new();
+        write_string(&mut canonical, &decoded);
+        if raw != canonical {
+            self.findings.push(
+                InvalidParam::new(
+                    at,
+                    code::CANONICAL_STRING,
+                    format!("Expected the string to be written {canonical}, found {raw}"),
+                )
+                .expected(ExpectedOrActual::new("canonical", "string"))
+                .actual(ExpectedOrActual::new("string", "string")),
+            );
+        }
+    }
+
+    fn number(&mut self, at: &str) {
+        let start = self.index;
+        while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') = self.json.get(self.index) {
+            self.index += 1;
+        }
+        let raw = std::str::from_utf8(&self.json[start..self.index]).expect("numbers are ASCII");
+        let number: f64 = raw.parse().unwrap_or(f64::INFINITY);
+        let canonical = number_to_string(number);
+        if raw != canonical {
+            self.findings.push(
+                InvalidParam::new(
+                    at,
+                    code::CANONICAL_NUMBER,
+                    format!("Expected the number to be written {canonical}, found {raw}"),
+                )
+                .expected(ExpectedOrActual::new("canonical", "number"))
+                .actual(ExpectedOrActual::new("f64", "number")),
+            );
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Stable identifiers for every kind of finding serdify reports.
+//!
+//! Codes are carried by [`InvalidParam::code`](crate::InvalidParam::code) and
+//! are meant for machines: policies, filters and client-side mappings should
+//! match on them rather than on the human-readable reason.
+
+/// The JSON type of a value does not fit the target type.
+pub const TYPE_MISMATCH: &str = "type.mismatch";
+/// A number does not fit the range of the target type.
+pub const RANGE_OVERFLOW: &str = "range.overflow";
+/// A required field is absent.
+pub const FIELD_MISSING: &str = "field.missing";
+/// A field is not known to a type that denies unknown fields.
+pub const FIELD_UNKNOWN: &str = "field.unknown";
+/// A field appears more than once.
+pub const FIELD_DUPLICATE: &str = "field.duplicate";
+/// A field appears before one it must follow, see
+/// [`KeyOrder`](crate::KeyOrder).
+pub const FIELD_ORDER: &str = "field.order";
+/// An object key of a format other than JSON is not a string, see
+/// [`KeyPolicy`](crate::config::KeyPolicy).
+pub const KEY_TYPE: &str = "key.type";
+/// A string does not name any variant of the target enum.
+pub const VARIANT_UNKNOWN: &str = "variant.unknown";
+/// An array or object has the wrong number of elements.
+pub const LENGTH_INVALID: &str = "length.invalid";
+/// A value has the right type but is rejected by the target type.
+pub const VALUE_INVALID: &str = "value.invalid";
+/// A `Deserialize` implementation rejected the value with a custom message.
+pub const CUSTOM: &str = "custom";
+/// The payload is not valid JSON.
+pub const SYNTAX: &str = "syntax";
+/// A valid JSON document is followed by more data.
+pub const SYNTAX_TRAILING: &str = "syntax.trailing";
+/// A number has more digits than allowed by
+/// [`NumberLimits`](crate::NumberLimits).
+pub const NUMBER_DIGITS: &str = "number.digits";
+/// The exponent of a number exceeds the limit of
+/// [`NumberLimits`](crate::NumberLimits).
+pub const NUMBER_EXPONENT: &str = "number.exponent";
+/// A string contains a script injection vector such as `<script>`.
+pub const SECURITY_SCRIPT: &str = "security.script";
+/// A string contains SQL meta-sequences such as `' OR 1=1 --`.
+pub const SECURITY_SQL: &str = "security.sql";
+/// A string contains an extremely long token.
+pub const SECURITY_TOKEN: &str = "security.token";
+/// A JSON-LD document lacks the expected `@context`.
+pub const JSONLD_CONTEXT: &str = "jsonld.context";
+/// A JSON-LD `@type` is malformed or uses an undefined prefix.
+pub const JSONLD_TYPE: &str = "jsonld.type";
+/// A string contains an escaped lone UTF-16 surrogate.
+pub const STRICT_SURROGATE: &str = "strict.surrogate";
+/// An integer is too large to be represented exactly by most parsers.
+pub const STRICT_NUMBER: &str = "strict.number";
+/// Object keys are not sorted as canonical JSON requires.
+pub const CANONICAL_ORDER: &str = "canonical.order";
+/// A number is not written in its canonical form.
+pub const CANONICAL_NUMBER: &str = "canonical.number";
+/// A string is not escaped in its canonical form.
+pub const CANONICAL_STRING: &str = "canonical.string";
+/// Canonical JSON has whitespace between tokens.
+pub const CANONICAL_WHITESPACE: &str = "canonical.whitespace";
+/// A binary payload ends before the value it encodes.
+pub const BINARY_TRUNCATED: &str = "binary.truncated";
+/// A binary payload holds bytes its type cannot take, such as a boolean
+/// other than 0 or 1.
+pub const BINARY_INVALID: &str = "binary.invalid";
+/// A binary payload continues after the value it encodes.
+pub const BINARY_TRAILING: &str = "binary.trailing";
+/// A string is longer than the grapheme limit of its field.
+pub const TEXT_LENGTH: &str = "text.length";
+/// A string restricted to a single line contains a line break.
+pub const TEXT_MULTILINE: &str = "text.multiline";
+/// A string restricted to printable text contains a control or invisible
+/// formatting character.
+pub const TEXT_UNPRINTABLE: &str = "text.unprintable";
+/// A boolean was written as a string or number, such as `"yes"` or `1`,
+/// and accepted under [`Options::coerce_bools`](crate::Options::coerce_bools).
+pub const COERCED_BOOL: &str = "coerced.bool";
+/// An enum variant was spelled with another case or word separators and
+/// accepted under [`Options::lenient_variants`](crate::Options::lenient_variants).
+pub const COERCED_VARIANT: &str = "coerced.variant";
+/// A flag set contains a flag its field does not allow.
+pub const FLAG_UNKNOWN: &str = "flag.unknown";
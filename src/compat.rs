@@ -0,0 +1,298 @@
+//! Backward compatibility between two versions of a type.
+//!
+//! [`check`] compares the [`Descriptor`]s of the old and new version of a
+//! payload type and lists every change that makes the new version reject
+//! payloads the old one accepted: removed fields and variants, fields that
+//! became required, narrowed numeric ranges and changed types. Run it in CI
+//! against the descriptor of the released version to gate releases.
+//!
+//! ```
+//! use serdify::compat::{self, Kind};
+//! use serdify::descriptor::Describe;
+//!
+//! mod v1 {
+//!     #[derive(serde::Deserialize)]
+//!     pub struct Order {
+//!         pub id: u64,
+//!         pub quantity: u32,
+//!         pub note: Option<String>,
+//!     }
+//! }
+//!
+//! mod v2 {
+//!     #[derive(serde::Deserialize)]
+//!     pub struct Order {
+//!         pub id: u64,
+//!         pub quantity: u16,
+//!         pub coupon: String,
+//!     }
+//! }
+//!
+//! let changes = compat::check(&v1::Order::serdify_descriptor(), &v2::Order::serdify_descriptor());
+//! let found: Vec<_> = changes.iter().map(|c| (c.pointer.as_str(), c.kind)).collect();
+//! assert_eq!(
+//!     found,
+//!     [
+//!         ("#/quantity", Kind::RangeNarrowed),
+//!         ("#/note", Kind::FieldRemoved),
+//!         ("#/coupon", Kind::FieldRequired),
+//!     ]
+//! );
+//! ```
+
+use std::collections::HashSet;
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::descriptor::{Container, Descriptor, Field, Fields, Shape};
+use crate::pointer;
+
+/// Nesting limit for newtype structs unwrapped while comparing.
+const MAX_NEWTYPES: usize = 64;
+
+/// A change rejecting payloads the old version accepted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BreakingChange {
+    /// Location of the change in the payload. `*` stands for any element of
+    /// a sequence or value of a map.
+    pub pointer: String,
+    pub kind: Kind,
+    /// Human-readable explanation of the change.
+    pub reason: String,
+}
+
+/// Category of a [`BreakingChange`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Kind {
+    /// A field of the old version no longer exists.
+    FieldRemoved,
+    /// A field is required by the new version only.
+    FieldRequired,
+    /// A number type accepts fewer values.
+    RangeNarrowed,
+    /// A value has an incompatible type, or no longer accepts `null`.
+    TypeChanged,
+    /// A variant of the old version no longer exists.
+    VariantRemoved,
+}
+
+impl fmt::Display for BreakingChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.pointer, self.reason)
+    }
+}
+
+/// Lists the changes from `old` to `new` that break payloads `old` accepted.
+pub fn check(old: &Descriptor, new: &Descriptor) -> Vec<BreakingChange> {
+    let mut checker = Checker {
+        old,
+        new,
+        seen: HashSet::new(),
+        changes: Vec::new(),
+    };
+    checker.shape("#", &old.root, &new.root);
+    checker.changes
+}
+
+struct Checker<'a> {
+    old: &'a Descriptor,
+    new: &'a Descriptor,
+    /// Pairs of named types already compared, ending recursion.
+    seen: HashSet<(&'a str, &'a str)>,
+    changes: Vec<BreakingChange>,
+}
+
+/// Values of a number type, for comparing ranges.
+enum Range {
+    Integer(i128, u128),
+    Float(u8),
+}
+
+impl Range {
+    fn of(shape: &Shape) -> Option<Self> {
+        Some(match shape {
+            Shape::I8 => Range::Integer(i8::MIN.into(), i8::MAX as u128),
+            Shape::I16 => Range::Integer(i16::MIN.into(), i16::MAX as u128),
+            Shape::I32 => Range::Integer(i32::MIN.into(), i32::MAX as u128),
+            Shape::I64 => Range::Integer(i64::MIN.into(), i64::MAX as u128),
+            Shape::I128 => Range::Integer(i128::MIN, i128::MAX as u128),
+            Shape::U8 => Range::Integer(0, u8::MAX.into()),
+            Shape::U16 => Range::Integer(0, u16::MAX.into()),
+            Shape::U32 => Range::Integer(0, u32::MAX.into()),
+            Shape::U64 => Range::Integer(0, u64::MAX.into()),
+            Shape::U128 => Range::Integer(0, u128::MAX),
+            Shape::F32 => Range::Float(32),
+            Shape::F64 => Range::Float(64),
+            _ => return None,
+        })
+    }
+
+    /// Returns `true` if every value of `self` fits in `other`.
+    fn fits(&self, other: &Range) -> bool {
+        match (self, other) {
+            (Range::Integer(min, max), Range::Integer(new_min, new_max)) => {
+                new_min <= min && new_max >= max
+            }
+            (Range::Integer(..), Range::Float(_)) => true,
+            (Range::Float(bits), Range::Float(new_bits)) => new_bits >= bits,
+            (Range::Float(_), Range::Integer(..)) => false,
+        }
+    }
+}
+
+impl<'a> Checker<'a> {
+    fn push(&mut self, at: &str, kind: Kind, reason: String) {
+        self.changes.push(BreakingChange {
+            pointer: at.to_owned(),
+            kind,
+            reason,
+        });
+    }
+
+    fn shape(&mut self, at: &str, old: &'a Shape, new: &'a Shape) {
+        let old = transparent(self.old, old);
+        let new = transparent(self.new, new);
+        match (old, new) {
+            (_, Shape::Any) => {}
+            (Shape::Option(old), Shape::Option(new)) => self.shape(at, old, new),
+            (old, Shape::Option(new)) => self.shape(at, old, new),
+            (Shape::Option(old), new) => {
+                self.push(
+                    at,
+                    Kind::TypeChanged,
+                    format!("`null` is no longer accepted as {new}"),
+                );
+                self.shape(at, old, new);
+            }
+            (Shape::Seq(old), Shape::Seq(new)) => self.shape(&format!("{at}/*"), old, new),
+            (Shape::Tuple(old), Shape::Tuple(new)) if old.len() == new.len() => {
+                self.elements(at, old, new);
+            }
+            (Shape::Map { key, value }, Shape::Map { key: k, value: v }) => {
+                self.shape(at, key, k);
+                self.shape(&format!("{at}/*"), value, v);
+            }
+            (Shape::Named(old), Shape::Named(new)) => self.named(at, old, new),
+            (Shape::Char, Shape::String) => {}
+            (old, new) if old == new => {}
+            (old, new) => match (Range::of(old), Range::of(new)) {
+                (Some(old_range), Some(new_range)) if old_range.fits(&new_range) => {}
+                (Some(_), Some(_)) => self.push(
+                    at,
+                    Kind::RangeNarrowed,
+                    format!("{new} does not hold every {old}"),
+                ),
+                _ => self.push(
+                    at,
+                    Kind::TypeChanged,
+                    format!("Expected {old} before, {new} now"),
+                ),
+            },
+        }
+    }
+
+    fn elements(&mut self, at: &str, old: &'a [Shape], new: &'a [Shape]) {
+        for (index, (old, new)) in old.iter().zip(new).enumerate() {
+            self.shape(&format!("{at}/{index}"), old, new);
+        }
+    }
+
+    fn named(&mut self, at: &str, old_name: &'a str, new_name: &'a str) {
+        if !self.seen.insert((old_name, new_name)) {
+            return;
+        }
+        let (Some(old), Some(new)) = (self.old.types.get(old_name), self.new.types.get(new_name))
+        else {
+            return;
+        };
+        match (old, new) {
+            (Container::Struct(old), Container::Struct(new)) => self.fields(at, old, new),
+            (Container::Enum(old), Container::Enum(new)) => {
+                for variant in old {
+                    let Some(replacement) = new.iter().find(|v| v.name == variant.name) else {
+                        self.push(
+                            at,
+                            Kind::VariantRemoved,
+                            format!("Variant `{}` of {old_name} was removed", variant.name),
+                        );
+                        continue;
+                    };
+                    if let (Some(old), Some(new)) = (&variant.fields, &replacement.fields) {
+                        // Externally tagged, the contents sit under the variant name.
+                        self.fields(&pointer::join(at, &variant.name), old, new);
+                    }
+                }
+            }
+            _ => self.push(
+                at,
+                Kind::TypeChanged,
+                format!("Expected {old_name} before, {new_name} now"),
+            ),
+        }
+    }
+
+    fn fields(&mut self, at: &str, old: &'a Fields, new: &'a Fields) {
+        match (old, new) {
+            (Fields::Unit, Fields::Unit) => {}
+            (Fields::Newtype(old), Fields::Newtype(new)) => self.shape(at, old, new),
+            (Fields::Tuple(old), Fields::Tuple(new)) if old.len() == new.len() => {
+                self.elements(at, old, new);
+            }
+            (Fields::Named(old), Fields::Named(new)) => self.members(at, old, new),
+            _ => self.push(
+                at,
+                Kind::TypeChanged,
+                "The contents changed shape".to_owned(),
+            ),
+        }
+    }
+
+    fn members(&mut self, at: &str, old: &'a [Field], new: &'a [Field]) {
+        for field in old {
+            let field_at = pointer::join(at, &field.name);
+            match new.iter().find(|f| f.name == field.name) {
+                Some(replacement) => {
+                    self.shape(&field_at, &field.shape, &replacement.shape);
+                    if replacement.required && !field.required {
+                        self.push(
+                            &field_at,
+                            Kind::FieldRequired,
+                            format!("Field `{}` became required", field.name),
+                        );
+                    }
+                }
+                None => self.push(
+                    &field_at,
+                    Kind::FieldRemoved,
+                    format!("Field `{}` was removed", field.name),
+                ),
+            }
+        }
+        for field in new {
+            if field.required && !old.iter().any(|f| f.name == field.name) {
+                self.push(
+                    &pointer::join(at, &field.name),
+                    Kind::FieldRequired,
+                    format!("Field `{}` was added as required", field.name),
+                );
+            }
+        }
+    }
+}
+
+/// Looks through newtype structs, which serialize as their contents.
+fn transparent<'a>(descriptor: &'a Descriptor, mut shape: &'a Shape) -> &'a Shape {
+    for _ in 0..MAX_NEWTYPES {
+        match shape {
+            Shape::Named(name) => match descriptor.types.get(name) {
+                Some(Container::Struct(Fields::Newtype(inner))) => shape = inner,
+                _ => break,
+            },
+            _ => break,
+        }
+    }
+    shape
+}
@@ -0,0 +1,161 @@
+//! Process-wide defaults, with scoped overrides.
+//!
+//! Large codebases call serdify from many places; rather than threading
+//! the same settings through each of them, set them once at startup with
+//! [`configure`]. Tests and special endpoints can then deviate from them
+//! for a while with [`scoped`].
+//!
+//! ```rust,standalone_crate
+//! use serdify::{config, from_str};
+//!
+//! serdify::configure(|config| {
+//!     config.status = 422;
+//!     config.max_errors = Some(2);
+//! });
+//!
+//! let error = from_str::<Vec<u8>>("[300, 301, 302]").unwrap_err();
+//! assert_eq!(error.status, Some(422));
+//! assert_eq!(error.invalid_params.len(), 2);
+//!
+//! {
+//!     let _scope = config::scoped(|config| config.max_errors = None);
+//!     let error = from_str::<Vec<u8>>("[300, 301, 302]").unwrap_err();
+//!     assert_eq!(error.status, Some(422));
+//!     assert_eq!(error.invalid_params.len(), 3);
+//! }
+//!
+//! let error = from_str::<Vec<u8>>("[300, 301, 302]").unwrap_err();
+//! assert_eq!(error.invalid_params.len(), 2);
+//! ```
+//!
+//! There is no locale setting: reasons and details are written in English.
+//! [`Config::title`] can be translated, and clients showing errors to end
+//! users should map the stable [`code`](crate::code) of each invalid
+//! parameter to their own messages.
+
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::sync::{Arc, RwLock};
+
+use crate::error::{STATUS, TITLE};
+
+/// Default of [`Config::max_errors`].
+const MAX_ERRORS: usize = 100;
+
+/// Settings applying to every call that does not say otherwise.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct Config {
+    /// HTTP status code of every [`Error`](crate::Error), 400 by default.
+    pub status: u16,
+    /// Title of every [`Error`](crate::Error), the one text serdify
+    /// produces that can be set in another language.
+    pub title: String,
+    /// Most validation errors collected for one payload, 100 by default, or
+    /// `None` to collect them all. Deserialization stops once the limit is
+    /// reached, bounding the work spent on hopeless payloads; lifting it
+    /// exposes servers to payloads crafted to hold thousands of errors.
+    pub max_errors: Option<usize>,
+    /// What to do with object keys that are not strings in formats other
+    /// than JSON, such as the map keys of bincode and postcard payloads.
+    pub keys: KeyPolicy,
+}
+
+/// How object keys that are not strings, which formats other than JSON may
+/// carry, become member names of the JSON tree that is validated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum KeyPolicy {
+    /// Uses the JSON text of the key as member name: `1`, `true`, `null`
+    /// or `[1,2]`. Types expecting keys of that type parse them back, as
+    /// they do for JSON.
+    #[default]
+    Stringify,
+    /// Reports every such key at the pointer of its object, leaving out its
+    /// entry but validating the rest of the document.
+    Error,
+    /// Rejects the document at the first such key.
+    Reject,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            status: STATUS,
+            title: TITLE.to_owned(),
+            max_errors: Some(MAX_ERRORS),
+            keys: KeyPolicy::default(),
+        }
+    }
+}
+
+/// Configuration set by [`configure`], or `None` for the default one.
+static GLOBAL: RwLock<Option<Arc<Config>>> = RwLock::new(None);
+
+thread_local! {
+    /// Innermost override of the current thread, see [`scoped`].
+    static SCOPED: RefCell<Option<Arc<Config>>> = const { RefCell::new(None) };
+}
+
+/// Changes the process-wide configuration, starting from the current one.
+///
+/// Calls on every thread see the change, except inside a [`scoped`]
+/// override.
+pub fn configure(f: impl FnOnce(&mut Config)) {
+    let mut global = GLOBAL.write().unwrap_or_else(|err| err.into_inner());
+    let mut config = global.as_deref().cloned().unwrap_or_default();
+    f(&mut config);
+    *global = Some(Arc::new(config));
+}
+
+/// Overrides the configuration on the current thread until the returned
+/// guard is dropped, starting from the configuration in effect.
+///
+/// Overrides nest, and only affect the thread creating them: tests running
+/// in parallel do not see each other's. The guard cannot be sent to another
+/// thread, so keep async code between an override and its end on one
+/// thread.
+///
+/// ```
+/// use serdify::{config, from_str};
+///
+/// let _scope = config::scoped(|config| config.title = "Invalid order.".to_owned());
+///
+/// let error = from_str::<u8>("300").unwrap_err();
+/// assert_eq!(error.title, "Invalid order.");
+/// ```
+pub fn scoped(f: impl FnOnce(&mut Config)) -> Scope {
+    let mut config = (*current()).clone();
+    f(&mut config);
+    let previous = SCOPED.with(|scoped| scoped.replace(Some(Arc::new(config))));
+    Scope {
+        previous,
+        _thread: PhantomData,
+    }
+}
+
+/// Returns the configuration in effect on the current thread.
+pub fn current() -> Arc<Config> {
+    if let Some(config) = SCOPED.with(|scoped| scoped.borrow().clone()) {
+        return config;
+    }
+    let global = GLOBAL.read().unwrap_or_else(|err| err.into_inner());
+    global.clone().unwrap_or_default()
+}
+
+/// Guard of an override made by [`scoped`], restoring the configuration
+/// in effect before it when dropped.
+#[must_use = "the override ends when the guard is dropped"]
+#[derive(Debug)]
+pub struct Scope {
+    previous: Option<Arc<Config>>,
+    /// Keeps the guard on the thread whose configuration it restores.
+    _thread: PhantomData<*const ()>,
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        SCOPED.with(|scoped| *scoped.borrow_mut() = previous);
+    }
+}
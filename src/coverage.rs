@@ -0,0 +1,343 @@
+//! Checks that serdify locates every error in payloads of a type.
+//!
+//! serdify reports errors at their exact pointer, except inside shapes that
+//! serde buffers before deserializing them: untagged, internally and
+//! adjacently tagged enums, and structs with `#[serde(flatten)]` fields.
+//! Errors there are still collected, but reported at the pointer of the
+//! whole buffered value with serde's own message.
+//!
+//! [`check`] traces the [`Descriptor`] of a type, then deserializes
+//! generated documents holding a probe value wherever the descriptor cannot
+//! see inside, and lists the locations where the type buffers the probe and
+//! then rejects it. [`assert_serdify_compatible!`](crate::assert_serdify_compatible)
+//! turns those into a test failure.
+//!
+//! ```
+//! use serde::Deserialize;
+//! use serdify::coverage;
+//!
+//! #[derive(Deserialize)]
+//! struct Event {
+//!     id: u64,
+//!     payload: Payload,
+//!     extra: serde_json::Value,
+//! }
+//!
+//! #[derive(Deserialize)]
+//! #[serde(tag = "type")]
+//! enum Payload {
+//!     Click { x: u32, y: u32 },
+//!     Key { code: u8 },
+//! }
+//!
+//! let degradations = coverage::check::<Event>();
+//! assert_eq!(degradations.len(), 1);
+//! assert_eq!(degradations[0].pointer, "#/payload");
+//! ```
+//!
+//! The check is heuristic: it only reaches the variants and fields it can
+//! trace, and buffered shapes that accept the probe, such as flattened
+//! structs whose fields are all optional, or turn it down before buffering
+//! it, such as adjacently tagged enums missing their tag, go unnoticed.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+
+use crate::descriptor::{Container, Descriptor, Fields, Shape, describe};
+use crate::{Result, de, pointer};
+
+/// Key of the probe, and of map entries leading to it.
+const PROBE_KEY: &str = "serdify_probe";
+
+/// A location where errors are reported less precisely than at their exact
+/// pointer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Degradation {
+    /// Location of the buffered value, where errors inside it are reported.
+    pub pointer: String,
+    /// Error the type reported for the probe, showing how errors there read.
+    pub reason: String,
+}
+
+impl fmt::Display for Degradation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.pointer, self.reason)
+    }
+}
+
+/// Lists the locations in payloads of `T` where serdify cannot report
+/// errors at their exact pointer.
+pub fn check<T>() -> Vec<Degradation>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let descriptor = describe::<T>();
+    let mut prober = Prober {
+        descriptor: &descriptor,
+        visiting: Vec::new(),
+    };
+    let mut degradations = Vec::<Degradation>::new();
+    for (at, document) in prober.probes("#", &descriptor.root) {
+        let (Result::Err(error), buffered) = de::from_value_buffered::<T>(&document) else {
+            continue;
+        };
+        // An error about a value the type buffered came out of serde rather
+        // than from serdify following the value down.
+        let found = error.invalid_params.iter().find(|param| {
+            pointer::starts_with(&param.pointer, &at)
+                && buffered
+                    .iter()
+                    .any(|buffer| pointer::starts_with(buffer, &param.pointer))
+        });
+        if let Some(param) = found
+            && !degradations
+                .iter()
+                .any(|known| known.pointer == param.pointer)
+        {
+            degradations.push(Degradation {
+                pointer: param.pointer.clone(),
+                reason: param.reason.clone().unwrap_or_default(),
+            });
+        }
+    }
+    degradations
+}
+
+/// Panics, listing every [`Degradation`], if serdify cannot report every
+/// error in payloads of `T` at its exact pointer. See
+/// [`assert_serdify_compatible!`](crate::assert_serdify_compatible).
+#[track_caller]
+pub fn assert_compatible<T>()
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let degradations = check::<T>();
+    if !degradations.is_empty() {
+        let list: Vec<_> = degradations.iter().map(ToString::to_string).collect();
+        panic!(
+            "serdify cannot locate errors inside {} location(s) of {}:\n- {}",
+            degradations.len(),
+            std::any::type_name::<T>(),
+            list.join("\n- ")
+        );
+    }
+}
+
+/// Asserts that serdify reports every error in payloads of a type at its
+/// exact pointer, for use in tests. The type must implement `Deserialize`,
+/// or the test does not compile; the assertion itself runs when the test
+/// does. See [`coverage`](crate::coverage).
+///
+/// ```should_panic
+/// use serde::Deserialize;
+/// use serdify::assert_serdify_compatible;
+///
+/// #[derive(Deserialize)]
+/// struct Order {
+///     id: u64,
+///     item: Item,
+/// }
+///
+/// #[derive(Deserialize)]
+/// #[serde(untagged)]
+/// enum Item {
+///     Sku(String),
+///     Custom { name: String, price: u32 },
+/// }
+///
+/// assert_serdify_compatible!(Order);
+/// ```
+#[macro_export]
+macro_rules! assert_serdify_compatible {
+    ($type:ty) => {
+        $crate::coverage::assert_compatible::<$type>()
+    };
+}
+
+struct Prober<'a> {
+    descriptor: &'a Descriptor,
+    /// Named types being generated, ending recursion.
+    visiting: Vec<&'a str>,
+}
+
+impl<'a> Prober<'a> {
+    /// Returns a document of `shape` for each location inside it the
+    /// descriptor cannot see, holding the probe there, with that location.
+    fn probes(&mut self, at: &str, shape: &'a Shape) -> Vec<(String, Value)> {
+        match shape {
+            Shape::Any => vec![(at.to_owned(), json!({ PROBE_KEY: [null] }))],
+            Shape::Option(inner) => self.probes(at, inner),
+            Shape::Seq(inner) => {
+                let element = pointer::join(at, "0");
+                self.probes(&element, inner)
+                    .into_iter()
+                    .map(|(at, value)| (at, Value::Array(vec![value])))
+                    .collect()
+            }
+            Shape::Tuple(shapes) => self.elements(at, shapes),
+            // Flattened structs look like maps of anything.
+            Shape::Map { key, value } if **value == Shape::Any => {
+                let key = self.key(key);
+                vec![(at.to_owned(), json!({ key: { PROBE_KEY: [null] } }))]
+            }
+            Shape::Map { key, value } => {
+                let key = self.key(key);
+                let entry = pointer::join(at, &key);
+                self.probes(&entry, value)
+                    .into_iter()
+                    .map(|(at, value)| (at, json!({ key.clone(): value })))
+                    .collect()
+            }
+            Shape::Named(name) => self.named(at, name),
+            _ => Vec::new(),
+        }
+    }
+
+    fn named(&mut self, at: &str, name: &'a str) -> Vec<(String, Value)> {
+        let Some(container) = self.descriptor.types.get(name) else {
+            return Vec::new();
+        };
+        if self.visiting.contains(&name) {
+            return Vec::new();
+        }
+        self.visiting.push(name);
+        let probes = match container {
+            Container::Struct(fields) => self.fields(at, fields),
+            Container::Enum(variants) => variants
+                .iter()
+                .filter_map(|variant| Some((&variant.name, variant.fields.as_ref()?)))
+                .flat_map(|(name, fields)| {
+                    let inner = pointer::join(at, name);
+                    self.fields(&inner, fields)
+                        .into_iter()
+                        .map(|(at, value)| (at, json!({ name.clone(): value })))
+                        .collect::<Vec<_>>()
+                })
+                .collect(),
+        };
+        self.visiting.pop();
+        probes
+    }
+
+    fn fields(&mut self, at: &str, fields: &'a Fields) -> Vec<(String, Value)> {
+        match fields {
+            Fields::Unit => Vec::new(),
+            Fields::Newtype(shape) => self.probes(at, shape),
+            Fields::Tuple(shapes) => self.elements(at, shapes),
+            Fields::Named(fields) => {
+                let mut probes = Vec::new();
+                for (index, field) in fields.iter().enumerate() {
+                    let inner = pointer::join(at, &field.name);
+                    for (probe, value) in self.probes(&inner, &field.shape) {
+                        let object: Map<_, _> = fields
+                            .iter()
+                            .enumerate()
+                            .map(|(other, field)| {
+                                let value = if other == index {
+                                    value.clone()
+                                } else {
+                                    self.sample(&field.shape)
+                                };
+                                (field.name.clone(), value)
+                            })
+                            .collect();
+                        probes.push((probe, Value::Object(object)));
+                    }
+                }
+                probes
+            }
+        }
+    }
+
+    /// Probes each element of a fixed-length sequence in turn.
+    fn elements(&mut self, at: &str, shapes: &'a [Shape]) -> Vec<(String, Value)> {
+        let mut probes = Vec::new();
+        for (index, shape) in shapes.iter().enumerate() {
+            let inner = pointer::join(at, &index.to_string());
+            for (probe, value) in self.probes(&inner, shape) {
+                let array = shapes
+                    .iter()
+                    .enumerate()
+                    .map(|(other, shape)| {
+                        if other == index {
+                            value.clone()
+                        } else {
+                            self.sample(shape)
+                        }
+                    })
+                    .collect();
+                probes.push((probe, Value::Array(array)));
+            }
+        }
+        probes
+    }
+
+    /// Returns a map key of shape `key`.
+    fn key(&self, key: &'a Shape) -> String {
+        match self.sample(key) {
+            Value::String(_) => PROBE_KEY.to_owned(),
+            key => key.to_string(),
+        }
+    }
+
+    /// Returns a plain value of `shape`, surrounding the probe so that the
+    /// document reaches it.
+    fn sample(&self, shape: &'a Shape) -> Value {
+        self.sample_in(shape, &mut self.visiting.clone())
+    }
+
+    fn sample_in(&self, shape: &'a Shape, seen: &mut Vec<&'a str>) -> Value {
+        match shape {
+            Shape::Bool => Value::Bool(false),
+            Shape::I8
+            | Shape::I16
+            | Shape::I32
+            | Shape::I64
+            | Shape::I128
+            | Shape::U8
+            | Shape::U16
+            | Shape::U32
+            | Shape::U64
+            | Shape::U128 => json!(0),
+            Shape::F32 | Shape::F64 => json!(0.0),
+            Shape::Char => json!("a"),
+            Shape::String => json!(""),
+            Shape::Bytes | Shape::Seq(_) => json!([]),
+            Shape::Option(inner) => self.sample_in(inner, seen),
+            Shape::Tuple(shapes) => shapes
+                .iter()
+                .map(|shape| self.sample_in(shape, seen))
+                .collect(),
+            Shape::Map { .. } => json!({}),
+            Shape::Named(name) if !seen.contains(&name.as_str()) => {
+                seen.push(name);
+                let value = self.sample_named(name, seen);
+                seen.pop();
+                value
+            }
+            Shape::Any | Shape::Unit | Shape::Named(_) => Value::Null,
+        }
+    }
+
+    fn sample_named(&self, name: &str, seen: &mut Vec<&'a str>) -> Value {
+        match self.descriptor.types.get(name) {
+            Some(Container::Struct(Fields::Named(fields))) => fields
+                .iter()
+                .map(|field| (field.name.clone(), self.sample_in(&field.shape, seen)))
+                .collect::<Map<_, _>>()
+                .into(),
+            Some(Container::Struct(Fields::Newtype(shape))) => self.sample_in(shape, seen),
+            Some(Container::Struct(Fields::Tuple(shapes))) => shapes
+                .iter()
+                .map(|shape| self.sample_in(shape, seen))
+                .collect(),
+            Some(Container::Enum(variants)) => match variants.first() {
+                Some(variant) => json!(variant.name),
+                None => Value::Null,
+            },
+            Some(Container::Struct(Fields::Unit)) | None => Value::Null,
+        }
+    }
+}
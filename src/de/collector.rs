@@ -0,0 +1,404 @@
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+
+use serde::de::DeserializeSeed;
+
+use super::synth::Synthesizer;
+use super::{DeError, Leniency, value::Hint};
+use crate::pointer::Path;
+use crate::{ExpectedOrActual, InvalidParam, Severity, code};
+
+/// How a location that already produced an error is treated on later runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Patch {
+    /// Feed the visitor a synthesized stand-in value.
+    Synthesize,
+    /// Leave the entry out, as if it was never in the payload.
+    Skip,
+}
+
+/// What the collector knows about a location and the locations beneath it,
+/// found by walking a [`Path`] down from the root rather than by rendering
+/// its pointer.
+#[derive(Default)]
+struct Mark {
+    patch: Option<Patch>,
+    /// Required fields found missing in the struct here, with the index of
+    /// the error reporting each.
+    missing: Vec<(&'static str, usize)>,
+    /// Whether the struct here failed once fed the fields its type requires
+    /// elsewhere, and must be visited without them.
+    withdrawn: bool,
+    keys: HashMap<String, Mark>,
+    indices: HashMap<usize, Mark>,
+}
+
+impl Mark {
+    fn is_empty(&self) -> bool {
+        self.patch.is_none()
+            && self.missing.is_empty()
+            && self.keys.is_empty()
+            && self.indices.is_empty()
+    }
+
+    fn get(&self, path: &Path<'_>) -> Option<&Mark> {
+        match path {
+            Path::Root => Some(self),
+            Path::Key(parent, key) => self.get(parent)?.keys.get(*key),
+            Path::Index(parent, index) => self.get(parent)?.indices.get(index),
+        }
+    }
+
+    fn entry(&mut self, path: &Path<'_>) -> &mut Mark {
+        match path {
+            Path::Root => self,
+            Path::Key(parent, key) => {
+                let parent = self.entry(parent);
+                if !parent.keys.contains_key(*key) {
+                    parent.keys.insert((*key).to_owned(), Mark::default());
+                }
+                parent.keys.get_mut(*key).expect("mark was just inserted")
+            }
+            Path::Index(parent, index) => self.entry(parent).indices.entry(*index).or_default(),
+        }
+    }
+}
+
+/// Identifies a struct type by its name and the address of its field list.
+type StructKey = (&'static str, usize);
+
+/// Errors and patches accumulated across the runs of a single call.
+#[derive(Default)]
+pub(crate) struct Collector {
+    leniency: Leniency,
+    /// Most errors to collect before giving up on the payload.
+    limit: Option<usize>,
+    errors: RefCell<Vec<InvalidParam>>,
+    /// Findings below [`Severity::Error`] about values accepted leniently,
+    /// recorded once however many runs visit them.
+    notes: RefCell<Vec<InvalidParam>>,
+    noted: RefCell<HashSet<(String, String)>>,
+    marks: RefCell<Mark>,
+    /// Fields each struct type was found to require, so that later
+    /// instances missing them are reported within the same run.
+    required: RefCell<HashMap<StructKey, Vec<&'static str>>>,
+    revision: Cell<usize>,
+    /// Pointers of the objects and arrays the target type asked for through
+    /// `deserialize_any`, buffering them, once recording is turned on.
+    buffered: Option<RefCell<Vec<String>>>,
+}
+
+impl Collector {
+    pub(crate) fn new(leniency: Leniency) -> Self {
+        Self {
+            leniency,
+            ..Self::default()
+        }
+    }
+
+    /// Creates a collector also recording where the target type buffers
+    /// values, see [`buffered`](Self::buffered).
+    pub(crate) fn recording_buffers() -> Self {
+        Self {
+            buffered: Some(RefCell::default()),
+            ..Self::default()
+        }
+    }
+
+    /// Stops collecting errors once `limit` of them are recorded.
+    pub(crate) fn limit(mut self, limit: Option<usize>) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub(crate) fn leniency(&self) -> &Leniency {
+        &self.leniency
+    }
+
+    /// Counter bumped every time the collector learns something new, used
+    /// by the driver to detect runs that made no progress.
+    pub(crate) fn revision(&self) -> usize {
+        self.revision.get()
+    }
+
+    pub(crate) fn has_errors(&self) -> bool {
+        !self.errors.borrow().is_empty()
+    }
+
+    pub(crate) fn error_count(&self) -> usize {
+        self.errors.borrow().len()
+    }
+
+    /// Returns `true` once the error limit is reached.
+    pub(crate) fn is_full(&self) -> bool {
+        self.limit.is_some_and(|limit| self.error_count() >= limit)
+    }
+
+    pub(crate) fn is_root_patched(&self) -> bool {
+        self.marks.borrow().patch.is_some()
+    }
+
+    pub(crate) fn into_parts(self) -> (Vec<InvalidParam>, Vec<InvalidParam>) {
+        (self.errors.into_inner(), self.notes.into_inner())
+    }
+
+    /// Records a note about a value accepted leniently, unless an earlier
+    /// run already did.
+    pub(crate) fn note(&self, note: InvalidParam) {
+        let key = (note.pointer.clone(), note.code.clone());
+        if self.noted.borrow_mut().insert(key) {
+            self.notes.borrow_mut().push(note);
+        }
+    }
+
+    /// Records that the target type buffers the object or array at `path`.
+    pub(crate) fn buffer(&self, path: &Path<'_>) {
+        if let Some(buffered) = &self.buffered {
+            let pointer = path.pointer();
+            let mut buffered = buffered.borrow_mut();
+            if !buffered.contains(&pointer) {
+                buffered.push(pointer);
+            }
+        }
+    }
+
+    /// Returns the pointers recorded by [`buffer`](Self::buffer).
+    pub(crate) fn buffered(&self) -> Vec<String> {
+        self.buffered
+            .as_ref()
+            .map(|buffered| buffered.borrow().clone())
+            .unwrap_or_default()
+    }
+
+    /// Returns the patch applied to `path`, if any.
+    pub(crate) fn patch_at(&self, path: &Path<'_>) -> Option<Patch> {
+        let marks = self.marks.borrow();
+        if marks.is_empty() {
+            return None;
+        }
+        marks.get(path)?.patch
+    }
+
+    /// Returns the required fields found missing in the struct at `path`.
+    pub(crate) fn missing_at(&self, path: &Path<'_>) -> Vec<&'static str> {
+        let marks = self.marks.borrow();
+        if marks.is_empty() {
+            return Vec::new();
+        }
+        marks.get(path).map_or_else(Vec::new, |mark| {
+            mark.missing.iter().map(|(field, _)| *field).collect()
+        })
+    }
+
+    /// Returns the fields the struct `key` is known to require that the
+    /// instance at `path` lacks, leaving out those in `missing` already.
+    pub(crate) fn required_absent(
+        &self,
+        key: StructKey,
+        path: &Path<'_>,
+        present: impl Fn(&str) -> bool,
+        missing: &[&'static str],
+    ) -> Vec<&'static str> {
+        let required = self.required.borrow();
+        let Some(fields) = required.get(&key) else {
+            return Vec::new();
+        };
+        if self
+            .marks
+            .borrow()
+            .get(path)
+            .is_some_and(|mark| mark.withdrawn)
+        {
+            return Vec::new();
+        }
+        fields
+            .iter()
+            .filter(|field| !present(field) && !missing.contains(field))
+            .copied()
+            .collect()
+    }
+
+    /// Remembers that the struct `key` requires the fields found missing in
+    /// the instance at `path`.
+    pub(crate) fn learn_required(&self, key: StructKey, path: &Path<'_>) {
+        let missing = self.missing_at(path);
+        if missing.is_empty() {
+            return;
+        }
+        let mut required = self.required.borrow_mut();
+        let fields = required.entry(key).or_default();
+        for field in missing {
+            if !fields.contains(&field) {
+                fields.push(field);
+            }
+        }
+    }
+
+    fn patch(&self, path: &Path<'_>, patch: Patch) {
+        let mut marks = self.marks.borrow_mut();
+        let mark = marks.entry(path);
+        if mark.patch.replace(patch) != Some(patch) {
+            self.bump();
+        }
+    }
+
+    fn bump(&self) {
+        self.revision.set(self.revision.get() + 1);
+    }
+
+    fn has_skipped_child(&self, path: &Path<'_>) -> bool {
+        let marks = self.marks.borrow();
+        let Some(mark) = marks.get(path) else {
+            return false;
+        };
+        let skipped = |child: &Mark| child.patch == Some(Patch::Skip);
+        mark.keys.values().any(skipped) || mark.indices.values().any(skipped)
+    }
+
+    /// Stops feeding the struct at `path` the fields its type requires
+    /// elsewhere, for the visitor rejected them.
+    pub(crate) fn withdraw(&self, path: &Path<'_>) -> DeError {
+        self.marks.borrow_mut().entry(path).withdrawn = true;
+        self.bump();
+        DeError::Reported
+    }
+
+    /// Records that the struct at `path` lacks the required `field`, whose
+    /// shape is `hint` if known.
+    pub(crate) fn missing(&self, path: &Path<'_>, field: &'static str, hint: Option<Hint>) {
+        let mut param = InvalidParam::missing(path.pointer(), field);
+        if let Some(hint) = hint {
+            param.expected = hint.into();
+        }
+        let mut errors = self.errors.borrow_mut();
+        errors.push(param);
+        let index = errors.len() - 1;
+        self.marks
+            .borrow_mut()
+            .entry(path)
+            .missing
+            .push((field, index));
+        self.bump();
+    }
+
+    /// Records a validation error at `path` and patches it for later runs.
+    pub(crate) fn reject(&self, path: &Path<'_>, param: InvalidParam, patch: Patch) -> DeError {
+        self.errors.borrow_mut().push(param);
+        self.patch(path, patch);
+        DeError::Reported
+    }
+
+    /// Records a validation error at `path`, like [`reject`](Self::reject),
+    /// then carries on with the stand-in `synthesize` hands to the visitor
+    /// of the call that failed. The run thus goes on to the next problem
+    /// instead of starting over. Once the error limit is reached, or if the
+    /// visitor rejects the stand-in, the error is returned instead.
+    pub(crate) fn recover<T>(
+        &self,
+        path: &Path<'_>,
+        param: InvalidParam,
+        synthesize: impl FnOnce(Synthesizer<'_>) -> Result<T, DeError>,
+    ) -> Result<T, DeError> {
+        let err = self.reject(path, param, Patch::Synthesize);
+        if self.is_full() {
+            return Err(err);
+        }
+        let hint = Cell::new(None);
+        synthesize(Synthesizer::new(&hint)).map_err(|_| {
+            self.patch(path, Patch::Skip);
+            DeError::Reported
+        })
+    }
+
+    /// Patches `path` without recording anything, for failures that only
+    /// follow from earlier patches.
+    pub(crate) fn cascade(&self, path: &Path<'_>) -> DeError {
+        self.patch(path, Patch::Synthesize);
+        DeError::Reported
+    }
+
+    /// Turns an error returned by a visitor into a recorded one.
+    ///
+    /// Errors that are mere consequences of earlier patches (a struct
+    /// missing a field that was skipped, an array that lost an element) are
+    /// not recorded again; the location is patched all the same.
+    pub(crate) fn fail(
+        &self,
+        path: &Path<'_>,
+        err: DeError,
+        hint: Hint,
+        actual: impl FnOnce() -> ExpectedOrActual,
+        patch: Patch,
+    ) -> DeError {
+        let (code, reason) = match err {
+            DeError::Reported => return DeError::Reported,
+            DeError::MissingField(field) => {
+                let skipped = self.patch_at(&Path::Key(path, field)) == Some(Patch::Skip);
+                let known = self.missing_at(path).contains(&field);
+                if skipped || known {
+                    self.patch(path, patch);
+                } else {
+                    self.missing(path, field, None);
+                }
+                return DeError::Reported;
+            }
+            DeError::InvalidLength(_) if self.has_skipped_child(path) => {
+                self.patch(path, patch);
+                return DeError::Reported;
+            }
+            DeError::InvalidLength(message) => (code::LENGTH_INVALID, message),
+            DeError::Message(code, message) => (code, message),
+        };
+        let param = InvalidParam {
+            name: path.name(),
+            reason: Some(reason),
+            expected: hint.into(),
+            actual: actual(),
+            pointer: path.pointer(),
+            path: None,
+            code: code.to_owned(),
+            severity: Severity::Error,
+            caused_by: None,
+            offset: None,
+        };
+        self.reject(path, param, patch)
+    }
+
+    /// Deserializes a synthesized stand-in for the value at `path`, falling
+    /// back to skipping the location when the type rejects it.
+    pub(crate) fn synthesize<'de, T>(&self, seed: T, path: &Path<'_>) -> Result<T::Value, DeError>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let hint = Cell::new(None);
+        match seed.deserialize(Synthesizer::new(&hint)) {
+            Ok(value) => {
+                if let (Some(hint), Path::Key(parent, field)) = (hint.get(), path) {
+                    self.refine_missing(parent, field, hint);
+                }
+                Ok(value)
+            }
+            Err(_) => {
+                self.patch(path, Patch::Skip);
+                Err(DeError::Reported)
+            }
+        }
+    }
+
+    /// Fills in the expected type of a missing field once a stand-in for it
+    /// has revealed what the target type asks for.
+    fn refine_missing(&self, parent: &Path<'_>, field: &str, hint: Hint) {
+        let marks = self.marks.borrow();
+        let Some(mark) = marks.get(parent) else {
+            return;
+        };
+        let Some((_, index)) = mark.missing.iter().find(|(missing, _)| *missing == field) else {
+            return;
+        };
+        if let Some(param) = self.errors.borrow_mut().get_mut(*index)
+            && param.expected.r#type == "unknown"
+        {
+            param.expected = hint.into();
+        }
+    }
+}
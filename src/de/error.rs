@@ -0,0 +1,82 @@
+use std::fmt::{self, Display};
+
+use serde::de;
+
+use crate::code;
+
+/// Error type threaded through the deserializer.
+///
+/// Errors are recorded in the [`Collector`](super::Collector) by the
+/// deserializer closest to where they happened; once recorded they travel up
+/// the stack as [`DeError::Reported`] so every level can tell it has nothing
+/// left to do but unwind.
+#[derive(Debug)]
+pub(crate) enum DeError {
+    Reported,
+    Message(&'static str, String),
+    InvalidLength(String),
+    MissingField(&'static str),
+}
+
+impl Display for DeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeError::Reported => f.write_str("error already reported"),
+            DeError::Message(_, message) | DeError::InvalidLength(message) => f.write_str(message),
+            DeError::MissingField(field) => write!(f, "missing field `{field}`"),
+        }
+    }
+}
+
+impl std::error::Error for DeError {}
+
+impl de::Error for DeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        DeError::Message(code::CUSTOM, msg.to_string())
+    }
+
+    fn invalid_type(unexp: de::Unexpected<'_>, exp: &dyn de::Expected) -> Self {
+        let message = format!("invalid type: {unexp}, expected {exp}");
+        DeError::Message(code::TYPE_MISMATCH, message)
+    }
+
+    fn invalid_value(unexp: de::Unexpected<'_>, exp: &dyn de::Expected) -> Self {
+        let message = format!("invalid value: {unexp}, expected {exp}");
+        DeError::Message(code::VALUE_INVALID, message)
+    }
+
+    fn invalid_length(len: usize, exp: &dyn de::Expected) -> Self {
+        DeError::InvalidLength(format!("invalid length {len}, expected {exp}"))
+    }
+
+    fn unknown_variant(variant: &str, expected: &'static [&'static str]) -> Self {
+        let message = format!("unknown variant `{variant}`, expected {}", one_of(expected));
+        DeError::Message(code::VARIANT_UNKNOWN, message)
+    }
+
+    fn unknown_field(field: &str, expected: &'static [&'static str]) -> Self {
+        let message = format!("unknown field `{field}`, expected {}", one_of(expected));
+        DeError::Message(code::FIELD_UNKNOWN, message)
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        DeError::MissingField(field)
+    }
+
+    fn duplicate_field(field: &'static str) -> Self {
+        DeError::Message(code::FIELD_DUPLICATE, format!("duplicate field `{field}`"))
+    }
+}
+
+/// Formats a list of names the way `serde` does in its own messages.
+fn one_of(names: &[&str]) -> String {
+    match names {
+        [] => "nothing".to_owned(),
+        [name] => format!("`{name}`"),
+        [first, second] => format!("`{first}` or `{second}`"),
+        names => {
+            let names: Vec<_> = names.iter().map(|name| format!("`{name}`")).collect();
+            format!("one of {}", names.join(", "))
+        }
+    }
+}
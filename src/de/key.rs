@@ -0,0 +1,168 @@
+use std::str::FromStr;
+
+use serde::de::{self, IntoDeserializer, Visitor};
+
+use super::DeError;
+use super::collector::{Collector, Patch};
+use super::value::{Hint, mismatch, out_of_range};
+use crate::ExpectedOrActual;
+use crate::pointer::Path;
+
+const KEY: Hint = Hint::new("string", "string");
+
+fn actual() -> ExpectedOrActual {
+    ExpectedOrActual::new("string", "string")
+}
+
+/// Deserializer for object keys.
+///
+/// JSON keys are always strings, so keys of other types are parsed from the
+/// string the same way `serde_json` does. A key that fails to parse drops the
+/// whole entry from later runs.
+pub(crate) struct KeyDeserializer<'a, 'de> {
+    key: &'de str,
+    path: Path<'a>,
+    collector: &'a Collector,
+}
+
+impl<'a, 'de> KeyDeserializer<'a, 'de> {
+    pub(crate) fn new(key: &'de str, path: Path<'a>, collector: &'a Collector) -> Self {
+        Self {
+            key,
+            path,
+            collector,
+        }
+    }
+
+    fn finish<T>(&self, result: Result<T, DeError>, hint: Hint) -> Result<T, DeError> {
+        result.map_err(|err| {
+            self.collector
+                .fail(&self.path, err, hint, actual, Patch::Skip)
+        })
+    }
+
+    fn parse<T: FromStr>(&self, hint: Hint) -> Result<T, DeError> {
+        self.key.parse().map_err(|_| {
+            let param = mismatch(&self.path, hint, actual());
+            self.collector.reject(&self.path, param, Patch::Skip)
+        })
+    }
+}
+
+macro_rules! deserialize_integer_key {
+    ($method:ident, $ty:ident, $visit:ident) => {
+        fn $method<V>(self, visitor: V) -> Result<V::Value, DeError>
+        where
+            V: Visitor<'de>,
+        {
+            const HINT: Hint = Hint::new(stringify!($ty), "integer");
+            let value = if let Ok(value) = self.key.parse::<i128>() {
+                $ty::try_from(value).map_err(|_| value.to_string())
+            } else if let Ok(value) = self.key.parse::<u128>() {
+                $ty::try_from(value).map_err(|_| value.to_string())
+            } else {
+                let param = mismatch(&self.path, HINT, actual());
+                return Err(self.collector.reject(&self.path, param, Patch::Skip));
+            };
+            match value {
+                Ok(value) => self.finish(visitor.$visit(value), HINT),
+                Err(value) => {
+                    let param = out_of_range(&self.path, value, HINT, $ty::MIN, $ty::MAX, actual());
+                    Err(self.collector.reject(&self.path, param, Patch::Skip))
+                }
+            }
+        }
+    };
+}
+
+macro_rules! deserialize_parsed_key {
+    ($method:ident, $ty:ident, $visit:ident, $hint:expr) => {
+        fn $method<V>(self, visitor: V) -> Result<V::Value, DeError>
+        where
+            V: Visitor<'de>,
+        {
+            let value: $ty = self.parse($hint)?;
+            self.finish(visitor.$visit(value), $hint)
+        }
+    };
+}
+
+impl<'de> de::Deserializer<'de> for KeyDeserializer<'_, 'de> {
+    type Error = DeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        self.finish(visitor.visit_borrowed_str(self.key), KEY)
+    }
+
+    deserialize_integer_key!(deserialize_i8, i8, visit_i8);
+    deserialize_integer_key!(deserialize_i16, i16, visit_i16);
+    deserialize_integer_key!(deserialize_i32, i32, visit_i32);
+    deserialize_integer_key!(deserialize_i64, i64, visit_i64);
+    deserialize_integer_key!(deserialize_i128, i128, visit_i128);
+    deserialize_integer_key!(deserialize_u8, u8, visit_u8);
+    deserialize_integer_key!(deserialize_u16, u16, visit_u16);
+    deserialize_integer_key!(deserialize_u32, u32, visit_u32);
+    deserialize_integer_key!(deserialize_u64, u64, visit_u64);
+    deserialize_integer_key!(deserialize_u128, u128, visit_u128);
+    deserialize_parsed_key!(
+        deserialize_bool,
+        bool,
+        visit_bool,
+        Hint::new("bool", "boolean")
+    );
+    deserialize_parsed_key!(deserialize_f32, f32, visit_f32, Hint::new("f32", "number"));
+    deserialize_parsed_key!(deserialize_f64, f64, visit_f64, Hint::new("f64", "number"));
+    deserialize_parsed_key!(
+        deserialize_char,
+        char,
+        visit_char,
+        Hint::new("char", "string")
+    );
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        let hint = Hint::new("option", "string");
+        let (path, collector) = (self.path, self.collector);
+        visitor
+            .visit_some(self)
+            .map_err(|err| collector.fail(&path, err, hint, actual, Patch::Skip))
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        let hint = Hint::new(name, "string");
+        let (path, collector) = (self.path, self.collector);
+        visitor
+            .visit_newtype_struct(self)
+            .map_err(|err| collector.fail(&path, err, hint, actual, Patch::Skip))
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        let hint = Hint::new(name, "enum");
+        self.finish(visitor.visit_enum(self.key.into_deserializer()), hint)
+    }
+
+    serde::forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct seq tuple tuple_struct map
+        struct identifier ignored_any
+    }
+}
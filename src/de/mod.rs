@@ -0,0 +1,161 @@
+//! The collecting deserializer.
+//!
+//! `serde`'s derived visitors stop at the first error, so a single pass can
+//! only ever observe one problem. The driver here runs the target type's
+//! `Deserialize` impl repeatedly over the same parsed [`Value`]: every run
+//! records the error it stopped at, then patches that location so the next
+//! run gets past it. Patched locations are fed a synthesized stand-in value
+//! of the right shape, or skipped entirely when the type refuses every
+//! stand-in. Missing required fields are recorded and then synthesized the
+//! same way, so later fields of the same struct are still reported.
+//!
+//! Starting over is only needed for errors raised by visitors, which are
+//! gone once they fail. Values of the wrong type or out of range are found
+//! by the deserializer itself, which hands the stand-in to the visitor
+//! right away and carries on, and required fields missing from one
+//! instance of a struct are fed to later instances lacking them in the same
+//! run. Runs also end at the configured error limit, so a payload full of
+//! errors costs a handful of runs rather than one per error.
+
+mod collector;
+mod error;
+mod key;
+mod synth;
+#[cfg(test)]
+mod tests;
+mod value;
+
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::pointer::Path;
+use crate::{Error, InvalidParam, Result};
+
+pub(crate) use collector::Collector;
+pub(crate) use error::DeError;
+pub(crate) use value::ValueDeserializer;
+
+/// Relaxations of the JSON accepted for some types. Every value coerced
+/// thanks to one of them is reported with a note.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Leniency {
+    /// Accept `"yes"`, `"on"`, `1` and the like for booleans.
+    pub(crate) bools: bool,
+    /// Match enum variant names ignoring case, `-` and `_`.
+    pub(crate) variants: bool,
+    /// Accept the index of a variant in place of its name, for every enum.
+    pub(crate) indices: bool,
+    /// Numbers standing for variants, by enum name, taking precedence over
+    /// indices.
+    pub(crate) discriminants: Arc<BTreeMap<String, Vec<(i64, String)>>>,
+}
+
+impl Leniency {
+    /// Returns the numbers accepted for the enum `name` with their variant,
+    /// or `None` if the enum is only accepted by name.
+    pub(crate) fn discriminants(
+        &self,
+        name: &str,
+        variants: &'static [&'static str],
+    ) -> Option<Vec<(i64, &'static str)>> {
+        if let Some(discriminants) = self.discriminants.get(name) {
+            let known = discriminants.iter().filter_map(|(number, variant)| {
+                let variant = variants.iter().find(|name| *name == variant)?;
+                Some((*number, *variant))
+            });
+            return Some(known.collect());
+        }
+        self.indices
+            .then(|| (0..).zip(variants.iter().copied()).collect())
+    }
+}
+
+/// Deserializes `value` into `T`, collecting every error along the way.
+pub(crate) fn from_value<'de, T>(value: &'de Value) -> Result<T>
+where
+    T: Deserialize<'de>,
+{
+    from_value_lenient(value, &Leniency::default()).0
+}
+
+/// Like [`from_value`], accepting what `leniency` allows and returning the
+/// notes left by doing so.
+pub(crate) fn from_value_lenient<'de, T>(
+    value: &'de Value,
+    leniency: &Leniency,
+) -> (Result<T>, Vec<InvalidParam>)
+where
+    T: Deserialize<'de>,
+{
+    let limit = crate::config::current().max_errors;
+    let collector = Collector::new(leniency.clone()).limit(limit);
+    let value = drive(value, &collector);
+    let (mut errors, notes) = collector.into_parts();
+    if let Some(limit) = limit {
+        errors.truncate(limit);
+    }
+    match value {
+        Some(value) => (Result::Ok(value), notes),
+        None => (Result::Err(Error::new(errors)), notes),
+    }
+}
+
+/// Like [`from_value`], dropping the value as soon as it is built and
+/// returning the errors only.
+pub(crate) fn check<'de, T>(value: &'de Value) -> Option<Error>
+where
+    T: Deserialize<'de>,
+{
+    let limit = crate::config::current().max_errors;
+    let collector = Collector::new(Leniency::default()).limit(limit);
+    if drive::<T>(value, &collector).is_some() {
+        return None;
+    }
+    let (mut errors, _) = collector.into_parts();
+    if let Some(limit) = limit {
+        errors.truncate(limit);
+    }
+    Some(Error::new(errors))
+}
+
+/// Like [`from_value`], also returning the pointers of the objects and
+/// arrays `T` buffered by asking for any value, in the order it did.
+pub(crate) fn from_value_buffered<'de, T>(value: &'de Value) -> (Result<T>, Vec<String>)
+where
+    T: Deserialize<'de>,
+{
+    let collector = Collector::recording_buffers();
+    let value = drive(value, &collector);
+    let buffered = collector.buffered();
+    let result = match value {
+        Some(value) => Result::Ok(value),
+        None => Result::Err(Error::new(collector.into_parts().0)),
+    };
+    (result, buffered)
+}
+
+/// Runs `T`'s `Deserialize` impl until it succeeds without errors, stops
+/// making progress or reaches the collector's error limit, in which case
+/// the errors are left in `collector`.
+fn drive<'de, T>(value: &'de Value, collector: &Collector) -> Option<T>
+where
+    T: Deserialize<'de>,
+{
+    loop {
+        if collector.is_full() {
+            return None;
+        }
+        let revision = collector.revision();
+        let seed = PhantomData::<T>;
+        match ValueDeserializer::new(value, Path::Root, collector).deserialize_seed(seed) {
+            Ok(value) if !collector.has_errors() => return Some(value),
+            Ok(_) => return None,
+            Err(_) if collector.revision() != revision && !collector.is_root_patched() => {}
+            Err(_) => return None,
+        }
+    }
+}
@@ -0,0 +1,368 @@
+use std::cell::Cell;
+
+use serde::de::{
+    self, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess,
+    Visitor,
+};
+
+use super::DeError;
+use super::value::Hint;
+
+/// Nesting limit for synthesized values, guarding against recursive types.
+const MAX_DEPTH: usize = 64;
+
+/// Deserializer producing the smallest value of whatever shape is asked for:
+/// zero, `false`, empty strings and sequences, `None`, the first variant of
+/// an enum and structs with every field synthesized in turn.
+///
+/// Stand-ins are only ever handed to visitors of calls that already failed,
+/// so the value they produce is never returned to the caller.
+pub(crate) struct Synthesizer<'a> {
+    depth: usize,
+    hint: Option<&'a Cell<Option<Hint>>>,
+}
+
+impl<'a> Synthesizer<'a> {
+    /// Creates a synthesizer reporting the shape requested at the top level
+    /// into `hint`.
+    pub(crate) fn new(hint: &'a Cell<Option<Hint>>) -> Self {
+        Self {
+            depth: 0,
+            hint: Some(hint),
+        }
+    }
+
+    fn guard(&self) -> Result<(), DeError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(de::Error::custom("recursion limit exceeded"));
+        }
+        Ok(())
+    }
+
+    fn requested(&self, hint: Hint) {
+        if let Some(cell) = self.hint {
+            cell.set(Some(hint));
+        }
+    }
+}
+
+macro_rules! synthesize_primitive {
+    ($method:ident, $visit:ident, $value:expr, $ty:literal, $format:literal) => {
+        fn $method<V>(self, visitor: V) -> Result<V::Value, DeError>
+        where
+            V: Visitor<'de>,
+        {
+            self.requested(Hint::new($ty, $format));
+            visitor.$visit($value)
+        }
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Synthesizer<'_> {
+    type Error = DeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    synthesize_primitive!(deserialize_bool, visit_bool, false, "bool", "boolean");
+    synthesize_primitive!(deserialize_i8, visit_i8, 0, "i8", "integer");
+    synthesize_primitive!(deserialize_i16, visit_i16, 0, "i16", "integer");
+    synthesize_primitive!(deserialize_i32, visit_i32, 0, "i32", "integer");
+    synthesize_primitive!(deserialize_i64, visit_i64, 0, "i64", "integer");
+    synthesize_primitive!(deserialize_i128, visit_i128, 0, "i128", "integer");
+    synthesize_primitive!(deserialize_u8, visit_u8, 0, "u8", "integer");
+    synthesize_primitive!(deserialize_u16, visit_u16, 0, "u16", "integer");
+    synthesize_primitive!(deserialize_u32, visit_u32, 0, "u32", "integer");
+    synthesize_primitive!(deserialize_u64, visit_u64, 0, "u64", "integer");
+    synthesize_primitive!(deserialize_u128, visit_u128, 0, "u128", "integer");
+    synthesize_primitive!(deserialize_f32, visit_f32, 0.0, "f32", "number");
+    synthesize_primitive!(deserialize_f64, visit_f64, 0.0, "f64", "number");
+    synthesize_primitive!(deserialize_char, visit_char, '\0', "char", "string");
+    synthesize_primitive!(deserialize_str, visit_borrowed_str, "", "string", "string");
+    synthesize_primitive!(
+        deserialize_string,
+        visit_borrowed_str,
+        "",
+        "string",
+        "string"
+    );
+    synthesize_primitive!(
+        deserialize_bytes,
+        visit_borrowed_bytes,
+        &[],
+        "bytes",
+        "array"
+    );
+    synthesize_primitive!(
+        deserialize_byte_buf,
+        visit_borrowed_bytes,
+        &[],
+        "bytes",
+        "array"
+    );
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        self.requested(Hint::new("unit", "null"));
+        visitor.visit_unit()
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_none()
+    }
+
+    fn deserialize_unit_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        self.requested(Hint::new(name, "null"));
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        let inner = Synthesizer {
+            depth: self.depth,
+            hint: self.hint,
+        };
+        visitor.visit_newtype_struct(inner)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        self.requested(Hint::new("array", "array"));
+        visitor.visit_seq(SynthesizedSeq {
+            remaining: 0,
+            depth: self.depth,
+        })
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        self.requested(Hint::new("tuple", "array"));
+        self.guard()?;
+        visitor.visit_seq(SynthesizedSeq {
+            remaining: len,
+            depth: self.depth,
+        })
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        self.requested(Hint::new(name, "array"));
+        self.guard()?;
+        visitor.visit_seq(SynthesizedSeq {
+            remaining: len,
+            depth: self.depth,
+        })
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        self.requested(Hint::new("map", "object"));
+        visitor.visit_map(SynthesizedMap {
+            fields: [].iter(),
+            depth: self.depth,
+        })
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        self.requested(Hint::new(name, "object"));
+        self.guard()?;
+        visitor.visit_map(SynthesizedMap {
+            fields: fields.iter(),
+            depth: self.depth,
+        })
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        self.requested(Hint::new(name, "enum"));
+        self.guard()?;
+        match variants.first() {
+            Some(variant) => visitor.visit_enum(SynthesizedEnum {
+                variant,
+                depth: self.depth,
+            }),
+            None => Err(de::Error::custom("enum has no variants")),
+        }
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_borrowed_str("")
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+}
+
+struct SynthesizedSeq {
+    remaining: usize,
+    depth: usize,
+}
+
+impl<'de> SeqAccess<'de> for SynthesizedSeq {
+    type Error = DeError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, DeError>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        let element = Synthesizer {
+            depth: self.depth + 1,
+            hint: None,
+        };
+        seed.deserialize(element).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+struct SynthesizedMap {
+    fields: std::slice::Iter<'static, &'static str>,
+    depth: usize,
+}
+
+impl<'de> MapAccess<'de> for SynthesizedMap {
+    type Error = DeError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, DeError>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match self.fields.next() {
+            Some(field) => seed.deserialize((*field).into_deserializer()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, DeError>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        seed.deserialize(Synthesizer {
+            depth: self.depth + 1,
+            hint: None,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.fields.len())
+    }
+}
+
+struct SynthesizedEnum {
+    variant: &'static str,
+    depth: usize,
+}
+
+impl<'de> EnumAccess<'de> for SynthesizedEnum {
+    type Error = DeError;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self), DeError>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for SynthesizedEnum {
+    type Error = DeError;
+
+    fn unit_variant(self) -> Result<(), DeError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, DeError>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(Synthesizer {
+            depth: self.depth + 1,
+            hint: None,
+        })
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(SynthesizedSeq {
+            remaining: len,
+            depth: self.depth,
+        })
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(SynthesizedMap {
+            fields: fields.iter(),
+            depth: self.depth,
+        })
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::BTreeMap;
+use std::num::NonZeroU8;
+use std::ops::Bound;
+use std::time::{Duration, SystemTime};
+
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::{Error, code, config};
+
+/// Deserializes `value` as `T`, expecting it to fail.
+fn error<T>(value: Value) -> Error
+where
+    T: for<'de> Deserialize<'de>,
+{
+    match super::from_value::<T>(&value) {
+        crate::Result::Ok(_) => panic!("{value} was accepted"),
+        crate::Result::Err(error) => error,
+    }
+}
+
+/// Like [`error`], returning the pointers of the errors with their codes.
+fn errors<T>(value: Value) -> Vec<(String, String)>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    error::<T>(value)
+        .invalid_params
+        .into_iter()
+        .map(|param| (param.pointer, param.code))
+        .collect()
+}
+
+fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+    expected
+        .iter()
+        .map(|(pointer, code)| ((*pointer).to_owned(), (*code).to_owned()))
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+enum Shape {
+    Unit,
+    Newtype(u8),
+    Tuple(u8, u8),
+    Struct { a: u8, b: u8 },
+}
+
+#[test]
+fn unit_variant_with_content_is_reported_once() {
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Payload {
+        e: Shape,
+        n: u8,
+        m: u8,
+    }
+
+    let value = json!({"e": {"Unit": 5}, "n": 300, "m": 300});
+    assert_eq!(
+        errors::<Payload>(value),
+        pairs(&[
+            ("#/e/Unit", code::TYPE_MISMATCH),
+            ("#/m", code::RANGE_OVERFLOW),
+            ("#/n", code::RANGE_OVERFLOW),
+        ])
+    );
+}
+
+#[test]
+fn unit_variants_inside_maps() {
+    let value = json!({"a": {"Unit": 1}, "b": "Unit", "c": {"Unit": []}});
+    assert_eq!(
+        errors::<BTreeMap<String, Shape>>(value),
+        pairs(&[
+            ("#/a/Unit", code::TYPE_MISMATCH),
+            ("#/c/Unit", code::TYPE_MISMATCH),
+        ])
+    );
+}
+
+#[test]
+fn struct_and_tuple_variants() {
+    let value = json!([
+        {"Struct": {"a": 300, "b": "x"}},
+        {"Struct": {"a": 1}},
+        {"Tuple": [1]},
+        {"Tuple": [1, 2, 3]},
+        {"Tuple": [1, -1]},
+        {"Newtype": "x"},
+    ]);
+    assert_eq!(
+        errors::<Vec<Shape>>(value),
+        pairs(&[
+            ("#/0/Struct/a", code::RANGE_OVERFLOW),
+            ("#/0/Struct/b", code::TYPE_MISMATCH),
+            ("#/1/Struct", code::FIELD_MISSING),
+            ("#/2/Tuple", code::LENGTH_INVALID),
+            ("#/3/Tuple", code::LENGTH_INVALID),
+            ("#/4/Tuple/1", code::RANGE_OVERFLOW),
+            ("#/5/Newtype", code::TYPE_MISMATCH),
+        ])
+    );
+}
+
+#[test]
+fn bounds() {
+    let value = json!([{"Included": 300}, "Unbounded", {"Excluded": "x"}, {"Above": 1}]);
+    assert_eq!(
+        errors::<Vec<Bound<u8>>>(value),
+        pairs(&[
+            ("#/0/Included", code::RANGE_OVERFLOW),
+            ("#/2/Excluded", code::TYPE_MISMATCH),
+            ("#/3", code::VARIANT_UNKNOWN),
+        ])
+    );
+}
+
+#[test]
+fn durations() {
+    let value = json!([
+        {"secs": -1, "nanos": 0},
+        {"nanos": 5},
+        {"secs": u64::MAX, "nanos": 2_000_000_000u32},
+        {"secs": 1, "nanos": 5},
+    ]);
+    let error = error::<Vec<Duration>>(value);
+    let params = &error.invalid_params;
+    assert_eq!(params.len(), 3);
+    assert_eq!(
+        (params[0].pointer.as_str(), params[0].code.as_str()),
+        ("#/0/secs", code::RANGE_OVERFLOW)
+    );
+    assert_eq!(
+        (params[1].pointer.as_str(), params[1].name.as_str()),
+        ("#/1", "secs")
+    );
+    assert_eq!(params[1].expected.r#type, "u64");
+    assert_eq!(params[2].pointer, "#/2");
+    assert!(params[2].reason.as_deref().unwrap().contains("overflow"));
+}
+
+#[test]
+fn system_times() {
+    let value = json!([
+        {"secs_since_epoch": "x", "nanos_since_epoch": 0},
+        {"secs_since_epoch": 1},
+        {"secs_since_epoch": 1, "nanos_since_epoch": 0},
+    ]);
+    let error = error::<Vec<SystemTime>>(value);
+    let params = &error.invalid_params;
+    assert_eq!(params.len(), 2);
+    assert_eq!(
+        (params[0].pointer.as_str(), params[0].code.as_str()),
+        ("#/0/secs_since_epoch", code::TYPE_MISMATCH)
+    );
+    assert_eq!(
+        (params[1].pointer.as_str(), params[1].name.as_str()),
+        ("#/1", "nanos_since_epoch")
+    );
+    assert_eq!(params[1].expected.r#type, "u32");
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct Inner {
+    a: u8,
+    b: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct Outer {
+    first: Inner,
+    last: u8,
+}
+
+#[test]
+fn missing_fields_are_refined_with_their_type() {
+    let value = json!([{"last": 1}, {"first": {"a": 1}}, {"first": {"b": "x"}}]);
+    let error = error::<Vec<Outer>>(value);
+    let missing: Vec<_> = error
+        .invalid_params
+        .iter()
+        .map(|param| {
+            assert_eq!(param.code, code::FIELD_MISSING);
+            assert_eq!(param.reason.as_deref(), Some("missing required field"));
+            (
+                param.pointer.as_str(),
+                param.name.as_str(),
+                param.expected.r#type.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        missing,
+        [
+            ("#/0", "first", "Inner"),
+            ("#/1/first", "b", "string"),
+            ("#/1", "last", "u8"),
+            ("#/2/first", "a", "u8"),
+            ("#/2", "last", "u8"),
+        ]
+    );
+}
+
+#[test]
+fn missing_fields_under_an_alias_are_not_reported() {
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Aliased {
+        #[serde(alias = "b")]
+        a: u8,
+    }
+
+    let value = json!([{}, {"b": 1}, {"a": 300}]);
+    assert_eq!(
+        errors::<Vec<Aliased>>(value),
+        pairs(&[
+            ("#/0", code::FIELD_MISSING),
+            ("#/2/a", code::RANGE_OVERFLOW),
+        ])
+    );
+}
+
+#[test]
+fn skipped_fields_are_not_reported_missing() {
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Holder {
+        n: NonZeroU8,
+        m: u8,
+    }
+
+    let value = json!([{"n": 0, "m": 300}, {"n": 0, "m": 1}]);
+    assert_eq!(
+        errors::<Vec<Holder>>(value),
+        pairs(&[
+            ("#/0/m", code::RANGE_OVERFLOW),
+            ("#/0/n", code::VALUE_INVALID),
+            ("#/1/n", code::VALUE_INVALID),
+        ])
+    );
+}
+
+#[test]
+fn skipped_elements_cause_no_length_error() {
+    let value = json!([[0, 1], [1, 300]]);
+    assert_eq!(
+        errors::<Vec<(NonZeroU8, u8)>>(value),
+        pairs(&[
+            ("#/0/0", code::VALUE_INVALID),
+            ("#/1/1", code::RANGE_OVERFLOW),
+        ])
+    );
+
+    let value = json!([0, 1, 0]);
+    assert_eq!(
+        errors::<Vec<NonZeroU8>>(value),
+        pairs(&[("#/0", code::VALUE_INVALID), ("#/2", code::VALUE_INVALID)])
+    );
+
+    // Leaving out the element would shift `300` into the array's place.
+    let value = json!([[0, 1], 300]);
+    assert_eq!(
+        errors::<([NonZeroU8; 2], u8)>(value),
+        pairs(&[("#/0/0", code::VALUE_INVALID)])
+    );
+}
+
+#[test]
+fn length_errors() {
+    assert_eq!(
+        errors::<(u8, u8)>(json!([1, 2, 3])),
+        pairs(&[("#", code::LENGTH_INVALID)])
+    );
+    assert_eq!(
+        errors::<(u8, u8)>(json!([1])),
+        pairs(&[("#", code::LENGTH_INVALID)])
+    );
+    assert_eq!(
+        errors::<[u8; 2]>(json!([300])),
+        pairs(&[("#/0", code::RANGE_OVERFLOW), ("#", code::LENGTH_INVALID)])
+    );
+}
+
+#[test]
+fn errors_stop_at_the_limit() {
+    let value = Value::Array(vec![json!(300); 1000]);
+    assert_eq!(error::<Vec<u8>>(value.clone()).invalid_params.len(), 100);
+
+    let _scope = config::scoped(|config| config.max_errors = Some(3));
+    let error = error::<Vec<u8>>(value);
+    let pointers: Vec<_> = error
+        .invalid_params
+        .iter()
+        .map(|param| param.pointer.as_str())
+        .collect();
+    assert_eq!(pointers, ["#/0", "#/1", "#/2"]);
+}
@@ -0,0 +1,947 @@
+use std::cell::Cell;
+
+use serde::de::{
+    self, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess,
+    Visitor,
+};
+use serde_json::{Map, Number, Value};
+
+use super::DeError;
+use super::collector::{Collector, Patch};
+use super::key::KeyDeserializer;
+use super::synth::Synthesizer;
+use crate::pointer::Path;
+use crate::{ExpectedOrActual, InvalidParam, Severity, code};
+
+/// What a `deserialize_*` call asked for, reported as the expected side of
+/// an error.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Hint {
+    pub(crate) r#type: &'static str,
+    pub(crate) format: &'static str,
+}
+
+impl Hint {
+    pub(crate) const fn new(r#type: &'static str, format: &'static str) -> Self {
+        Self { r#type, format }
+    }
+}
+
+impl From<Hint> for ExpectedOrActual {
+    fn from(hint: Hint) -> Self {
+        ExpectedOrActual::new(hint.r#type, hint.format)
+    }
+}
+
+const BOOL: Hint = Hint::new("bool", "boolean");
+const F32: Hint = Hint::new("f32", "number");
+const F64: Hint = Hint::new("f64", "number");
+const CHAR: Hint = Hint::new("char", "string");
+const STRING: Hint = Hint::new("string", "string");
+const BYTES: Hint = Hint::new("bytes", "array");
+const UNIT: Hint = Hint::new("unit", "null");
+const SEQ: Hint = Hint::new("array", "array");
+const TUPLE: Hint = Hint::new("tuple", "array");
+const MAP: Hint = Hint::new("map", "object");
+const ANY: Hint = Hint::new("any", "any");
+
+/// Describes the JSON value found in the payload.
+pub(crate) fn actual(value: &Value) -> ExpectedOrActual {
+    ExpectedOrActual::of(value)
+}
+
+/// Builds the error for a value whose JSON type does not fit the target.
+pub(crate) fn mismatch(path: &Path<'_>, hint: Hint, actual: ExpectedOrActual) -> InvalidParam {
+    InvalidParam {
+        name: path.name(),
+        reason: Some(format!("Expected {}, found {}", hint.format, actual.format)),
+        expected: hint.into(),
+        actual,
+        pointer: path.pointer(),
+        path: None,
+        code: code::TYPE_MISMATCH.to_owned(),
+        severity: Severity::Error,
+        caused_by: None,
+        offset: None,
+    }
+}
+
+/// Builds the error for a number that does not fit the target type.
+pub(crate) fn out_of_range(
+    path: &Path<'_>,
+    value: impl std::fmt::Display,
+    hint: Hint,
+    min: impl std::fmt::Display,
+    max: impl std::fmt::Display,
+    actual: ExpectedOrActual,
+) -> InvalidParam {
+    InvalidParam {
+        name: path.name(),
+        reason: Some(format!(
+            "Value {value} is out of range for type {}. Expected range: {min} to {max}",
+            hint.r#type
+        )),
+        expected: hint.into(),
+        actual,
+        pointer: path.pointer(),
+        path: None,
+        code: code::RANGE_OVERFLOW.to_owned(),
+        severity: Severity::Error,
+        caused_by: None,
+        offset: None,
+    }
+}
+
+/// Folds the case and word separators of a variant name.
+fn normalize_variant(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, '-' | '_'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Reads the spellings of booleans common in form data and legacy clients.
+fn coerce_bool(value: &Value) -> Option<bool> {
+    match value {
+        Value::Number(number) => match number.as_u64() {
+            Some(0) => Some(false),
+            Some(1) => Some(true),
+            _ => None,
+        },
+        Value::String(text) => match text.to_ascii_lowercase().as_str() {
+            "true" | "yes" | "y" | "on" | "1" => Some(true),
+            "false" | "no" | "n" | "off" | "0" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Deserializer over a borrowed [`Value`] that records errors instead of
+/// stopping at the first one.
+#[derive(Clone, Copy)]
+pub(crate) struct ValueDeserializer<'a, 'de> {
+    value: &'de Value,
+    path: Path<'a>,
+    collector: &'a Collector,
+}
+
+impl<'a, 'de> ValueDeserializer<'a, 'de> {
+    pub(crate) fn new(value: &'de Value, path: Path<'a>, collector: &'a Collector) -> Self {
+        Self {
+            value,
+            path,
+            collector,
+        }
+    }
+
+    /// Runs `seed` against this deserializer, recording errors raised by
+    /// the seed itself once the deserializer call has returned (buffered
+    /// enums and `#[serde(flatten)]` fail that way).
+    pub(crate) fn deserialize_seed<T>(self, seed: T) -> Result<T::Value, DeError>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.finish(seed.deserialize(self), ANY)
+    }
+
+    fn finish<T>(&self, result: Result<T, DeError>, hint: Hint) -> Result<T, DeError> {
+        result.map_err(|err| {
+            self.collector.fail(
+                &self.path,
+                err,
+                hint,
+                || actual(self.value),
+                Patch::Synthesize,
+            )
+        })
+    }
+
+    /// Reports a value of the wrong JSON type, carrying on with the value
+    /// `synthesize` produces, see [`Collector::recover`].
+    fn mismatch<T>(
+        &self,
+        hint: Hint,
+        synthesize: impl FnOnce(Synthesizer<'_>) -> Result<T, DeError>,
+    ) -> Result<T, DeError> {
+        let param = mismatch(&self.path, hint, actual(self.value));
+        self.collector.recover(&self.path, param, synthesize)
+    }
+
+    fn out_of_range<T>(
+        &self,
+        number: &Number,
+        hint: Hint,
+        min: impl std::fmt::Display,
+        max: impl std::fmt::Display,
+        synthesize: impl FnOnce(Synthesizer<'_>) -> Result<T, DeError>,
+    ) -> Result<T, DeError> {
+        let param = out_of_range(&self.path, number, hint, min, max, actual(self.value));
+        self.collector.recover(&self.path, param, synthesize)
+    }
+
+    /// Returns the variant `variant` names: itself, or under
+    /// [`Leniency::variants`](super::Leniency::variants) the only variant
+    /// spelled the same ignoring case, `-` and `_`.
+    fn variant_name(&self, variant: &'de str, variants: &'static [&'static str]) -> &'de str {
+        if !self.collector.leniency().variants || variants.contains(&variant) {
+            return variant;
+        }
+        let normalized = normalize_variant(variant);
+        let mut matches = variants
+            .iter()
+            .filter(|name| normalize_variant(name) == normalized);
+        let (Some(name), None) = (matches.next(), matches.next()) else {
+            return variant;
+        };
+        let reason = format!("Read variant `{variant}` as `{name}`");
+        let note = InvalidParam::new(self.path.pointer(), code::COERCED_VARIANT, reason)
+            .expected(ExpectedOrActual::new(*name, "string"))
+            .actual(actual(self.value))
+            .severity(Severity::Info);
+        self.collector.note(note);
+        name
+    }
+
+    /// Deserializes the unit variant the number `number` stands for.
+    fn visit_discriminant<V>(
+        &self,
+        number: &Number,
+        discriminants: &[(i64, &'static str)],
+        visitor: V,
+        hint: Hint,
+    ) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        let found = discriminants
+            .iter()
+            .find(|(discriminant, _)| number.as_i64() == Some(*discriminant));
+        let Some((_, variant)) = found else {
+            let expected: Vec<_> = discriminants
+                .iter()
+                .map(|(discriminant, variant)| format!("{discriminant} (`{variant}`)"))
+                .collect();
+            let param = InvalidParam {
+                reason: Some(format!(
+                    "Unknown discriminant {number}, expected one of {}",
+                    expected.join(", ")
+                )),
+                code: code::VARIANT_UNKNOWN.to_owned(),
+                ..mismatch(&self.path, hint, actual(self.value))
+            };
+            return Err(self.collector.reject(&self.path, param, Patch::Synthesize));
+        };
+        let access = EnumDeserializer {
+            name: variant,
+            variant,
+            value: None,
+            path: &self.path,
+            collector: self.collector,
+        };
+        self.finish(visitor.visit_enum(access), hint)
+    }
+
+    /// Visits `array`. Elements of `positional` arrays, such as tuples,
+    /// cannot be left out without shifting the ones after them, so the
+    /// whole array fails in their place.
+    fn visit_array<V>(
+        &self,
+        array: &'de [Value],
+        visitor: V,
+        hint: Hint,
+        positional: bool,
+    ) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        let mut seq = SeqDeserializer {
+            iter: array.iter().enumerate(),
+            positional,
+            path: &self.path,
+            collector: self.collector,
+        };
+        let value = self.finish(visitor.visit_seq(&mut seq), hint)?;
+        let remaining = seq.iter.len();
+        if remaining == 0 {
+            Ok(value)
+        } else {
+            let err = de::Error::invalid_length(array.len(), &"fewer elements in array");
+            self.finish(Err(err), hint)
+        }
+    }
+
+    /// Visits `object`, then the fields in `missing`, known to be missing
+    /// from it, then those in `absent`, which the struct required
+    /// elsewhere. The latter are only reported once the visitor accepts
+    /// them: if it fails instead, the struct is visited again on the next
+    /// run without them.
+    fn visit_object<V>(
+        &self,
+        object: &'de Map<String, Value>,
+        visitor: V,
+        hint: Hint,
+        missing: Vec<&'static str>,
+        absent: Vec<&'static str>,
+    ) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        let mut map = MapDeserializer {
+            iter: object.iter(),
+            missing: missing.into_iter(),
+            absent: absent.into_iter(),
+            injected: Vec::new(),
+            pending: None,
+            path: &self.path,
+            collector: self.collector,
+        };
+        let result = visitor.visit_map(&mut map);
+        if result.is_err() && !map.injected.is_empty() {
+            return Err(self.collector.withdraw(&self.path));
+        }
+        for (field, hint) in map.injected {
+            self.collector.missing(&self.path, field, hint);
+        }
+        let value = self.finish(result, hint)?;
+        let remaining = map.iter.len();
+        if remaining == 0 {
+            Ok(value)
+        } else {
+            let err = de::Error::invalid_length(object.len(), &"fewer elements in map");
+            self.finish(Err(err), hint)
+        }
+    }
+}
+
+macro_rules! deserialize_integer {
+    ($method:ident, $ty:ident, $visit:ident) => {
+        fn $method<V>(self, visitor: V) -> Result<V::Value, DeError>
+        where
+            V: Visitor<'de>,
+        {
+            const HINT: Hint = Hint::new(stringify!($ty), "integer");
+            let Value::Number(number) = self.value else {
+                return self.mismatch(HINT, |synth| synth.$method(visitor));
+            };
+            let value = if let Some(value) = number.as_u64() {
+                $ty::try_from(value).ok()
+            } else if let Some(value) = number.as_i64() {
+                $ty::try_from(value).ok()
+            } else {
+                return self.mismatch(HINT, |synth| synth.$method(visitor));
+            };
+            match value {
+                Some(value) => self.finish(visitor.$visit(value), HINT),
+                None => self.out_of_range(number, HINT, $ty::MIN, $ty::MAX, |synth| {
+                    synth.$method(visitor)
+                }),
+            }
+        }
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'_, 'de> {
+    type Error = DeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::Null => self.finish(visitor.visit_unit(), ANY),
+            Value::Bool(value) => self.finish(visitor.visit_bool(*value), ANY),
+            Value::Number(number) => {
+                let result = if let Some(value) = number.as_u64() {
+                    visitor.visit_u64(value)
+                } else if let Some(value) = number.as_i64() {
+                    visitor.visit_i64(value)
+                } else {
+                    visitor.visit_f64(number.as_f64().unwrap_or(f64::NAN))
+                };
+                self.finish(result, ANY)
+            }
+            Value::String(value) => self.finish(visitor.visit_borrowed_str(value), ANY),
+            Value::Array(array) => {
+                self.collector.buffer(&self.path);
+                self.visit_array(array, visitor, ANY, false)
+            }
+            Value::Object(object) => {
+                self.collector.buffer(&self.path);
+                self.visit_object(object, visitor, ANY, Vec::new(), Vec::new())
+            }
+        }
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::Bool(value) => self.finish(visitor.visit_bool(*value), BOOL),
+            value if self.collector.leniency().bools => match coerce_bool(value) {
+                Some(coerced) => {
+                    let reason = format!("Read {value} as {coerced}");
+                    let note = InvalidParam::new(self.path.pointer(), code::COERCED_BOOL, reason)
+                        .expected(BOOL.into())
+                        .actual(actual(value))
+                        .severity(Severity::Warning);
+                    self.collector.note(note);
+                    self.finish(visitor.visit_bool(coerced), BOOL)
+                }
+                None => self.mismatch(BOOL, |synth| synth.deserialize_bool(visitor)),
+            },
+            _ => self.mismatch(BOOL, |synth| synth.deserialize_bool(visitor)),
+        }
+    }
+
+    deserialize_integer!(deserialize_i8, i8, visit_i8);
+    deserialize_integer!(deserialize_i16, i16, visit_i16);
+    deserialize_integer!(deserialize_i32, i32, visit_i32);
+    deserialize_integer!(deserialize_i64, i64, visit_i64);
+    deserialize_integer!(deserialize_i128, i128, visit_i128);
+    deserialize_integer!(deserialize_u8, u8, visit_u8);
+    deserialize_integer!(deserialize_u16, u16, visit_u16);
+    deserialize_integer!(deserialize_u32, u32, visit_u32);
+    deserialize_integer!(deserialize_u64, u64, visit_u64);
+    deserialize_integer!(deserialize_u128, u128, visit_u128);
+
+    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        let Value::Number(number) = self.value else {
+            return self.mismatch(F32, |synth| synth.deserialize_f32(visitor));
+        };
+        let value = number.as_f64().unwrap_or(f64::NAN);
+        if value.abs() > f64::from(f32::MAX) {
+            return self.out_of_range(
+                number,
+                F32,
+                format!("{:e}", f32::MIN),
+                format!("{:e}", f32::MAX),
+                |synth| synth.deserialize_f32(visitor),
+            );
+        }
+        self.finish(visitor.visit_f32(value as f32), F32)
+    }
+
+    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::Number(number) => {
+                let value = number.as_f64().unwrap_or(f64::NAN);
+                self.finish(visitor.visit_f64(value), F64)
+            }
+            _ => self.mismatch(F64, |synth| synth.deserialize_f64(visitor)),
+        }
+    }
+
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        let Value::String(value) = self.value else {
+            return self.mismatch(CHAR, |synth| synth.deserialize_char(visitor));
+        };
+        let mut chars = value.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => self.finish(visitor.visit_char(c), CHAR),
+            _ => {
+                let param = InvalidParam {
+                    reason: Some(format!(
+                        "Expected a single character, found {} characters",
+                        value.chars().count()
+                    )),
+                    code: code::VALUE_INVALID.to_owned(),
+                    ..mismatch(&self.path, CHAR, actual(self.value))
+                };
+                self.collector
+                    .recover(&self.path, param, |synth| synth.deserialize_char(visitor))
+            }
+        }
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::String(value) => self.finish(visitor.visit_borrowed_str(value), STRING),
+            _ => self.mismatch(STRING, |synth| synth.deserialize_str(visitor)),
+        }
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::String(value) => {
+                self.finish(visitor.visit_borrowed_bytes(value.as_bytes()), BYTES)
+            }
+            Value::Array(array) => self.visit_array(array, visitor, BYTES, false),
+            _ => self.mismatch(BYTES, |synth| synth.deserialize_bytes(visitor)),
+        }
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        const OPTION: Hint = Hint::new("option", "any");
+        match self.value {
+            Value::Null => self.finish(visitor.visit_none(), OPTION),
+            _ => self.finish(visitor.visit_some(self), OPTION),
+        }
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::Null => self.finish(visitor.visit_unit(), UNIT),
+            _ => self.mismatch(UNIT, |synth| synth.deserialize_unit(visitor)),
+        }
+    }
+
+    fn deserialize_unit_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::Null => self.finish(visitor.visit_unit(), Hint::new(name, "null")),
+            _ => self.mismatch(Hint::new(name, "null"), |synth| {
+                synth.deserialize_unit_struct(name, visitor)
+            }),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        self.finish(visitor.visit_newtype_struct(self), Hint::new(name, "any"))
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::Array(array) => self.visit_array(array, visitor, SEQ, false),
+            _ => self.mismatch(SEQ, |synth| synth.deserialize_seq(visitor)),
+        }
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::Array(array) => self.visit_array(array, visitor, TUPLE, true),
+            _ => self.mismatch(TUPLE, |synth| synth.deserialize_tuple(len, visitor)),
+        }
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        let hint = Hint::new(name, "array");
+        match self.value {
+            Value::Array(array) => self.visit_array(array, visitor, hint, true),
+            _ => self.mismatch(hint, |synth| {
+                synth.deserialize_tuple_struct(name, len, visitor)
+            }),
+        }
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::Object(object) => {
+                self.visit_object(object, visitor, MAP, Vec::new(), Vec::new())
+            }
+            _ => self.mismatch(MAP, |synth| synth.deserialize_map(visitor)),
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        let hint = Hint::new(name, "object");
+        match self.value {
+            Value::Object(object) => {
+                let key = (name, fields.as_ptr() as usize);
+                let missing = self.collector.missing_at(&self.path);
+                let absent = self.collector.required_absent(
+                    key,
+                    &self.path,
+                    |field| object.contains_key(field),
+                    &missing,
+                );
+                let result = self.visit_object(object, visitor, hint, missing, absent);
+                if result.is_err() {
+                    self.collector.learn_required(key, &self.path);
+                }
+                result
+            }
+            Value::Array(array) => self.visit_array(array, visitor, hint, true),
+            _ => self.mismatch(hint, |synth| {
+                synth.deserialize_struct(name, fields, visitor)
+            }),
+        }
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        let hint = Hint::new(name, "enum");
+        if let Value::Number(number) = self.value
+            && let Some(discriminants) = self.collector.leniency().discriminants(name, variants)
+        {
+            return self.visit_discriminant(number, &discriminants, visitor, hint);
+        }
+        let (variant, value) = match self.value {
+            Value::String(variant) => (variant.as_str(), None),
+            Value::Object(object) if object.len() == 1 => {
+                let (variant, value) = object.iter().next().expect("object has one entry");
+                (variant.as_str(), Some(value))
+            }
+            Value::Object(object) => {
+                let param = InvalidParam {
+                    reason: Some(format!(
+                        "Expected an object with a single key naming the variant, found {} keys",
+                        object.len()
+                    )),
+                    ..mismatch(&self.path, hint, actual(self.value))
+                };
+                return self.collector.recover(&self.path, param, |synth| {
+                    synth.deserialize_enum(name, variants, visitor)
+                });
+            }
+            _ => {
+                return self.mismatch(hint, |synth| {
+                    synth.deserialize_enum(name, variants, visitor)
+                });
+            }
+        };
+        let access = EnumDeserializer {
+            name: self.variant_name(variant, variants),
+            variant,
+            value,
+            path: &self.path,
+            collector: self.collector,
+        };
+        self.finish(visitor.visit_enum(access), hint)
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+}
+
+struct SeqDeserializer<'a, 'de> {
+    iter: std::iter::Enumerate<std::slice::Iter<'de, Value>>,
+    positional: bool,
+    path: &'a Path<'a>,
+    collector: &'a Collector,
+}
+
+impl<'de> SeqAccess<'de> for SeqDeserializer<'_, 'de> {
+    type Error = DeError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, DeError>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        for (index, value) in self.iter.by_ref() {
+            let path = Path::Index(self.path, index);
+            return match self.collector.patch_at(&path) {
+                None => ValueDeserializer::new(value, path, self.collector)
+                    .deserialize_seed(seed)
+                    .map(Some),
+                Some(Patch::Synthesize) => self.collector.synthesize(seed, &path).map(Some),
+                Some(Patch::Skip) if self.positional => Err(self.collector.cascade(self.path)),
+                Some(Patch::Skip) => continue,
+            };
+        }
+        Ok(None)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+enum Pending<'de> {
+    Value(&'de str, &'de Value),
+    Synthesize(&'de str),
+    /// A field absent from the payload that the struct required elsewhere.
+    Absent,
+}
+
+struct MapDeserializer<'a, 'de> {
+    iter: serde_json::map::Iter<'de>,
+    missing: std::vec::IntoIter<&'static str>,
+    absent: std::vec::IntoIter<&'static str>,
+    /// The fields of `absent` handed out so far, with the shape the target
+    /// type asked for.
+    injected: Vec<(&'static str, Option<Hint>)>,
+    pending: Option<Pending<'de>>,
+    path: &'a Path<'a>,
+    collector: &'a Collector,
+}
+
+impl<'de> MapAccess<'de> for MapDeserializer<'_, 'de> {
+    type Error = DeError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, DeError>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        for (key, value) in self.iter.by_ref() {
+            let path = Path::Key(self.path, key);
+            self.pending = match self.collector.patch_at(&path) {
+                None => Some(Pending::Value(key, value)),
+                Some(Patch::Synthesize) => Some(Pending::Synthesize(key)),
+                Some(Patch::Skip) => continue,
+            };
+            return seed
+                .deserialize(KeyDeserializer::new(key, path, self.collector))
+                .map(Some);
+        }
+        for key in self.missing.by_ref() {
+            let path = Path::Key(self.path, key);
+            if self.collector.patch_at(&path) == Some(Patch::Skip) {
+                continue;
+            }
+            self.pending = Some(Pending::Synthesize(key));
+            return seed.deserialize(key.into_deserializer()).map(Some);
+        }
+        if let Some(key) = self.absent.next() {
+            self.injected.push((key, None));
+            self.pending = Some(Pending::Absent);
+            return seed.deserialize(key.into_deserializer()).map(Some);
+        }
+        Ok(None)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, DeError>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        match self.pending.take() {
+            Some(Pending::Value(key, value)) => {
+                let path = Path::Key(self.path, key);
+                ValueDeserializer::new(value, path, self.collector).deserialize_seed(seed)
+            }
+            Some(Pending::Synthesize(key)) => {
+                self.collector.synthesize(seed, &Path::Key(self.path, key))
+            }
+            Some(Pending::Absent) => {
+                let hint = Cell::new(None);
+                let value = seed
+                    .deserialize(Synthesizer::new(&hint))
+                    .map_err(|_| DeError::Reported)?;
+                if let Some((_, requested)) = self.injected.last_mut() {
+                    *requested = hint.get();
+                }
+                Ok(value)
+            }
+            None => Err(de::Error::custom("value is missing")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct EnumDeserializer<'a, 'de> {
+    /// Name of the variant, as the target type spells it.
+    name: &'de str,
+    /// Name of the variant, as the payload spells it.
+    variant: &'de str,
+    value: Option<&'de Value>,
+    path: &'a Path<'a>,
+    collector: &'a Collector,
+}
+
+impl<'a, 'de> EnumAccess<'de> for EnumDeserializer<'a, 'de> {
+    type Error = DeError;
+    type Variant = VariantDeserializer<'a, 'de>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), DeError>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(self.name.into_deserializer())?;
+        let access = VariantDeserializer {
+            variant: self.variant,
+            value: self.value,
+            path: self.path,
+            collector: self.collector,
+        };
+        Ok((variant, access))
+    }
+}
+
+struct VariantDeserializer<'a, 'de> {
+    variant: &'de str,
+    value: Option<&'de Value>,
+    path: &'a Path<'a>,
+    collector: &'a Collector,
+}
+
+impl<'de> VariantDeserializer<'_, 'de> {
+    /// Deserializes the variant content with `seed`, honouring patches left
+    /// by earlier runs.
+    fn content<T>(self, seed: T, expected: &str) -> Result<T::Value, DeError>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let Some(value) = self.value else {
+            return Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &expected,
+            ));
+        };
+        let path = Path::Key(self.path, self.variant);
+        match self.collector.patch_at(&path) {
+            None => ValueDeserializer::new(value, path, self.collector).deserialize_seed(seed),
+            Some(Patch::Synthesize) => self.collector.synthesize(seed, &path),
+            Some(Patch::Skip) => Err(self.collector.cascade(self.path)),
+        }
+    }
+}
+
+impl<'de> VariantAccess<'de> for VariantDeserializer<'_, 'de> {
+    type Error = DeError;
+
+    fn unit_variant(self) -> Result<(), DeError> {
+        let path = Path::Key(self.path, self.variant);
+        match self.collector.patch_at(&path) {
+            Some(Patch::Synthesize) => return Ok(()),
+            Some(Patch::Skip) => return Err(self.collector.cascade(self.path)),
+            None => {}
+        }
+        match self.value {
+            None | Some(Value::Null) => Ok(()),
+            Some(value) => {
+                let param = mismatch(&path, UNIT, actual(value));
+                Err(self.collector.reject(&path, param, Patch::Synthesize))
+            }
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, DeError>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.content(seed, "newtype variant")
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        self.content(TupleSeed { len, visitor }, "tuple variant")
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        self.content(StructSeed { fields, visitor }, "struct variant")
+    }
+}
+
+/// Seed deserializing a tuple variant's content with its visitor.
+struct TupleSeed<V> {
+    len: usize,
+    visitor: V,
+}
+
+impl<'de, V: Visitor<'de>> DeserializeSeed<'de> for TupleSeed<V> {
+    type Value = V::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<V::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_tuple(self.len, self.visitor)
+    }
+}
+
+/// Seed deserializing a struct variant's content with its visitor.
+struct StructSeed<V> {
+    fields: &'static [&'static str],
+    visitor: V,
+}
+
+impl<'de, V: Visitor<'de>> DeserializeSeed<'de> for StructSeed<V> {
+    type Value = V::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<V::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_struct("struct variant", self.fields, self.visitor)
+    }
+}
@@ -0,0 +1,1118 @@
+//! Machine-readable descriptions of deserializable types.
+//!
+//! A [`Descriptor`] lists the shape of a type and of every struct and enum
+//! it contains: field names and types, variants and their contents. It is
+//! built at runtime by tracing the type's own `Deserialize` impl, so any
+//! type works without further annotations and the description always
+//! matches what deserialization actually accepts, including
+//! `#[serde(rename)]` and skipped fields.
+//!
+//! ```
+//! use serde::Deserialize;
+//! use serdify::descriptor::{Container, Describe, Fields, Shape};
+//!
+//! #[derive(Deserialize)]
+//! struct Order {
+//!     id: u64,
+//!     #[serde(rename = "lines")]
+//!     items: Vec<Line>,
+//! }
+//!
+//! #[derive(Deserialize)]
+//! struct Line {
+//!     sku: String,
+//!     quantity: Option<u16>,
+//! }
+//!
+//! let descriptor = Order::serdify_descriptor();
+//! assert_eq!(descriptor.root, Shape::Named("Order".to_owned()));
+//!
+//! let Some(Container::Struct(Fields::Named(fields))) = descriptor.types.get("Line") else {
+//!     panic!("Line is a struct");
+//! };
+//! assert_eq!(fields[1].name, "quantity");
+//! assert_eq!(fields[1].shape, Shape::Option(Box::new(Shape::U16)));
+//! ```
+//!
+//! Tracing feeds sample values to the type, so parts of types rejecting
+//! every sample, such as strings parsed into addresses, are described as
+//! [`Shape::Any`]. Constraints checked outside of `Deserialize`, by passes
+//! for instance, are not part of the description.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+use serde::de::{
+    self, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess,
+    Visitor,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::de::DeError;
+
+/// Nesting limit while tracing, guarding against recursive types.
+const MAX_DEPTH: usize = 64;
+
+/// Number of times a variant failing to trace is retried, once other
+/// variants of its enum may have made recursive references traceable.
+const MAX_ATTEMPTS: u32 = 2;
+
+/// Limit on tracing runs, each exploring more enum variants.
+const MAX_RUNS: usize = 1024;
+
+/// Strings fed to types in turn, until one is accepted. Besides the empty
+/// string, these parse into the common standard library and ecosystem
+/// types deserialized from strings.
+const STRING_SAMPLES: &[&str] = &[
+    "",
+    "0",
+    "0.0.0.0",
+    "0.0.0.0:0",
+    "1970-01-01T00:00:00Z",
+    "1970-01-01",
+    "00000000-0000-0000-0000-000000000000",
+    "http://localhost/",
+];
+
+/// Description of a type and of every named type it contains.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Descriptor {
+    /// Shape of the described type itself.
+    pub root: Shape,
+    /// Structs and enums reachable from the root, by name.
+    pub types: BTreeMap<String, Container>,
+}
+
+/// Shape of a value in the serde data model.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Shape {
+    /// Anything, for self-describing types such as `serde_json::Value` and
+    /// for parts that could not be traced.
+    Any,
+    Unit,
+    Bool,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    F32,
+    F64,
+    Char,
+    String,
+    Bytes,
+    Option(Box<Shape>),
+    /// A sequence of any length, such as a `Vec` or a set.
+    Seq(Box<Shape>),
+    /// A sequence of fixed length, such as a tuple or an array.
+    Tuple(Vec<Shape>),
+    Map {
+        key: Box<Shape>,
+        value: Box<Shape>,
+    },
+    /// A struct or enum described in [`Descriptor::types`].
+    Named(String),
+}
+
+/// Description of a named type.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Container {
+    Struct(Fields),
+    Enum(Vec<Variant>),
+}
+
+/// Contents of a struct or enum variant.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Fields {
+    Unit,
+    Newtype(Shape),
+    Tuple(Vec<Shape>),
+    Named(Vec<Field>),
+}
+
+/// A named field of a struct or struct variant.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Field {
+    /// Name of the field in the payload.
+    pub name: String,
+    pub shape: Shape,
+    /// Whether payloads must contain the field, as opposed to `Option` and
+    /// `#[serde(default)]` fields.
+    pub required: bool,
+}
+
+/// A variant of an enum.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Variant {
+    /// Name of the variant in the payload.
+    pub name: String,
+    /// Contents of the variant, or `None` if they could not be traced.
+    pub fields: Option<Fields>,
+}
+
+/// Gives every deserializable type a [`Descriptor`] of itself.
+pub trait Describe {
+    /// Describes `Self`. See [`describe`].
+    fn serdify_descriptor() -> Descriptor;
+}
+
+impl<T> Describe for T
+where
+    T: for<'de> Deserialize<'de>,
+{
+    fn serdify_descriptor() -> Descriptor {
+        describe::<T>()
+    }
+}
+
+/// Describes `T` by tracing its `Deserialize` impl.
+pub fn describe<T>() -> Descriptor
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let state = RefCell::new(State::default());
+    let mut root = Shape::Any;
+    for _ in 0..MAX_RUNS {
+        {
+            let mut state = state.borrow_mut();
+            state.explored = false;
+            state.strings = 0;
+        }
+        let mut shape = Shape::Any;
+        let _ = T::deserialize(Tracer {
+            state: &state,
+            out: &mut shape,
+            record: true,
+            depth: 0,
+        });
+        root.merge(shape);
+        if !state.borrow().explored {
+            break;
+        }
+    }
+    let mut state = state.into_inner();
+    state.settle();
+    Descriptor {
+        root,
+        types: state.types,
+    }
+}
+
+impl Descriptor {
+    /// Returns the shape of the value at `pointer` in payloads of the
+    /// described type, or `None` if the type has no such location.
+    ///
+    /// `Option`s and newtype structs are looked through on the way down,
+    /// the way they deserialize, and the contents of enum variants sit
+    /// under the variant name.
+    pub fn shape_at(&self, pointer: &str) -> Option<&Shape> {
+        let mut at = Location::Shape(&self.root);
+        for token in crate::pointer::tokens(pointer) {
+            at = self.child(at, &token)?;
+        }
+        match at {
+            Location::Shape(shape) => Some(shape),
+            Location::Fields(_) => None,
+        }
+    }
+
+    /// Returns the named fields of the struct or struct variant at
+    /// `pointer`, in declaration order, or `None` if the value there is not
+    /// such a struct.
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use serdify::descriptor::describe;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Order {
+    ///     lines: Vec<Option<Line>>,
+    /// }
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Line {
+    ///     sku: String,
+    ///     quantity: u16,
+    /// }
+    ///
+    /// let descriptor = describe::<Order>();
+    /// let fields = descriptor.fields_at("#/lines/0").unwrap();
+    /// assert_eq!(fields[0].name, "sku");
+    /// assert_eq!(fields[1].name, "quantity");
+    /// assert!(descriptor.fields_at("#/lines").is_none());
+    /// ```
+    pub fn fields_at(&self, pointer: &str) -> Option<&[Field]> {
+        let mut at = Location::Shape(&self.root);
+        for token in crate::pointer::tokens(pointer) {
+            at = self.child(at, &token)?;
+        }
+        for _ in 0..MAX_DEPTH {
+            at = match at {
+                Location::Fields(Fields::Named(fields)) => return Some(fields),
+                Location::Fields(Fields::Newtype(inner)) => Location::Shape(inner),
+                Location::Shape(Shape::Option(inner)) => Location::Shape(inner),
+                Location::Shape(Shape::Named(name)) => match self.types.get(name)? {
+                    Container::Struct(fields) => Location::Fields(fields),
+                    Container::Enum(_) => return None,
+                },
+                _ => return None,
+            };
+        }
+        None
+    }
+
+    fn child<'a>(&'a self, mut at: Location<'a>, token: &str) -> Option<Location<'a>> {
+        for _ in 0..MAX_DEPTH {
+            let shape = match at {
+                Location::Shape(shape) => shape,
+                Location::Fields(Fields::Unit) => return None,
+                Location::Fields(Fields::Newtype(inner)) => {
+                    at = Location::Shape(inner);
+                    continue;
+                }
+                Location::Fields(Fields::Tuple(elements)) => {
+                    return elements
+                        .get(token.parse::<usize>().ok()?)
+                        .map(Location::Shape);
+                }
+                Location::Fields(Fields::Named(fields)) => {
+                    let field = fields.iter().find(|field| field.name == token)?;
+                    return Some(Location::Shape(&field.shape));
+                }
+            };
+            at = match shape {
+                Shape::Any => return Some(Location::Shape(shape)),
+                Shape::Option(inner) => Location::Shape(inner),
+                Shape::Seq(inner) => {
+                    token.parse::<usize>().ok()?;
+                    return Some(Location::Shape(inner));
+                }
+                Shape::Tuple(elements) => {
+                    return elements
+                        .get(token.parse::<usize>().ok()?)
+                        .map(Location::Shape);
+                }
+                Shape::Map { value, .. } => return Some(Location::Shape(value)),
+                Shape::Named(name) => match self.types.get(name)? {
+                    Container::Struct(fields) => Location::Fields(fields),
+                    Container::Enum(variants) => {
+                        let variant = variants.iter().find(|variant| variant.name == token)?;
+                        return match variant.fields.as_ref()? {
+                            Fields::Newtype(inner) => Some(Location::Shape(inner)),
+                            Fields::Unit => None,
+                            fields => Some(Location::Fields(fields)),
+                        };
+                    }
+                },
+                _ => return None,
+            };
+        }
+        None
+    }
+}
+
+/// A position within a [`Descriptor`], for [`Descriptor::shape_at`] and
+/// [`Descriptor::fields_at`].
+#[derive(Clone, Copy)]
+enum Location<'a> {
+    Shape(&'a Shape),
+    /// The contents of a struct or struct variant, which have no shape of
+    /// their own.
+    Fields(&'a Fields),
+}
+
+/// Formats the shape the way the matching Rust type is written, such as
+/// `Option<u8>` or `Vec<Line>`.
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Shape::Any => "any",
+            Shape::Unit => "()",
+            Shape::Bool => "bool",
+            Shape::I8 => "i8",
+            Shape::I16 => "i16",
+            Shape::I32 => "i32",
+            Shape::I64 => "i64",
+            Shape::I128 => "i128",
+            Shape::U8 => "u8",
+            Shape::U16 => "u16",
+            Shape::U32 => "u32",
+            Shape::U64 => "u64",
+            Shape::U128 => "u128",
+            Shape::F32 => "f32",
+            Shape::F64 => "f64",
+            Shape::Char => "char",
+            Shape::String => "String",
+            Shape::Bytes => "bytes",
+            Shape::Option(inner) => return write!(f, "Option<{inner}>"),
+            Shape::Seq(inner) => return write!(f, "Vec<{inner}>"),
+            Shape::Tuple(elements) => {
+                f.write_str("(")?;
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{element}")?;
+                }
+                return f.write_str(")");
+            }
+            Shape::Map { key, value } => return write!(f, "Map<{key}, {value}>"),
+            Shape::Named(name) => name,
+        };
+        f.write_str(name)
+    }
+}
+
+impl Shape {
+    /// Fills in parts of `self` still [`Shape::Any`] from `other`.
+    fn merge(&mut self, other: Shape) {
+        match (self, other) {
+            (this @ Shape::Any, other) => *this = other,
+            (Shape::Option(this), Shape::Option(other)) | (Shape::Seq(this), Shape::Seq(other)) => {
+                this.merge(*other);
+            }
+            (Shape::Tuple(this), Shape::Tuple(other)) => merge_all(this, other),
+            (Shape::Map { key, value }, Shape::Map { key: k, value: v }) => {
+                key.merge(*k);
+                value.merge(*v);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Fields {
+    fn merge(&mut self, other: Fields) {
+        match (self, other) {
+            (Fields::Newtype(this), Fields::Newtype(other)) => this.merge(other),
+            (Fields::Tuple(this), Fields::Tuple(other)) => merge_all(this, other),
+            (Fields::Named(this), Fields::Named(other)) => {
+                for (this, other) in this.iter_mut().zip(other) {
+                    this.shape.merge(other.shape);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Container {
+    fn merge(&mut self, other: Container) {
+        match (self, other) {
+            (Container::Struct(this), Container::Struct(other)) => this.merge(other),
+            (Container::Enum(this), Container::Enum(other)) => {
+                for (this, other) in this.iter_mut().zip(other) {
+                    match (&mut this.fields, other.fields) {
+                        (Some(this), Some(other)) => this.merge(other),
+                        (this @ None, other) => *this = other,
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn merge_all(this: &mut [Shape], other: Vec<Shape>) {
+    for (this, other) in this.iter_mut().zip(other) {
+        this.merge(other);
+    }
+}
+
+#[derive(Default)]
+struct State {
+    types: BTreeMap<String, Container>,
+    /// Named types being traced, to detect recursion.
+    stack: Vec<&'static str>,
+    enums: HashMap<&'static str, Exploration>,
+    /// Whether the current run traced something for the first time.
+    explored: bool,
+    /// Number of strings requested so far in the current run.
+    strings: usize,
+    /// Index into [`STRING_SAMPLES`] for each string requested in a run, by
+    /// order of request, once the first sample was rejected.
+    samples: HashMap<usize, usize>,
+    /// Which fields are required, by struct or by enum and variant.
+    probes: HashMap<Owner, Probe>,
+}
+
+/// Struct name, or enum and variant names, owning a list of fields.
+type Owner = (&'static str, &'static str);
+
+/// Progress finding out which fields of a struct or variant are required,
+/// by leaving one out per run.
+#[derive(Default)]
+struct Probe {
+    traced: bool,
+    required: HashMap<&'static str, bool>,
+    attempts: HashMap<&'static str, u32>,
+}
+
+/// Progress tracing the variants of one enum.
+#[derive(Default)]
+struct Exploration {
+    complete: BTreeSet<usize>,
+    attempts: HashMap<usize, u32>,
+}
+
+impl State {
+    fn register(&mut self, name: &str, container: Container) {
+        match self.types.get_mut(name) {
+            Some(existing) => existing.merge(container),
+            None => {
+                self.types.insert(name.to_owned(), container);
+            }
+        }
+    }
+
+    /// Returns the sample for the next string requested, with its order.
+    fn next_string(&mut self) -> (usize, &'static str) {
+        let ordinal = self.strings;
+        self.strings += 1;
+        let sample = self.samples.get(&ordinal).copied().unwrap_or(0);
+        (ordinal, STRING_SAMPLES[sample])
+    }
+
+    /// Moves on to the next sample for a string its type rejected.
+    fn rejected_string(&mut self, ordinal: usize) {
+        let sample = self.samples.entry(ordinal).or_default();
+        if *sample + 1 < STRING_SAMPLES.len() {
+            *sample += 1;
+            self.explored = true;
+        }
+    }
+
+    /// Picks a field of `owner` to leave out, to find out whether the type
+    /// requires it. Fields are only left out once traced.
+    fn omit(
+        &mut self,
+        owner: Owner,
+        names: &[&'static str],
+        explore: bool,
+    ) -> Option<&'static str> {
+        let probe = self.probes.entry(owner).or_default();
+        if !explore || !std::mem::replace(&mut probe.traced, true) {
+            return None;
+        }
+        let name = *names.iter().find(|name| {
+            !probe.required.contains_key(*name)
+                && probe.attempts.get(*name).copied().unwrap_or(0) < MAX_ATTEMPTS
+        })?;
+        *probe.attempts.entry(name).or_default() += 1;
+        self.explored = true;
+        Some(name)
+    }
+
+    /// Records the outcome of leaving out `name`: an error naming it means
+    /// the field is required, success means it is not, and any other error
+    /// is inconclusive.
+    fn probed(&mut self, owner: Owner, name: &'static str, error: Option<&DeError>) {
+        let probe = self.probes.entry(owner).or_default();
+        match error {
+            None => {
+                probe.required.insert(name, false);
+            }
+            Some(DeError::MissingField(field)) if *field == name => {
+                probe.required.insert(name, true);
+            }
+            Some(_) => {}
+        }
+    }
+
+    /// Marks the fields found to be optional in every registered type.
+    fn settle(&mut self) {
+        for (name, container) in &mut self.types {
+            match container {
+                Container::Struct(fields) => settle(self.probes.get(&(name, "")), fields),
+                Container::Enum(variants) => {
+                    for variant in variants {
+                        if let Some(fields) = &mut variant.fields {
+                            settle(self.probes.get(&(name, &variant.name)), fields);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Picks the variant of `name` to trace: one not traced yet, or else one
+    /// known to trace successfully, which also ends recursion.
+    fn choose(&mut self, name: &'static str, variants: usize, explore: bool) -> usize {
+        let exploration = self.enums.entry(name).or_default();
+        let attempts = |index: &usize| exploration.attempts.get(index).copied().unwrap_or(0);
+        let fresh = (0..variants)
+            .filter(|index| !exploration.complete.contains(index) && attempts(index) < MAX_ATTEMPTS)
+            .min_by_key(attempts);
+        match fresh {
+            Some(index) if explore => {
+                *exploration.attempts.entry(index).or_default() += 1;
+                self.explored = true;
+                index
+            }
+            _ => exploration.complete.first().copied().unwrap_or(0),
+        }
+    }
+}
+
+/// Deserializer feeding sample values to a type and recording the shape
+/// requested at each step into `out`.
+///
+/// Named types already being traced further up are fed the smallest value
+/// that fits instead, without recording: `None`, empty sequences and maps
+/// and a variant known to trace successfully.
+struct Tracer<'a> {
+    state: &'a RefCell<State>,
+    out: &'a mut Shape,
+    record: bool,
+    depth: usize,
+}
+
+impl<'a> Tracer<'a> {
+    fn child<'b>(
+        state: &'b RefCell<State>,
+        out: &'b mut Shape,
+        record: bool,
+        depth: usize,
+    ) -> Tracer<'b> {
+        Tracer {
+            state,
+            out,
+            record,
+            depth: depth + 1,
+        }
+    }
+
+    fn guard(&self) -> Result<(), DeError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(de::Error::custom("recursion limit exceeded"));
+        }
+        Ok(())
+    }
+
+    /// Enters the named type `name`, returning whether to record it.
+    fn enter(&mut self, name: &'static str) -> bool {
+        *self.out = Shape::Named(name.to_owned());
+        let mut state = self.state.borrow_mut();
+        let record = self.record && !state.stack.contains(&name);
+        if record {
+            state.stack.push(name);
+        }
+        record
+    }
+
+    fn leave(&self, name: &str, container: Option<Container>) {
+        let mut state = self.state.borrow_mut();
+        state.stack.pop();
+        if let Some(container) = container {
+            state.register(name, container);
+        }
+    }
+}
+
+macro_rules! trace_primitive {
+    ($method:ident, $visit:ident, $shape:ident $(, $value:expr)?) => {
+        fn $method<V>(self, visitor: V) -> Result<V::Value, DeError>
+        where
+            V: Visitor<'de>,
+        {
+            *self.out = Shape::$shape;
+            visitor.$visit($($value)?)
+        }
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Tracer<'_> {
+    type Error = DeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        *self.out = Shape::Any;
+        visitor.visit_unit()
+    }
+
+    trace_primitive!(deserialize_bool, visit_bool, Bool, false);
+    trace_primitive!(deserialize_i8, visit_i8, I8, 1);
+    trace_primitive!(deserialize_i16, visit_i16, I16, 1);
+    trace_primitive!(deserialize_i32, visit_i32, I32, 1);
+    trace_primitive!(deserialize_i64, visit_i64, I64, 1);
+    trace_primitive!(deserialize_i128, visit_i128, I128, 1);
+    trace_primitive!(deserialize_u8, visit_u8, U8, 1);
+    trace_primitive!(deserialize_u16, visit_u16, U16, 1);
+    trace_primitive!(deserialize_u32, visit_u32, U32, 1);
+    trace_primitive!(deserialize_u64, visit_u64, U64, 1);
+    trace_primitive!(deserialize_u128, visit_u128, U128, 1);
+    trace_primitive!(deserialize_f32, visit_f32, F32, 1.0);
+    trace_primitive!(deserialize_f64, visit_f64, F64, 1.0);
+    trace_primitive!(deserialize_char, visit_char, Char, 'a');
+    trace_primitive!(deserialize_bytes, visit_borrowed_bytes, Bytes, &[]);
+    trace_primitive!(deserialize_byte_buf, visit_borrowed_bytes, Bytes, &[]);
+    trace_primitive!(deserialize_unit, visit_unit, Unit);
+    trace_primitive!(deserialize_identifier, visit_borrowed_str, String, "");
+    trace_primitive!(deserialize_ignored_any, visit_unit, Any);
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        *self.out = Shape::String;
+        let (ordinal, sample) = self.state.borrow_mut().next_string();
+        let result = visitor.visit_borrowed_str(sample);
+        if result.is_err() {
+            self.state.borrow_mut().rejected_string(ordinal);
+        }
+        result
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        let mut inner = Shape::Any;
+        let result = if self.record {
+            visitor.visit_some(Tracer::child(self.state, &mut inner, true, self.depth))
+        } else {
+            visitor.visit_none()
+        };
+        *self.out = Shape::Option(Box::new(inner));
+        result
+    }
+
+    fn deserialize_unit_struct<V>(
+        mut self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        let record = self.enter(name);
+        let result = visitor.visit_unit();
+        if record {
+            self.leave(name, Some(Container::Struct(Fields::Unit)));
+        }
+        result
+    }
+
+    fn deserialize_newtype_struct<V>(
+        mut self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        self.guard()?;
+        let record = self.enter(name);
+        let mut inner = Shape::Any;
+        let result =
+            visitor.visit_newtype_struct(Tracer::child(self.state, &mut inner, record, self.depth));
+        if record {
+            self.leave(name, Some(Container::Struct(Fields::Newtype(inner))));
+        }
+        result
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        self.guard()?;
+        let mut shapes = vec![Shape::Any; usize::from(self.record)];
+        let result = visitor.visit_seq(Elements::new(&self, &mut shapes, true));
+        *self.out = Shape::Seq(Box::new(shapes.pop().unwrap_or(Shape::Any)));
+        result
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        self.guard()?;
+        let mut shapes = vec![Shape::Any; len];
+        let result = visitor.visit_seq(Elements::new(&self, &mut shapes, false));
+        *self.out = Shape::Tuple(shapes);
+        result
+    }
+
+    fn deserialize_tuple_struct<V>(
+        mut self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        self.guard()?;
+        let record = self.enter(name);
+        let mut shapes = vec![Shape::Any; len];
+        self.record = record;
+        let result = visitor.visit_seq(Elements::new(&self, &mut shapes, false));
+        if record {
+            self.leave(name, Some(Container::Struct(Fields::Tuple(shapes))));
+        }
+        result
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        self.guard()?;
+        let (mut key, mut value) = (Shape::Any, Shape::Any);
+        let result = visitor.visit_map(Entries {
+            state: self.state,
+            key: &mut key,
+            value: &mut value,
+            remaining: self.record,
+            depth: self.depth,
+        });
+        *self.out = Shape::Map {
+            key: Box::new(key),
+            value: Box::new(value),
+        };
+        result
+    }
+
+    fn deserialize_struct<V>(
+        mut self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        self.guard()?;
+        let record = self.enter(name);
+        let (result, fields) =
+            trace_members(self.state, (name, ""), fields, record, self.depth, visitor);
+        if record {
+            self.leave(name, Some(Container::Struct(fields)));
+        }
+        result
+    }
+
+    fn deserialize_enum<V>(
+        mut self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        self.guard()?;
+        if variants.is_empty() {
+            return Err(de::Error::custom("enum has no variants"));
+        }
+        let record = self.enter(name);
+        let index = self.state.borrow_mut().choose(name, variants.len(), record);
+        let mut fields = None;
+        let result = visitor.visit_enum(Chosen {
+            state: self.state,
+            owner: name,
+            variant: variants[index],
+            fields: &mut fields,
+            record,
+            depth: self.depth,
+        });
+        if result.is_ok() {
+            let mut state = self.state.borrow_mut();
+            let exploration = state.enums.entry(name).or_default();
+            exploration.complete.insert(index);
+        }
+        if record {
+            let mut fields = Some(fields);
+            let variants = variants
+                .iter()
+                .enumerate()
+                .map(|(position, variant)| Variant {
+                    name: (*variant).to_owned(),
+                    fields: if position == index {
+                        fields.take().flatten()
+                    } else {
+                        None
+                    },
+                })
+                .collect();
+            self.leave(name, Some(Container::Enum(variants)));
+        }
+        result
+    }
+}
+
+fn settle(probe: Option<&Probe>, fields: &mut Fields) {
+    if let (Some(probe), Fields::Named(fields)) = (probe, fields) {
+        for field in fields {
+            if probe.required.get(field.name.as_str()) == Some(&false) {
+                field.required = false;
+            }
+        }
+    }
+}
+
+/// Traces the fields `names` of `owner`, possibly leaving one out to find
+/// out whether it is required.
+fn trace_members<'de, V>(
+    state: &RefCell<State>,
+    owner: Owner,
+    names: &'static [&'static str],
+    record: bool,
+    depth: usize,
+    visitor: V,
+) -> (Result<V::Value, DeError>, Fields)
+where
+    V: Visitor<'de>,
+{
+    let omit = state.borrow_mut().omit(owner, names, record);
+    let mut shapes = vec![Shape::Any; names.len()];
+    let result = visitor.visit_map(Members {
+        state,
+        names,
+        shapes: &mut shapes,
+        index: 0,
+        omit,
+        record,
+        depth,
+    });
+    if let Some(name) = omit {
+        state
+            .borrow_mut()
+            .probed(owner, name, result.as_ref().err());
+    }
+    (result, named(names, shapes))
+}
+
+fn named(names: &[&str], shapes: Vec<Shape>) -> Fields {
+    Fields::Named(
+        names
+            .iter()
+            .zip(shapes)
+            .map(|(name, shape)| Field {
+                name: (*name).to_owned(),
+                shape,
+                required: true,
+            })
+            .collect(),
+    )
+}
+
+/// Elements of a sequence or tuple, one per shape. Sequences give up on an
+/// element the type rejects and end early instead.
+struct Elements<'a> {
+    state: &'a RefCell<State>,
+    shapes: std::slice::IterMut<'a, Shape>,
+    record: bool,
+    depth: usize,
+    tolerant: bool,
+}
+
+impl<'a> Elements<'a> {
+    fn new(tracer: &Tracer<'a>, shapes: &'a mut [Shape], tolerant: bool) -> Self {
+        Self {
+            state: tracer.state,
+            shapes: shapes.iter_mut(),
+            record: tracer.record,
+            depth: tracer.depth,
+            tolerant,
+        }
+    }
+}
+
+impl<'de> SeqAccess<'de> for Elements<'_> {
+    type Error = DeError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, DeError>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let Some(shape) = self.shapes.next() else {
+            return Ok(None);
+        };
+        match seed.deserialize(Tracer::child(self.state, shape, self.record, self.depth)) {
+            Ok(element) => Ok(Some(element)),
+            Err(_) if self.tolerant => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.shapes.len())
+    }
+}
+
+/// At most one entry of a map, ending early if the type rejects the key.
+struct Entries<'a> {
+    state: &'a RefCell<State>,
+    key: &'a mut Shape,
+    value: &'a mut Shape,
+    remaining: bool,
+    depth: usize,
+}
+
+impl<'de> MapAccess<'de> for Entries<'_> {
+    type Error = DeError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, DeError>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if !std::mem::take(&mut self.remaining) {
+            return Ok(None);
+        }
+        Ok(seed
+            .deserialize(Tracer::child(self.state, self.key, true, self.depth))
+            .ok())
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, DeError>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        seed.deserialize(Tracer::child(self.state, self.value, true, self.depth))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(usize::from(self.remaining))
+    }
+}
+
+/// Every field of a struct or struct variant, in declaration order, except
+/// `omit`.
+struct Members<'a> {
+    state: &'a RefCell<State>,
+    names: &'static [&'static str],
+    shapes: &'a mut [Shape],
+    index: usize,
+    omit: Option<&'static str>,
+    record: bool,
+    depth: usize,
+}
+
+impl<'de> MapAccess<'de> for Members<'_> {
+    type Error = DeError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, DeError>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if self.omit.is_some() && self.names.get(self.index).copied() == self.omit {
+            self.index += 1;
+        }
+        match self.names.get(self.index) {
+            Some(name) => seed.deserialize((*name).into_deserializer()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, DeError>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let shape = &mut self.shapes[self.index];
+        self.index += 1;
+        seed.deserialize(Tracer::child(self.state, shape, self.record, self.depth))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.names.len() - self.index)
+    }
+}
+
+/// The variant picked for an enum, recording its contents into `fields`.
+struct Chosen<'a> {
+    state: &'a RefCell<State>,
+    owner: &'static str,
+    variant: &'static str,
+    fields: &'a mut Option<Fields>,
+    record: bool,
+    depth: usize,
+}
+
+impl<'de, 'a> EnumAccess<'de> for Chosen<'a> {
+    type Error = DeError;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self), DeError>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Chosen<'_> {
+    type Error = DeError;
+
+    fn unit_variant(self) -> Result<(), DeError> {
+        *self.fields = Some(Fields::Unit);
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, DeError>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let mut inner = Shape::Any;
+        let result = seed.deserialize(Tracer::child(
+            self.state,
+            &mut inner,
+            self.record,
+            self.depth,
+        ));
+        *self.fields = Some(Fields::Newtype(inner));
+        result
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        let mut shapes = vec![Shape::Any; len];
+        let result = visitor.visit_seq(Elements {
+            state: self.state,
+            shapes: shapes.iter_mut(),
+            record: self.record,
+            depth: self.depth,
+            tolerant: false,
+        });
+        *self.fields = Some(Fields::Tuple(shapes));
+        result
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError>
+    where
+        V: Visitor<'de>,
+    {
+        let owner = (self.owner, self.variant);
+        let (result, fields) =
+            trace_members(self.state, owner, fields, self.record, self.depth, visitor);
+        *self.fields = Some(fields);
+        result
+    }
+}
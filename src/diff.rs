@@ -0,0 +1,163 @@
+//! Explanations of the changes between two versions of a document.
+//!
+//! Deployment tooling reviewing a configuration change wants two answers:
+//! what changed, and whether the new version is still valid. [`explain_diff`]
+//! gives both at once, listing every added, removed and changed value with
+//! its pointer, its values before and after, and the [`Shape`] the target
+//! type declares there.
+//!
+//! ```
+//! use serde::Deserialize;
+//! use serdify::diff::Kind;
+//! use serdify::explain_diff;
+//!
+//! #[derive(Debug, Deserialize)]
+//! struct Config {
+//!     port: u16,
+//!     hosts: Vec<String>,
+//!     debug: Option<bool>,
+//! }
+//!
+//! let old = r#"{"port": 8080, "hosts": ["a"], "debug": true}"#;
+//! let new = r#"{"port": 80000, "hosts": ["a", "b"]}"#;
+//!
+//! let explanation = explain_diff::<Config>(old, new).unwrap();
+//! let changes: Vec<_> = explanation.changes.iter().map(|c| c.to_string()).collect();
+//! assert_eq!(
+//!     changes,
+//!     [
+//!         "#/debug: removed true",
+//!         "#/hosts/1: added \"b\"",
+//!         "#/port: changed 8080 to 80000",
+//!     ]
+//! );
+//! assert_eq!(explanation.changes[2].kind, Kind::Changed);
+//! assert_eq!(explanation.changes[2].shape.as_ref().unwrap().to_string(), "u16");
+//!
+//! let error = explanation.result.unwrap_err();
+//! assert_eq!(error.invalid_params[0].pointer, "#/port");
+//! ```
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::descriptor::{self, Shape};
+use crate::{Error, Result, from_value, pointer};
+
+/// Every change between two documents, and the validation of the new one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation<T> {
+    /// Changes from the old document to the new one, members in name order
+    /// and elements in index order.
+    pub changes: Vec<Change>,
+    /// The new document deserialized into the target type, or every error
+    /// found in it.
+    pub result: Result<T>,
+}
+
+/// A value added, removed or changed by the new document.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change {
+    /// Pointer to the value, in both documents.
+    pub pointer: String,
+    pub kind: Kind,
+    /// Value in the old document, or `None` if it was added.
+    pub before: Option<Value>,
+    /// Value in the new document, or `None` if it was removed.
+    pub after: Option<Value>,
+    /// Shape the target type declares at the pointer, or `None` if the type
+    /// has no such location, like an unknown field.
+    pub shape: Option<Shape>,
+}
+
+/// Category of a [`Change`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    Added,
+    Removed,
+    /// The value was replaced, including by a value of another type.
+    Changed,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (before, after) = (self.before.as_ref(), self.after.as_ref());
+        match (self.kind, before, after) {
+            (Kind::Changed, Some(before), Some(after)) => {
+                write!(f, "{}: changed {before} to {after}", self.pointer)
+            }
+            (Kind::Added, _, Some(after)) => write!(f, "{}: added {after}", self.pointer),
+            (Kind::Removed, Some(before), _) => write!(f, "{}: removed {before}", self.pointer),
+            _ => write!(f, "{}: {:?}", self.pointer, self.kind),
+        }
+    }
+}
+
+/// Lists the changes from `old` to `new` and validates `new` as `T`.
+///
+/// Objects are compared member by member and arrays element by element, so
+/// an element inserted in the middle of an array shows up as changes to the
+/// elements after it. Fails only if either document is not valid JSON.
+pub fn explain_diff<T>(old: &str, new: &str) -> Result<Explanation<T>>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let old: Value = match serde_json::from_str(old) {
+        Ok(value) => value,
+        Err(err) => return Result::Err(Error::syntax(&err)),
+    };
+    let new: Value = match serde_json::from_str(new) {
+        Ok(value) => value,
+        Err(err) => return Result::Err(Error::syntax(&err)),
+    };
+    let descriptor = descriptor::describe::<T>();
+    let mut changes = Vec::new();
+    compare("#", &old, &new, &mut |pointer, kind, before, after| {
+        changes.push(Change {
+            shape: descriptor.shape_at(&pointer).cloned(),
+            pointer,
+            kind,
+            before: before.cloned(),
+            after: after.cloned(),
+        });
+    });
+    Result::Ok(Explanation {
+        changes,
+        result: from_value(&new),
+    })
+}
+
+type Emit<'a> = dyn FnMut(String, Kind, Option<&Value>, Option<&Value>) + 'a;
+
+fn compare(at: &str, old: &Value, new: &Value, emit: &mut Emit<'_>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let member = pointer::join(at, key);
+                match (old.get(key), new.get(key)) {
+                    (Some(old), Some(new)) => compare(&member, old, new, emit),
+                    (Some(old), None) => emit(member, Kind::Removed, Some(old), None),
+                    (None, new) => emit(member, Kind::Added, None, new),
+                }
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            for index in 0..old.len().max(new.len()) {
+                let element = format!("{at}/{index}");
+                match (old.get(index), new.get(index)) {
+                    (Some(old), Some(new)) => compare(&element, old, new, emit),
+                    (Some(old), None) => emit(element, Kind::Removed, Some(old), None),
+                    (None, new) => emit(element, Kind::Added, None, new),
+                }
+            }
+        }
+        (old, new) if old != new => emit(at.to_owned(), Kind::Changed, Some(old), Some(new)),
+        _ => {}
+    }
+}
@@ -0,0 +1,184 @@
+//! Differential testing against `serde_json`.
+//!
+//! serdify is meant as a drop-in replacement for `serde_json`: for any type,
+//! it should accept exactly the payloads `serde_json` accepts, producing the
+//! same values, and only report rejections in more detail. [`compare`] runs
+//! both on one payload and tells whether they agree; [`run`] does so for a
+//! whole corpus, for use in CI against your own types and payloads.
+//!
+//! ```
+//! use serde::Deserialize;
+//! use serdify::difftest;
+//!
+//! #[derive(Debug, PartialEq, Deserialize)]
+//! struct Point {
+//!     x: i32,
+//!     y: i32,
+//! }
+//!
+//! let summary = difftest::run::<Point, _>([
+//!     r#"{"x": 1, "y": 2}"#,
+//!     r#"{"x": 1}"#,
+//!     r#"{"x": 1, "y": 2"#,
+//! ]);
+//!
+//! assert_eq!(summary.inputs, 3);
+//! assert_eq!(summary.accepted, 1);
+//! assert_eq!(summary.rejected, 2);
+//! assert!(summary.divergences.is_empty(), "{summary}");
+//! ```
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::Result;
+
+/// Outcome of running both deserializers on one payload.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    /// Both accepted the payload, producing equal values.
+    Accepted,
+    /// Both rejected the payload.
+    Rejected,
+    /// The deserializers disagree.
+    Diverged(Divergence),
+}
+
+/// A payload the two deserializers disagree about.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Divergence {
+    /// The payload.
+    pub input: String,
+    pub kind: Kind,
+    /// What `serde_json` produced: the value in debug notation, or its
+    /// error message.
+    pub serde_json: String,
+    /// What serdify produced, in the same notation.
+    pub serdify: String,
+}
+
+/// Category of a [`Divergence`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Kind {
+    /// Only `serde_json` accepted the payload.
+    OnlySerdeJsonAccepted,
+    /// Only serdify accepted the payload.
+    OnlySerdifyAccepted,
+    /// Both accepted the payload, producing different values.
+    ValuesDiffer,
+}
+
+/// Counts of the outcomes over a corpus, with every divergence.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Summary {
+    /// Number of payloads compared.
+    pub inputs: usize,
+    /// Payloads both deserializers accepted alike.
+    pub accepted: usize,
+    /// Payloads both deserializers rejected.
+    pub rejected: usize,
+    pub divergences: Vec<Divergence>,
+}
+
+/// Deserializes `input` into `T` with both `serde_json` and serdify, and
+/// compares the outcomes.
+pub fn compare<T>(input: &str) -> Comparison
+where
+    T: for<'de> Deserialize<'de> + PartialEq + fmt::Debug,
+{
+    let expected = serde_json::from_str::<T>(input);
+    let actual = crate::from_str::<T>(input);
+    let (kind, serde_json, serdify) = match (expected, actual) {
+        (Ok(expected), Result::Ok(actual)) if expected == actual => return Comparison::Accepted,
+        (Err(_), Result::Err(_)) => return Comparison::Rejected,
+        (Ok(expected), Result::Ok(actual)) => (
+            Kind::ValuesDiffer,
+            format!("{expected:?}"),
+            format!("{actual:?}"),
+        ),
+        (Ok(expected), Result::Err(error)) => (
+            Kind::OnlySerdeJsonAccepted,
+            format!("{expected:?}"),
+            error.to_string(),
+        ),
+        (Err(err), Result::Ok(actual)) => (
+            Kind::OnlySerdifyAccepted,
+            err.to_string(),
+            format!("{actual:?}"),
+        ),
+    };
+    Comparison::Diverged(Divergence {
+        input: input.to_owned(),
+        kind,
+        serde_json,
+        serdify,
+    })
+}
+
+/// Compares every payload of `inputs`, see [`compare`].
+pub fn run<T, I>(inputs: I) -> Summary
+where
+    T: for<'de> Deserialize<'de> + PartialEq + fmt::Debug,
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let mut summary = Summary::default();
+    for input in inputs {
+        summary.inputs += 1;
+        match compare::<T>(input.as_ref()) {
+            Comparison::Accepted => summary.accepted += 1,
+            Comparison::Rejected => summary.rejected += 1,
+            Comparison::Diverged(divergence) => summary.divergences.push(divergence),
+        }
+    }
+    summary
+}
+
+/// Panics, listing every divergence, unless both deserializers agree on
+/// every payload of `inputs`.
+#[track_caller]
+pub fn assert_agree<T, I>(inputs: I)
+where
+    T: for<'de> Deserialize<'de> + PartialEq + fmt::Debug,
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    let summary = run::<T, I>(inputs);
+    assert!(summary.divergences.is_empty(), "{summary}");
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            Kind::OnlySerdeJsonAccepted => "only serde_json accepted",
+            Kind::OnlySerdifyAccepted => "only serdify accepted",
+            Kind::ValuesDiffer => "values differ for",
+        };
+        write!(
+            f,
+            "{kind} {}\n  serde_json: {}\n  serdify: {}",
+            self.input, self.serde_json, self.serdify
+        )
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} inputs: {} accepted, {} rejected, {} diverged",
+            self.inputs,
+            self.accepted,
+            self.rejected,
+            self.divergences.len()
+        )?;
+        for divergence in &self.divergences {
+            write!(f, "\n- {divergence}")?;
+        }
+        Ok(())
+    }
+}
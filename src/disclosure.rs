@@ -0,0 +1,200 @@
+//! Controls how much of a validation error is shown to whom.
+//!
+//! Detailed errors are great for developers and equally helpful to someone
+//! probing a public endpoint. [`Disclosure`] returns the full problem
+//! document to trusted callers and to anonymous callers within a rate limit,
+//! and otherwise a redacted one holding only the number of errors and a
+//! reference. The full error is handed to an [`Observer`] together with the
+//! reference, so it can be logged and looked up later.
+//!
+//! References are an HMAC-SHA256 of the error under a secret key, so a
+//! caller cannot confirm a guess at the hidden detail by hashing it. The key
+//! is random unless set with [`Disclosure::secret`].
+//!
+//! ```
+//! use serdify::{Caller, Disclosure, from_str};
+//!
+//! let disclosure = Disclosure::new();
+//! let error = from_str::<(u8, u8)>("[300, -1]").unwrap_err();
+//!
+//! let full = disclosure.disclose(error.clone(), Caller::Trusted);
+//! assert_eq!(full.invalid_params.len(), 2);
+//!
+//! let redacted = disclosure.disclose(error, Caller::Anonymous("203.0.113.7"));
+//! assert!(redacted.invalid_params.is_empty());
+//! assert!(redacted.reference.is_some());
+//! assert_eq!(redacted.detail.as_deref(), Some("The payload has 2 invalid parameters."));
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::observer::Observer;
+use crate::{Error, digest};
+
+/// Number of tracked anonymous callers above which expired windows are
+/// pruned.
+const PRUNE_AT: usize = 4096;
+
+/// Who receives an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Caller<'a> {
+    /// An authenticated or internal caller, always given full detail.
+    Trusted,
+    /// An unauthenticated caller, identified by a key such as its IP address
+    /// for rate limiting.
+    Anonymous(&'a str),
+}
+
+/// Decides whether a caller sees the full error or a redacted one.
+pub struct Disclosure {
+    limit: Option<(u32, Duration)>,
+    observer: Option<Arc<dyn Observer>>,
+    secret: Vec<u8>,
+    windows: Mutex<HashMap<String, Window>>,
+}
+
+impl Default for Disclosure {
+    fn default() -> Self {
+        Self {
+            limit: None,
+            observer: None,
+            secret: random_secret(),
+            windows: Mutex::default(),
+        }
+    }
+}
+
+struct Window {
+    start: Instant,
+    count: u32,
+}
+
+impl Disclosure {
+    /// Creates a disclosure redacting every error for anonymous callers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the operating system cannot provide a random key.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gives each anonymous caller full detail for up to `limit` errors per
+    /// `window`, after which its errors are redacted until the window ends.
+    pub fn allow(mut self, limit: u32, window: Duration) -> Self {
+        self.limit = Some((limit, window));
+        self
+    }
+
+    /// Sets the observer receiving the full error of every redaction.
+    pub fn observer(mut self, observer: impl Observer + 'static) -> Self {
+        self.observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// Sets the key references are derived with.
+    ///
+    /// The default key is random, so the same error gets a different
+    /// reference from every disclosure. Share a key between instances and
+    /// deployments to keep references comparable, and keep it as secret as
+    /// any other server credential.
+    ///
+    /// ```
+    /// use serdify::{Caller, Disclosure, from_str};
+    ///
+    /// let error = from_str::<(u8, u8)>("[300, -1]").unwrap_err();
+    /// let reference = |disclosure: &Disclosure| {
+    ///     disclosure
+    ///         .disclose(error.clone(), Caller::Anonymous("203.0.113.7"))
+    ///         .reference
+    /// };
+    ///
+    /// let secret = b"loaded from the server's configuration";
+    /// let a = Disclosure::new().secret(secret.as_slice());
+    /// let b = Disclosure::new().secret(secret.as_slice());
+    /// assert_eq!(reference(&a), reference(&b));
+    /// assert_ne!(reference(&Disclosure::new()), reference(&Disclosure::new()));
+    /// ```
+    pub fn secret(mut self, secret: impl Into<Vec<u8>>) -> Self {
+        self.secret = secret.into();
+        self
+    }
+
+    /// Returns `error` as `caller` may see it.
+    ///
+    /// Syntax errors carry no information about the target type and are
+    /// always returned in full.
+    pub fn disclose(&self, error: Error, caller: Caller<'_>) -> Error {
+        match caller {
+            Caller::Anonymous(key) if !error.is_syntax() && !self.admit(key) => self.redact(error),
+            _ => error,
+        }
+    }
+
+    fn admit(&self, key: &str) -> bool {
+        let Some((limit, window)) = self.limit else {
+            return false;
+        };
+        let now = Instant::now();
+        let mut windows = self.windows.lock().unwrap_or_else(|err| err.into_inner());
+        if windows.len() >= PRUNE_AT {
+            windows.retain(|_, entry| now.duration_since(entry.start) < window);
+        }
+        let entry = windows.entry(key.to_owned()).or_insert(Window {
+            start: now,
+            count: 0,
+        });
+        if now.duration_since(entry.start) >= window {
+            *entry = Window {
+                start: now,
+                count: 0,
+            };
+        }
+        entry.count = entry.count.saturating_add(1);
+        entry.count <= limit
+    }
+
+    fn redact(&self, error: Error) -> Error {
+        let reference = self.reference(&error);
+        if let Some(observer) = &self.observer {
+            observer.redacted(&reference, &error);
+        }
+        let count = error.invalid_params.len();
+        let detail = match count {
+            1 => "The payload has 1 invalid parameter.".to_owned(),
+            _ => format!("The payload has {count} invalid parameters."),
+        };
+        Error {
+            title: error.title,
+            status: error.status,
+            detail: Some(detail),
+            reference: Some(reference),
+            ..Error::new(Vec::new())
+        }
+    }
+
+    /// Authenticates the serialized error under the secret, keeping 128 bits.
+    fn reference(&self, error: &Error) -> String {
+        let json = serde_json::to_vec(error).unwrap_or_default();
+        digest::hex(&digest::hmac_sha256(&self.secret, &json)[..16])
+    }
+}
+
+impl fmt::Debug for Disclosure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Disclosure")
+            .field("limit", &self.limit)
+            .field("observer", &self.observer.is_some())
+            .finish()
+    }
+}
+
+/// Draws a 256-bit key from the operating system's random number generator.
+fn random_secret() -> Vec<u8> {
+    let mut secret = vec![0; 32];
+    getrandom::fill(&mut secret).expect("the operating system provides random bytes");
+    secret
+}
@@ -0,0 +1,150 @@
+//! Detection of keys repeated within an object.
+//!
+//! Parsing into a [`Value`] keeps the last of several members of the same
+//! name without a word, where other parsers keep the first or reject the
+//! object. The document is therefore parsed here with a visitor building
+//! the same [`Value`] while noting every repeated key.
+
+use std::cell::RefCell;
+use std::fmt;
+
+use serde::Deserialize;
+use serde::de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor};
+use serde_json::{Map, Number, Value};
+
+use crate::pointer::Path;
+use crate::{ExpectedOrActual, InvalidParam, code};
+
+/// A parsed document, with a finding for every repeated key.
+pub(crate) struct Checked {
+    pub(crate) value: Value,
+    pub(crate) duplicates: Vec<InvalidParam>,
+}
+
+impl<'de> Deserialize<'de> for Checked {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let duplicates = RefCell::new(Vec::new());
+        let value = Seed {
+            path: &Path::Root,
+            duplicates: &duplicates,
+        }
+        .deserialize(deserializer)?;
+        Ok(Self {
+            value,
+            duplicates: duplicates.into_inner(),
+        })
+    }
+}
+
+/// Builds the value at `path`, like [`Value`]'s own `Deserialize` impl.
+#[derive(Clone, Copy)]
+struct Seed<'a> {
+    path: &'a Path<'a>,
+    duplicates: &'a RefCell<Vec<InvalidParam>>,
+}
+
+impl<'de> DeserializeSeed<'de> for Seed<'_> {
+    type Value = Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Seed<'_> {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("any valid JSON value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Value, E> {
+        Ok(Value::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Value, E> {
+        Ok(Value::Number(value.into()))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Value, E> {
+        Ok(Value::Number(value.into()))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Value, E> {
+        Ok(Number::from_f64(value).map_or(Value::Null, Value::Number))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Value, E> {
+        Ok(Value::String(value.to_owned()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Value, E> {
+        Ok(Value::String(value))
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.deserialize(deserializer)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut array = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        loop {
+            let path = Path::Index(self.path, array.len());
+            let seed = Seed {
+                path: &path,
+                duplicates: self.duplicates,
+            };
+            match seq.next_element_seed(seed)? {
+                Some(value) => array.push(value),
+                None => return Ok(Value::Array(array)),
+            }
+        }
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut object = Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            let path = Path::Key(self.path, &key);
+            let value = map.next_value_seed(Seed {
+                path: &path,
+                duplicates: self.duplicates,
+            })?;
+            if object.contains_key(&key) {
+                self.duplicates.borrow_mut().push(
+                    InvalidParam::new(
+                        path.pointer(),
+                        code::FIELD_DUPLICATE,
+                        format!("Duplicate key `{key}`; parsers disagree on which value wins"),
+                    )
+                    .expected(ExpectedOrActual::new("unique key", "string"))
+                    .actual(ExpectedOrActual::new("duplicate key", "string")),
+                );
+            }
+            object.insert(key, value);
+        }
+        Ok(Value::Object(object))
+    }
+}
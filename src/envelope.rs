@@ -0,0 +1,135 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::revalidate::rebase;
+use crate::{Error, InvalidParam, Result, config, from_str, from_value};
+
+/// A request whose headers, query and body are validated as different
+/// types, the way web frameworks extract them.
+///
+/// Every part is validated even when another one fails, and the errors of
+/// all parts are merged into one [`Error`] whose pointers start with the
+/// name of their part, as in `#/query/page` or `#/body/items/0`.
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_json::json;
+/// use serdify::Envelope;
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Headers {
+///     #[serde(rename = "x-request-id")]
+///     request_id: String,
+/// }
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Query {
+///     page: u32,
+/// }
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Order {
+///     items: Vec<u8>,
+/// }
+///
+/// let headers = json!({"x-request-id": "abc"});
+/// let error = Envelope::<Headers, Query, Order>::from_parts(
+///     &headers,
+///     &json!({"page": -1}),
+///     r#"{"items": [300]}"#,
+/// )
+/// .unwrap_err();
+///
+/// let pointers: Vec<_> = error.invalid_params.iter().map(|p| p.pointer.as_str()).collect();
+/// assert_eq!(pointers, ["#/query/page", "#/body/items/0"]);
+///
+/// let request = Envelope::<Headers, Query, Order>::from_parts(
+///     &headers,
+///     &json!({"page": 2}),
+///     r#"{"items": [3]}"#,
+/// )
+/// .unwrap();
+/// assert_eq!(request.query.page, 2);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Envelope<H, Q, B> {
+    pub headers: H,
+    pub query: Q,
+    pub body: B,
+}
+
+impl<H, Q, B> Envelope<H, Q, B>
+where
+    H: for<'de> Deserialize<'de>,
+    Q: for<'de> Deserialize<'de>,
+    B: for<'de> Deserialize<'de>,
+{
+    /// Validates `headers` and `query`, already gathered into JSON objects,
+    /// and the JSON text of `body`.
+    ///
+    /// A syntax error in the body is reported at `#/body`, alongside the
+    /// errors of the other parts. Missing fields are reported at their part
+    /// or object, named after the field.
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use serde_json::json;
+    /// use serdify::Envelope;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Query {
+    ///     page: u32,
+    ///     size: u32,
+    /// }
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Order {
+    ///     sku: String,
+    ///     quantity: u32,
+    /// }
+    ///
+    /// let error =
+    ///     Envelope::<serde_json::Value, Query, Order>::from_parts(&json!({}), &json!({}), r#"{"sku": "A1"}"#)
+    ///         .unwrap_err();
+    ///
+    /// let missing: Vec<_> = error
+    ///     .invalid_params
+    ///     .iter()
+    ///     .map(|p| (p.pointer.as_str(), p.name.as_str()))
+    ///     .collect();
+    /// assert_eq!(
+    ///     missing,
+    ///     [("#/query", "page"), ("#/query", "size"), ("#/body", "quantity")]
+    /// );
+    /// ```
+    pub fn from_parts(headers: &Value, query: &Value, body: &str) -> Result<Self> {
+        let mut params = Vec::new();
+        let headers = part(from_value(headers), "#/headers", &mut params);
+        let query = part(from_value(query), "#/query", &mut params);
+        let body = part(from_str(body), "#/body", &mut params);
+        match (headers, query, body) {
+            (Some(headers), Some(query), Some(body)) => Result::Ok(Self {
+                headers,
+                query,
+                body,
+            }),
+            _ => {
+                if let Some(limit) = config::current().max_errors {
+                    params.truncate(limit);
+                }
+                Result::Err(Error::new(params))
+            }
+        }
+    }
+}
+
+/// Returns the value of a part, or moves its errors beneath `at`.
+fn part<T>(result: Result<T>, at: &str, params: &mut Vec<InvalidParam>) -> Option<T> {
+    match result {
+        Result::Ok(value) => Some(value),
+        Result::Err(error) => {
+            params.extend(rebase(error, at).invalid_params);
+            None
+        }
+    }
+}
@@ -0,0 +1,376 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::pointer::{self, Style};
+use crate::{ExpectedErrors, SCHEMA_VERSION, code, config};
+
+/// Default title of every validation problem produced by serdify.
+pub(crate) const TITLE: &str = "Your request parameters didn't validate.";
+
+/// Default HTTP status code attached to validation problems.
+pub(crate) const STATUS: u16 = 400;
+
+/// An [RFC 7807](https://datatracker.ietf.org/doc/html/rfc7807) problem
+/// document describing why a JSON payload failed to deserialize.
+///
+/// Serializing this type yields a body that can be returned as-is with the
+/// `application/problem+json` content type.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Error {
+    /// Short, human-readable summary of the problem type.
+    pub title: String,
+    /// HTTP status code generated for this occurrence of the problem.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    /// Human-readable explanation, used for JSON syntax errors.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Every validation error found in the payload.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub invalid_params: Vec<InvalidParam>,
+    /// Reference to the full error when its detail was withheld, see
+    /// [`Disclosure`](crate::Disclosure).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    /// Version of the shape of this payload, see
+    /// [`SCHEMA_VERSION`](crate::SCHEMA_VERSION).
+    #[serde(default = "crate::version::unversioned")]
+    pub schema_version: u32,
+}
+
+/// A single validation error, located with a JSON pointer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InvalidParam {
+    /// Name of the offending field, or its index inside an array.
+    pub name: String,
+    /// Human-readable description of the error.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// What the target type expected at this location.
+    pub expected: ExpectedOrActual,
+    /// What the payload actually contained at this location.
+    pub actual: ExpectedOrActual,
+    /// [RFC 6901](https://datatracker.ietf.org/doc/html/rfc6901) pointer to the
+    /// error location, in URI fragment form (`#/items/0/id`).
+    pub pointer: String,
+    /// [`pointer`](Self::pointer) rendered in the notation the client asked
+    /// for, see [`Error::with_style`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Machine-readable kind of the error, one of the constants in
+    /// [`code`](crate::code).
+    #[serde(default)]
+    pub code: String,
+    /// How serious the finding is; everything the deserializer reports is an
+    /// [`Error`](Severity::Error).
+    #[serde(default, skip_serializing_if = "Severity::is_error")]
+    pub severity: Severity,
+    /// Pointer of the error this one follows from, when the value there is
+    /// so wrong that errors beneath it are mere noise. See
+    /// [`Error::link_causes`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub caused_by: Option<String>,
+    /// Byte offset in the JSON text, for findings about the text rather
+    /// than about a value, such as data after the document.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+}
+
+/// How serious a finding is.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Informational note, such as a normalization applied to the input.
+    Info,
+    /// Suspicious input that was nevertheless accepted.
+    Warning,
+    /// Input that cannot be accepted.
+    #[default]
+    Error,
+}
+
+/// Type information describing either side of a validation error.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ExpectedOrActual {
+    /// Type name, such as `u8`, `string` or the name of a struct.
+    pub r#type: String,
+    /// JSON format of the type, such as `integer`, `string` or `object`.
+    pub format: String,
+}
+
+impl Error {
+    /// Creates an error from a list of validation errors, with the title
+    /// and status of the current [`Config`](crate::config::Config).
+    pub fn new(invalid_params: Vec<InvalidParam>) -> Self {
+        let config = config::current();
+        Self {
+            title: config.title.clone(),
+            status: Some(config.status),
+            detail: None,
+            invalid_params,
+            reference: None,
+            schema_version: SCHEMA_VERSION,
+        }
+    }
+
+    /// Creates an error carrying a JSON syntax error message.
+    pub fn syntax(err: &serde_json::Error) -> Self {
+        let message = err.to_string();
+        let message = match message.rfind(" at line ") {
+            Some(index) => &message[..index],
+            None => &message,
+        };
+        let mut chars = message.chars();
+        let message = match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        };
+        Self {
+            detail: Some(format!(
+                "JSON syntax error at line {}, column {}: {}.",
+                err.line(),
+                err.column(),
+                message
+            )),
+            ..Self::new(Vec::new())
+        }
+    }
+
+    /// Renders the location of every invalid parameter in `style`, keeping
+    /// the canonical pointer alongside.
+    ///
+    /// ```
+    /// use serdify::{from_str, pointer::Style};
+    ///
+    /// let error = from_str::<Vec<u8>>("[1, 2, 300]").unwrap_err().with_style(Style::Bracket);
+    ///
+    /// assert_eq!(error.invalid_params[0].pointer, "#/2");
+    /// assert_eq!(error.invalid_params[0].path.as_deref(), Some("[2]"));
+    /// ```
+    pub fn with_style(mut self, style: Style) -> Self {
+        for param in &mut self.invalid_params {
+            param.set_style(style);
+        }
+        self
+    }
+
+    /// Links every invalid parameter that follows from another one to it,
+    /// through [`InvalidParam::caused_by`].
+    ///
+    /// A value of the wrong type, or naming no variant of its enum, is the
+    /// root cause of every other error at or beneath its pointer: checks of
+    /// its contents were bound to fail.
+    ///
+    /// ```
+    /// use serdify::{Error, InvalidParam, code};
+    ///
+    /// let error = Error::new(vec![
+    ///     InvalidParam::new("#/address", code::TYPE_MISMATCH, "Expected object, found array"),
+    ///     InvalidParam::new("#/address/0", code::VALUE_INVALID, "Expected a street"),
+    ///     InvalidParam::new("#/age", code::RANGE_OVERFLOW, "Value 300 is out of range"),
+    /// ])
+    /// .link_causes();
+    ///
+    /// assert_eq!(error.invalid_params[0].caused_by, None);
+    /// assert_eq!(error.invalid_params[1].caused_by.as_deref(), Some("#/address"));
+    /// assert_eq!(error.invalid_params[2].caused_by, None);
+    /// ```
+    pub fn link_causes(mut self) -> Self {
+        let causes = self.causes();
+        for (param, cause) in self.invalid_params.iter_mut().zip(causes) {
+            param.caused_by = cause;
+        }
+        self
+    }
+
+    /// Returns the pointer of the error each invalid parameter follows from,
+    /// keeping links already recorded in [`InvalidParam::caused_by`].
+    pub(crate) fn causes(&self) -> Vec<Option<String>> {
+        let causes: Vec<_> = self
+            .invalid_params
+            .iter()
+            .filter(|param| is_root_cause(&param.code))
+            .map(|param| param.pointer.as_str())
+            .collect();
+        self.invalid_params
+            .iter()
+            .map(|param| {
+                if param.caused_by.is_some() {
+                    return param.caused_by.clone();
+                }
+                let cause = causes.iter().filter(|cause| {
+                    pointer::starts_with(&param.pointer, cause)
+                        && (**cause != param.pointer || !is_root_cause(&param.code))
+                });
+                // The outermost cause is the root of the cascade.
+                cause
+                    .min_by_key(|cause| cause.len())
+                    .map(|cause| cause.to_string())
+            })
+            .collect()
+    }
+
+    /// Returns `true` if this error meets every expectation of `expected`.
+    /// See [`ExpectedErrors`].
+    pub fn matches(&self, expected: &ExpectedErrors) -> bool {
+        expected.is_met_by(self)
+    }
+
+    /// Returns `true` if the payload could not be parsed as JSON at all.
+    pub fn is_syntax(&self) -> bool {
+        self.invalid_params.is_empty() && self.detail.is_some() && self.reference.is_none()
+    }
+}
+
+impl InvalidParam {
+    /// Creates a finding at `pointer`, named after the pointer's last token.
+    ///
+    /// The expected and actual sides default to `any`; set them with
+    /// [`expected`](Self::expected) and [`actual`](Self::actual) when known.
+    pub fn new(
+        pointer: impl Into<String>,
+        code: impl Into<String>,
+        reason: impl Into<String>,
+    ) -> Self {
+        let pointer = pointer.into();
+        Self {
+            name: pointer::last_token(&pointer),
+            reason: Some(reason.into()),
+            expected: ExpectedOrActual::new("any", "any"),
+            actual: ExpectedOrActual::new("any", "any"),
+            pointer,
+            path: None,
+            code: code.into(),
+            severity: Severity::Error,
+            caused_by: None,
+            offset: None,
+        }
+    }
+
+    /// Creates a finding for the required `field` missing from the object
+    /// at `pointer`. Like the errors of structs missing a field, it is
+    /// located at the object and named after the field.
+    pub(crate) fn missing(pointer: impl Into<String>, field: &str) -> Self {
+        Self {
+            name: field.to_owned(),
+            expected: ExpectedOrActual::new("unknown", "unknown"),
+            actual: ExpectedOrActual::new("undefined", "missing"),
+            ..Self::new(pointer, code::FIELD_MISSING, "missing required field")
+        }
+    }
+
+    /// Sets the expected side of the finding.
+    pub fn expected(mut self, expected: ExpectedOrActual) -> Self {
+        self.expected = expected;
+        self
+    }
+
+    /// Sets the actual side of the finding.
+    pub fn actual(mut self, actual: ExpectedOrActual) -> Self {
+        self.actual = actual;
+        self
+    }
+
+    /// Renders [`pointer`](Self::pointer) in `style` into
+    /// [`path`](Self::path). The default style clears the path, since it
+    /// would only repeat the pointer.
+    pub fn set_style(&mut self, style: Style) {
+        self.path = match style {
+            Style::Pointer => None,
+            style => Some(pointer::render(&self.pointer, style)),
+        };
+    }
+
+    /// Sets the severity of the finding.
+    pub fn severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// Sets the byte offset of the finding in the JSON text.
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+}
+
+/// Returns `true` for codes meaning the value itself is unusable, making
+/// any other error about it or its contents a consequence.
+pub(crate) fn is_root_cause(code: &str) -> bool {
+    matches!(
+        code,
+        code::TYPE_MISMATCH | code::VARIANT_UNKNOWN | code::SYNTAX
+    )
+}
+
+impl Severity {
+    /// Returns `true` for [`Severity::Error`].
+    pub fn is_error(&self) -> bool {
+        *self == Severity::Error
+    }
+}
+
+impl ExpectedOrActual {
+    /// Creates a new type description.
+    pub fn new(r#type: impl Into<String>, format: impl Into<String>) -> Self {
+        Self {
+            r#type: r#type.into(),
+            format: format.into(),
+        }
+    }
+
+    /// Describes the JSON type of `value`.
+    pub fn of(value: &serde_json::Value) -> Self {
+        use serde_json::Value;
+
+        let (r#type, format) = match value {
+            Value::Null => ("null", "null"),
+            Value::Bool(_) => ("bool", "boolean"),
+            Value::Number(number) if number.is_u64() => ("u64", "integer"),
+            Value::Number(number) if number.is_i64() => ("i64", "integer"),
+            Value::Number(_) => ("f64", "number"),
+            Value::String(_) => ("string", "string"),
+            Value::Array(_) => ("array", "array"),
+            Value::Object(_) => ("object", "object"),
+        };
+        Self::new(r#type, format)
+    }
+}
+
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let json = if f.alternate() {
+            serde_json::to_string_pretty(self)
+        } else {
+            serde_json::to_string(self)
+        };
+        f.write_str(&json.map_err(|_| fmt::Error)?)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.title)?;
+        if let Some(detail) = &self.detail {
+            write!(f, " {detail}")?;
+        }
+        for param in &self.invalid_params {
+            write!(f, "\n- {param}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for InvalidParam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.reason {
+            Some(reason) => write!(f, "{}: {}", self.pointer, reason),
+            None => write!(f, "{}: invalid value", self.pointer),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,139 @@
+//! Validation of request and response pairs.
+//!
+//! Gateways and test harnesses often check a whole transaction: the request
+//! against one type and the response against another. [`Exchange`] holds
+//! the [`Options`] of each direction and validates both bodies in one call,
+//! and [`Transaction::report`] combines every finding into one report keyed
+//! by [`Direction`].
+//!
+//! ```
+//! use serde::Deserialize;
+//! use serdify::exchange::{Direction, Exchange};
+//!
+//! #[derive(Debug, Deserialize)]
+//! struct Transfer {
+//!     amount: u32,
+//! }
+//!
+//! #[derive(Debug, Deserialize)]
+//! struct Receipt {
+//!     id: u64,
+//! }
+//!
+//! let transaction = Exchange::new().validate::<Transfer, Receipt>(
+//!     r#"{"amount": -5}"#,
+//!     r#"{"id": "tx-1"}"#,
+//! );
+//! assert!(!transaction.is_ok());
+//!
+//! let report = transaction.report();
+//! let found: Vec<_> = report.findings().map(|(d, p)| (d, p.pointer.as_str())).collect();
+//! assert_eq!(found, [(Direction::Request, "#/amount"), (Direction::Response, "#/id")]);
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::{InvalidParam, Options, Outcome, Policy, Report};
+
+/// Which body of a transaction a finding is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Request,
+    Response,
+}
+
+/// Validates requests and responses, each with its own options.
+#[derive(Debug, Clone, Default)]
+pub struct Exchange {
+    request: Options,
+    response: Options,
+}
+
+/// Outcomes of both bodies of a transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transaction<Req, Res> {
+    pub request: Outcome<Req>,
+    pub response: Outcome<Res>,
+}
+
+/// Every finding about a transaction, by direction.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TransactionReport {
+    #[serde(default)]
+    pub request: Report,
+    #[serde(default)]
+    pub response: Report,
+}
+
+impl Exchange {
+    /// Creates an exchange validating both directions with default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the options requests are validated with.
+    pub fn request(mut self, options: Options) -> Self {
+        self.request = options;
+        self
+    }
+
+    /// Sets the options responses are validated with.
+    pub fn response(mut self, options: Options) -> Self {
+        self.response = options;
+        self
+    }
+
+    /// Deserializes `request` into `Req` and `response` into `Res`.
+    pub fn validate<Req, Res>(&self, request: &str, response: &str) -> Transaction<Req, Res>
+    where
+        Req: for<'de> Deserialize<'de>,
+        Res: for<'de> Deserialize<'de>,
+    {
+        Transaction {
+            request: self.request.from_str(request),
+            response: self.response.from_str(response),
+        }
+    }
+}
+
+impl<Req, Res> Transaction<Req, Res> {
+    /// Returns `true` if both bodies were accepted.
+    pub fn is_ok(&self) -> bool {
+        self.request.result.is_ok() && self.response.result.is_ok()
+    }
+
+    /// Builds a report of every finding of both bodies, errors and warnings
+    /// alike.
+    pub fn report(&self) -> TransactionReport {
+        TransactionReport {
+            request: self.request.report(),
+            response: self.response.report(),
+        }
+    }
+}
+
+impl TransactionReport {
+    /// Returns the report about the body travelling in `direction`.
+    pub fn get(&self, direction: Direction) -> &Report {
+        match direction {
+            Direction::Request => &self.request,
+            Direction::Response => &self.response,
+        }
+    }
+
+    /// Returns every finding, request findings first, tagged with their
+    /// direction.
+    pub fn findings(&self) -> impl Iterator<Item = (Direction, &InvalidParam)> {
+        let request = self.request.findings.iter();
+        let response = self.response.findings.iter();
+        request
+            .map(|finding| (Direction::Request, finding))
+            .chain(response.map(|finding| (Direction::Response, finding)))
+    }
+
+    /// Returns `true` if `policy` accepts both bodies.
+    pub fn passes(&self, policy: &Policy) -> bool {
+        self.request.passes(policy) && self.response.passes(policy)
+    }
+}
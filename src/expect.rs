@@ -0,0 +1,168 @@
+use crate::pointer;
+use crate::report::code_matches;
+use crate::{Error, InvalidParam, Severity};
+
+/// Expectations an [`Error`] is compared with by [`Error::matches`].
+///
+/// Each expectation must be met by at least one invalid parameter of the
+/// error. Other parameters are allowed unless [`only`](Self::only) is set,
+/// so contract tests can assert what matters to them without
+/// over-specifying.
+///
+/// ```
+/// use serdify::{ExpectedError, ExpectedErrors, code, from_str};
+///
+/// let error = from_str::<Vec<(String, u8)>>(r#"[["a", 1], ["b", 300], [3, 4]]"#).unwrap_err();
+///
+/// let range_error = ExpectedError::at("#/**").code(code::RANGE_OVERFLOW);
+/// assert!(error.matches(&ExpectedErrors::new().error(range_error.clone())));
+/// assert!(!error.matches(&ExpectedErrors::new().error(range_error).only()));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ExpectedErrors {
+    errors: Vec<ExpectedError>,
+    only: bool,
+}
+
+/// A pattern for one invalid parameter. Unset fields match anything.
+#[derive(Debug, Clone, Default)]
+pub struct ExpectedError {
+    pointer: Option<String>,
+    name: Option<String>,
+    code: Option<String>,
+    reason: Option<String>,
+    severity: Option<Severity>,
+    expected_type: Option<String>,
+    actual_type: Option<String>,
+}
+
+impl ExpectedErrors {
+    /// Creates an empty set of expectations, met by any error.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expects an invalid parameter matching `error`.
+    pub fn error(mut self, error: ExpectedError) -> Self {
+        self.errors.push(error);
+        self
+    }
+
+    /// Rejects errors holding parameters no expectation matches.
+    pub fn only(mut self) -> Self {
+        self.only = true;
+        self
+    }
+
+    pub(crate) fn is_met_by(&self, error: &Error) -> bool {
+        let params = &error.invalid_params;
+        self.errors
+            .iter()
+            .all(|expected| params.iter().any(|param| expected.is_met_by(param)))
+            && (!self.only
+                || params
+                    .iter()
+                    .all(|param| self.errors.iter().any(|expected| expected.is_met_by(param))))
+    }
+}
+
+impl ExpectedError {
+    /// Matches any invalid parameter.
+    pub fn any() -> Self {
+        Self::default()
+    }
+
+    /// Matches parameters located by `pattern`, which may use the `*` and
+    /// `**` wildcards of [`pointer::matches`].
+    pub fn at(pattern: impl Into<String>) -> Self {
+        Self {
+            pointer: Some(pattern.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Matches parameters named `name`. Missing fields are located at their
+    /// object and named after the field, so this tells them apart.
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use serdify::{ExpectedError, ExpectedErrors, code, from_str};
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct User {
+    ///     id: u32,
+    ///     email: String,
+    ///     name: String,
+    /// }
+    ///
+    /// let error = from_str::<User>(r#"{"name": "Ada"}"#).unwrap_err();
+    /// let missing = |field| ExpectedError::at("#").name(field).code(code::FIELD_MISSING);
+    ///
+    /// let expected = ExpectedErrors::new().error(missing("id")).error(missing("email"));
+    /// assert!(error.matches(&expected.only()));
+    /// assert!(!error.matches(&ExpectedErrors::new().error(missing("name"))));
+    /// ```
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Matches parameters with the given [`code`](crate::code). A code
+    /// ending in `*` matches every code starting with the rest.
+    pub fn code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    /// Matches parameters whose reason contains `text`.
+    pub fn reason_contains(mut self, text: impl Into<String>) -> Self {
+        self.reason = Some(text.into());
+        self
+    }
+
+    /// Matches parameters of the given severity.
+    pub fn severity(mut self, severity: Severity) -> Self {
+        self.severity = Some(severity);
+        self
+    }
+
+    /// Matches parameters expecting the given type.
+    pub fn expected_type(mut self, r#type: impl Into<String>) -> Self {
+        self.expected_type = Some(r#type.into());
+        self
+    }
+
+    /// Matches parameters whose actual value has the given type.
+    pub fn actual_type(mut self, r#type: impl Into<String>) -> Self {
+        self.actual_type = Some(r#type.into());
+        self
+    }
+
+    fn is_met_by(&self, param: &InvalidParam) -> bool {
+        self.pointer
+            .as_ref()
+            .is_none_or(|pattern| pointer::matches(pattern, &param.pointer))
+            && self.name.as_ref().is_none_or(|name| *name == param.name)
+            && self
+                .code
+                .as_ref()
+                .is_none_or(|code| code_matches(code, &param.code))
+            && self.reason.as_ref().is_none_or(|text| {
+                param
+                    .reason
+                    .as_ref()
+                    .is_some_and(|reason| reason.contains(text.as_str()))
+            })
+            && self
+                .severity
+                .is_none_or(|severity| severity == param.severity)
+            && self
+                .expected_type
+                .as_ref()
+                .is_none_or(|r#type| *r#type == param.expected.r#type)
+            && self
+                .actual_type
+                .as_ref()
+                .is_none_or(|r#type| *r#type == param.actual.r#type)
+    }
+}
@@ -0,0 +1,422 @@
+//! A small expression language selecting invalid parameters.
+//!
+//! Expressions compare fields of an [`InvalidParam`] with string literals
+//! and combine the comparisons with `&&`, `||`, `!` and parentheses, so
+//! large reports can be sliced without writing Rust:
+//!
+//! ```text
+//! code == "range.overflow" && pointer startswith "#/items"
+//! severity != "error" || !(reason contains "deprecated")
+//! pointer matches "#/items/*/price"
+//! ```
+//!
+//! The fields are `name`, `reason`, `pointer`, `path`, `code`, `severity`,
+//! `caused_by`, `expected` and `actual`, the last two standing for their
+//! type. Absent optional fields compare as the empty string. The operators
+//! are:
+//!
+//! - `==` and `!=`, comparing whole values;
+//! - `startswith`, `endswith` and `contains`, comparing parts of values;
+//!   `pointer` and `caused_by` start with a pointer only at token
+//!   boundaries, as in [`pointer::starts_with`];
+//! - `matches`, taking a [`pointer::matches`] pattern for `pointer` and
+//!   `caused_by`, and a pattern whose trailing `*` matches any suffix,
+//!   as in [`Policy`](crate::Policy) rules, for other fields.
+//!
+//! `&&` binds tighter than `||`. Literals are double-quoted, with `\"` and
+//! `\\` as escapes. Parentheses and `!` nest at most 128 deep, so
+//! expressions from untrusted sources cannot exhaust the stack.
+//!
+//! ```
+//! use serdify::from_str;
+//!
+//! let error = from_str::<Vec<(String, u8)>>(r#"[["a", 300], [1, 2], ["c", 301]]"#).unwrap_err();
+//!
+//! let overflows = error
+//!     .filter(r##"code == "range.overflow" && pointer startswith "#/2""##)
+//!     .unwrap();
+//! assert_eq!(overflows.invalid_params.len(), 1);
+//! assert_eq!(overflows.invalid_params[0].pointer, "#/2/1");
+//!
+//! assert!(error.filter("code = 1").is_err());
+//!
+//! let deep = format!(r#"{}code == "x"{}"#, "(".repeat(100_000), ")".repeat(100_000));
+//! let refused = error.filter(&deep).unwrap_err();
+//! assert_eq!((refused.offset, refused.message.as_str()), (128, "expression nested too deeply"));
+//! assert!(error.filter(&format!(r#"{}code == "x""#, "!".repeat(100_000))).is_err());
+//!
+//! let wide = vec![r#"code == "x""#; 100_000].join(" || ");
+//! assert!(error.filter(&wide).unwrap().invalid_params.is_empty());
+//! ```
+
+use std::fmt;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use crate::report::code_matches;
+use crate::{Error, InvalidParam, Severity, pointer};
+
+/// Most parentheses and `!` a subexpression may be nested in, keeping the
+/// recursive parser and matcher well within the stack.
+const MAX_DEPTH: usize = 128;
+
+/// A parsed filter expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    root: Node,
+}
+
+/// Why a filter expression could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterError {
+    /// Byte offset of the problem in the expression.
+    pub offset: usize,
+    /// What is wrong there.
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Or(Vec<Node>),
+    And(Vec<Node>),
+    Not(Box<Node>),
+    Compare(Field, Op, String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Name,
+    Reason,
+    Pointer,
+    Path,
+    Code,
+    Severity,
+    CausedBy,
+    Expected,
+    Actual,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    StartsWith,
+    EndsWith,
+    Contains,
+    Matches,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    Literal(String),
+    Eq,
+    Ne,
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+impl Filter {
+    /// Parses `expression`.
+    pub fn parse(expression: &str) -> std::result::Result<Self, FilterError> {
+        let tokens = lex(expression)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            index: 0,
+            end: expression.len(),
+            depth: 0,
+        };
+        let root = parser.or()?;
+        match parser.tokens.get(parser.index) {
+            Some((offset, _)) => Err(FilterError::new(*offset, "expected `&&`, `||` or the end")),
+            None => Ok(Self { root }),
+        }
+    }
+
+    /// Returns `true` if `param` is selected by the filter.
+    pub fn matches(&self, param: &InvalidParam) -> bool {
+        self.root.matches(param)
+    }
+}
+
+impl Error {
+    /// Returns a copy of this error keeping only the invalid parameters
+    /// selected by `expression`. See [`filter`](crate::filter).
+    pub fn filter(&self, expression: &str) -> std::result::Result<Error, FilterError> {
+        let filter = Filter::parse(expression)?;
+        Ok(Error {
+            invalid_params: self
+                .invalid_params
+                .iter()
+                .filter(|param| filter.matches(param))
+                .cloned()
+                .collect(),
+            ..self.clone()
+        })
+    }
+}
+
+impl Node {
+    fn matches(&self, param: &InvalidParam) -> bool {
+        match self {
+            Node::Or(nodes) => nodes.iter().any(|node| node.matches(param)),
+            Node::And(nodes) => nodes.iter().all(|node| node.matches(param)),
+            Node::Not(inner) => !inner.matches(param),
+            Node::Compare(field, op, literal) => {
+                let value = field.value(param);
+                let is_pointer = matches!(field, Field::Pointer | Field::CausedBy);
+                match op {
+                    Op::Eq => value == literal,
+                    Op::Ne => value != literal,
+                    Op::StartsWith if is_pointer => pointer::starts_with(value, literal),
+                    Op::StartsWith => value.starts_with(literal.as_str()),
+                    Op::EndsWith => value.ends_with(literal.as_str()),
+                    Op::Contains => value.contains(literal.as_str()),
+                    Op::Matches if is_pointer => pointer::matches(literal, value),
+                    Op::Matches => code_matches(literal, value),
+                }
+            }
+        }
+    }
+}
+
+impl Field {
+    fn parse(word: &str) -> Option<Self> {
+        Some(match word {
+            "name" => Field::Name,
+            "reason" => Field::Reason,
+            "pointer" => Field::Pointer,
+            "path" => Field::Path,
+            "code" => Field::Code,
+            "severity" => Field::Severity,
+            "caused_by" => Field::CausedBy,
+            "expected" => Field::Expected,
+            "actual" => Field::Actual,
+            _ => return None,
+        })
+    }
+
+    fn value<'a>(&self, param: &'a InvalidParam) -> &'a str {
+        match self {
+            Field::Name => &param.name,
+            Field::Reason => param.reason.as_deref().unwrap_or_default(),
+            Field::Pointer => &param.pointer,
+            Field::Path => param.path.as_deref().unwrap_or_default(),
+            Field::Code => &param.code,
+            Field::Severity => match param.severity {
+                Severity::Info => "info",
+                Severity::Warning => "warning",
+                Severity::Error => "error",
+            },
+            Field::CausedBy => param.caused_by.as_deref().unwrap_or_default(),
+            Field::Expected => &param.expected.r#type,
+            Field::Actual => &param.actual.r#type,
+        }
+    }
+}
+
+impl FilterError {
+    fn new(offset: usize, message: impl Into<String>) -> Self {
+        Self {
+            offset,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at offset {}: {}", self.offset, self.message)
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+fn lex(expression: &str) -> std::result::Result<Vec<(usize, Token)>, FilterError> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.char_indices().peekable();
+    while let Some(&(offset, c)) = chars.peek() {
+        let token = match c {
+            c if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '(' | ')' | '!' => {
+                chars.next();
+                match c {
+                    '(' => Token::Open,
+                    ')' => Token::Close,
+                    _ if chars.next_if(|&(_, c)| c == '=').is_some() => Token::Ne,
+                    _ => Token::Not,
+                }
+            }
+            '=' | '&' | '|' => {
+                chars.next();
+                if chars.next_if(|&(_, next)| next == c).is_none() {
+                    return Err(FilterError::new(offset, format!("expected `{c}{c}`")));
+                }
+                match c {
+                    '=' => Token::Eq,
+                    '&' => Token::And,
+                    _ => Token::Or,
+                }
+            }
+            '"' => Token::Literal(literal(&mut chars, offset)?),
+            c if c.is_alphabetic() || c == '_' => {
+                let mut word = String::new();
+                while let Some((_, c)) = chars.next_if(|&(_, c)| c.is_alphanumeric() || c == '_') {
+                    word.push(c);
+                }
+                Token::Word(word)
+            }
+            c => return Err(FilterError::new(offset, format!("unexpected `{c}`"))),
+        };
+        tokens.push((offset, token));
+    }
+    Ok(tokens)
+}
+
+/// Reads a double-quoted literal starting at `offset`.
+fn literal(
+    chars: &mut Peekable<CharIndices<'_>>,
+    offset: usize,
+) -> std::result::Result<String, FilterError> {
+    chars.next();
+    let mut literal = String::new();
+    loop {
+        match chars.next() {
+            Some((_, '"')) => return Ok(literal),
+            Some((_, '\\')) => match chars.next() {
+                Some((_, c @ ('"' | '\\'))) => literal.push(c),
+                Some((at, _)) => return Err(FilterError::new(at, "expected `\\\"` or `\\\\`")),
+                None => break,
+            },
+            Some((_, c)) => literal.push(c),
+            None => break,
+        }
+    }
+    Err(FilterError::new(offset, "unterminated string"))
+}
+
+struct Parser<'a> {
+    tokens: &'a [(usize, Token)],
+    index: usize,
+    /// Length of the expression, where errors about its end point.
+    end: usize,
+    /// Number of parentheses and `!` enclosing the current token.
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn next(&mut self) -> Option<&(usize, Token)> {
+        let token = self.tokens.get(self.index);
+        self.index += 1;
+        token
+    }
+
+    fn eat(&mut self, expected: &Token) -> bool {
+        let found = self
+            .tokens
+            .get(self.index)
+            .is_some_and(|(_, token)| token == expected);
+        if found {
+            self.index += 1;
+        }
+        found
+    }
+
+    fn offset(&self) -> usize {
+        self.tokens
+            .get(self.index)
+            .map_or(self.end, |(offset, _)| *offset)
+    }
+
+    fn or(&mut self) -> std::result::Result<Node, FilterError> {
+        let mut nodes = vec![self.and()?];
+        while self.eat(&Token::Or) {
+            nodes.push(self.and()?);
+        }
+        Ok(match nodes.len() {
+            1 => nodes.swap_remove(0),
+            _ => Node::Or(nodes),
+        })
+    }
+
+    fn and(&mut self) -> std::result::Result<Node, FilterError> {
+        let mut nodes = vec![self.unary()?];
+        while self.eat(&Token::And) {
+            nodes.push(self.unary()?);
+        }
+        Ok(match nodes.len() {
+            1 => nodes.swap_remove(0),
+            _ => Node::And(nodes),
+        })
+    }
+
+    fn unary(&mut self) -> std::result::Result<Node, FilterError> {
+        let offset = self.offset();
+        if self.eat(&Token::Not) {
+            let node = self.nested(offset, Self::unary)?;
+            return Ok(Node::Not(Box::new(node)));
+        }
+        if self.eat(&Token::Open) {
+            let node = self.nested(offset, Self::or)?;
+            if !self.eat(&Token::Close) {
+                return Err(FilterError::new(self.offset(), "expected `)`"));
+            }
+            return Ok(node);
+        }
+        self.comparison()
+    }
+
+    /// Parses a subexpression one level deeper than the `(` or `!` at
+    /// `offset`.
+    fn nested(
+        &mut self,
+        offset: usize,
+        parse: fn(&mut Self) -> std::result::Result<Node, FilterError>,
+    ) -> std::result::Result<Node, FilterError> {
+        if self.depth == MAX_DEPTH {
+            return Err(FilterError::new(offset, "expression nested too deeply"));
+        }
+        self.depth += 1;
+        let node = parse(self);
+        self.depth -= 1;
+        node
+    }
+
+    fn comparison(&mut self) -> std::result::Result<Node, FilterError> {
+        let offset = self.offset();
+        let field = match self.next() {
+            Some((_, Token::Word(word))) => Field::parse(word)
+                .ok_or_else(|| FilterError::new(offset, format!("unknown field `{word}`")))?,
+            _ => return Err(FilterError::new(offset, "expected a field")),
+        };
+        let offset = self.offset();
+        let op = match self.next() {
+            Some((_, Token::Eq)) => Op::Eq,
+            Some((_, Token::Ne)) => Op::Ne,
+            Some((_, Token::Word(word))) => match word.as_str() {
+                "startswith" => Op::StartsWith,
+                "endswith" => Op::EndsWith,
+                "contains" => Op::Contains,
+                "matches" => Op::Matches,
+                _ => {
+                    return Err(FilterError::new(
+                        offset,
+                        format!("unknown operator `{word}`"),
+                    ));
+                }
+            },
+            _ => return Err(FilterError::new(offset, "expected an operator")),
+        };
+        let offset = self.offset();
+        match self.next() {
+            Some((_, Token::Literal(literal))) => Ok(Node::Compare(field, op, literal.clone())),
+            _ => Err(FilterError::new(offset, "expected a string")),
+        }
+    }
+}
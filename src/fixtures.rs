@@ -0,0 +1,234 @@
+//! Regression suites of recorded validations.
+//!
+//! A [`Recorder`] validates payloads like the [`Options`] it wraps, and
+//! keeps each payload with the [`Report`] it produced. Written to a file as
+//! JSON Lines, these fixtures pin down how your types and options judge
+//! real payloads. A [`Replayer`] later validates every payload again, with
+//! the current version of serdify and of your code, and lists the reports
+//! that changed.
+//!
+//! Options cannot be serialized, so fixtures refer to them by the name
+//! given to the recorder, and the replayer is told which options each name
+//! stands for.
+//!
+//! ```
+//! use serdify::Options;
+//! use serdify::fixtures::{self, Recorder, Replayer};
+//!
+//! let strict = || Options::new().suppress_cascades();
+//! let recorder = Recorder::new(strict()).name("strict");
+//! let _ = recorder.from_str::<Vec<u8>>("[1, 2]");
+//! let _ = recorder.from_str::<Vec<u8>>("[1, 300]");
+//!
+//! let mut file = Vec::new();
+//! recorder.write(&mut file).unwrap();
+//! let fixtures = fixtures::read(file.as_slice()).unwrap();
+//! assert_eq!(fixtures.len(), 2);
+//!
+//! let replayer = Replayer::new().options("strict", strict());
+//! assert!(replayer.run::<Vec<u8>>(&fixtures).is_empty());
+//!
+//! // The type changed: 300 is accepted now.
+//! let mismatches = replayer.run::<Vec<u16>>(&fixtures);
+//! assert_eq!(mismatches.len(), 1);
+//! assert_eq!(mismatches[0].fixture.input, "[1, 300]");
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Options, Outcome, Report};
+
+/// Name of the options of recorders not given one.
+const DEFAULT: &str = "default";
+
+/// A payload with the report its validation produced.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fixture {
+    /// Name of the options the payload was validated with.
+    pub options: String,
+    pub input: String,
+    pub report: Report,
+}
+
+/// Validates payloads, recording a [`Fixture`] for each.
+pub struct Recorder {
+    options: Options,
+    name: String,
+    fixtures: Mutex<Vec<Fixture>>,
+}
+
+impl Recorder {
+    /// Records validations made with `options`, named `default`.
+    pub fn new(options: Options) -> Self {
+        Self {
+            options,
+            name: DEFAULT.to_owned(),
+            fixtures: Mutex::default(),
+        }
+    }
+
+    /// Names the options in the fixtures recorded from now on.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Deserializes `T` from a JSON string, see [`Options::from_str`], and
+    /// records the payload with its report.
+    pub fn from_str<T>(&self, json: &str) -> Outcome<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let outcome = self.options.from_str(json);
+        let fixture = Fixture {
+            options: self.name.clone(),
+            input: json.to_owned(),
+            report: outcome.report(),
+        };
+        self.fixtures
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .push(fixture);
+        outcome
+    }
+
+    /// Returns the fixtures recorded so far.
+    pub fn fixtures(&self) -> Vec<Fixture> {
+        self.fixtures
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clone()
+    }
+
+    /// Writes the fixtures recorded so far to `writer`, one JSON document
+    /// per line.
+    pub fn write(&self, mut writer: impl Write) -> io::Result<()> {
+        for fixture in self.fixtures() {
+            serde_json::to_writer(&mut writer, &fixture)?;
+            writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Recorder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let len = self.fixtures.lock().map_or(0, |fixtures| fixtures.len());
+        f.debug_struct("Recorder")
+            .field("name", &self.name)
+            .field("fixtures", &len)
+            .finish()
+    }
+}
+
+/// Reads fixtures written by [`Recorder::write`], skipping blank lines.
+pub fn read(reader: impl BufRead) -> io::Result<Vec<Fixture>> {
+    let mut fixtures = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        fixtures.push(serde_json::from_str(&line)?);
+    }
+    Ok(fixtures)
+}
+
+/// Validates the payloads of fixtures again, comparing the reports.
+#[derive(Default)]
+pub struct Replayer {
+    options: BTreeMap<String, Options>,
+}
+
+/// A fixture whose payload is now judged differently.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    pub fixture: Fixture,
+    /// The report produced now, or `None` if the replayer has no options
+    /// of the fixture's name.
+    pub actual: Option<Report>,
+}
+
+impl Replayer {
+    /// Creates a replayer knowing the default options only, under the name
+    /// `default`.
+    pub fn new() -> Self {
+        Self::default().options(DEFAULT, Options::new())
+    }
+
+    /// Replays fixtures recorded under `name` with `options`.
+    pub fn options(mut self, name: impl Into<String>, options: Options) -> Self {
+        self.options.insert(name.into(), options);
+        self
+    }
+
+    /// Validates the payload of every fixture as `T` again, returning those
+    /// whose report changed.
+    pub fn run<T>(&self, fixtures: &[Fixture]) -> Vec<Mismatch>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        fixtures
+            .iter()
+            .filter_map(|fixture| {
+                let actual = self
+                    .options
+                    .get(&fixture.options)
+                    .map(|options| options.from_str::<T>(&fixture.input).report());
+                (actual.as_ref() != Some(&fixture.report)).then(|| Mismatch {
+                    fixture: fixture.clone(),
+                    actual,
+                })
+            })
+            .collect()
+    }
+
+    /// Panics, listing every mismatch, unless every fixture replays as
+    /// recorded.
+    #[track_caller]
+    pub fn assert<T>(&self, fixtures: &[Fixture])
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let mismatches = self.run::<T>(fixtures);
+        assert!(
+            mismatches.is_empty(),
+            "{} of {} fixtures changed:{}",
+            mismatches.len(),
+            fixtures.len(),
+            mismatches
+                .iter()
+                .map(|mismatch| format!("\n- {mismatch}"))
+                .collect::<String>()
+        );
+    }
+}
+
+impl fmt::Debug for Replayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Replayer")
+            .field("options", &self.options.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let report = |report: &Report| serde_json::to_string(report).unwrap_or_default();
+        write!(f, "{}", self.fixture.input)?;
+        match &self.actual {
+            Some(actual) => write!(
+                f,
+                "\n  recorded: {}\n  actual: {}",
+                report(&self.fixture.report),
+                report(actual)
+            ),
+            None => write!(f, "\n  no options named `{}`", self.fixture.options),
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Validation of flag sets such as OAuth scopes or feature lists.
+//!
+//! [`Flags`] is a [`Pass`] checking fields holding a set of flags, written
+//! either as an array of strings or as one string separated by commas or
+//! whitespace, against the flags each field allows. Every unknown flag is
+//! reported on its own: at the pointer of its element in arrays, and at the
+//! pointer of the string otherwise.
+//!
+//! ```
+//! use serde::Deserialize;
+//! use serdify::Options;
+//! use serdify::flags::Flags;
+//!
+//! #[derive(Deserialize)]
+//! #[serde(rename_all = "snake_case")]
+//! enum Feature {
+//!     DarkMode,
+//!     Beta,
+//! }
+//!
+//! let flags = Flags::new()
+//!     .field("#/scope", ["read", "write", "admin"])
+//!     .field_enum::<Feature>("#/features");
+//! let options = Options::new().pass(flags);
+//!
+//! let error = options
+//!     .from_str::<serde_json::Value>(
+//!         r#"{"scope": "read delete, purge", "features": ["beta", "turbo"]}"#,
+//!     )
+//!     .result
+//!     .unwrap_err();
+//! let found: Vec<_> = error
+//!     .invalid_params
+//!     .iter()
+//!     .map(|p| (p.pointer.as_str(), p.reason.as_deref().unwrap()))
+//!     .collect();
+//! assert_eq!(
+//!     found,
+//!     [
+//!         ("#/features/1", "Unknown flag `turbo`, expected one of `dark_mode`, `beta`"),
+//!         ("#/scope", "Unknown flag `delete`, expected one of `read`, `write`, `admin`"),
+//!         ("#/scope", "Unknown flag `purge`, expected one of `read`, `write`, `admin`"),
+//!     ]
+//! );
+//! ```
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::descriptor::{Container, Shape, describe};
+use crate::pass::Pass;
+use crate::{ExpectedOrActual, InvalidParam, Severity, code, pointer};
+
+/// Pass checking flag sets against the flags allowed per field.
+#[derive(Debug, Clone, Default)]
+pub struct Flags {
+    fields: Vec<(String, Vec<String>)>,
+    severity: Severity,
+}
+
+impl Flags {
+    /// Creates a pass checking no field until some are added.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allows only `flags` in the flag sets whose pointer matches `pattern`,
+    /// a glob as accepted by [`pointer::matches`]. Later fields take
+    /// precedence.
+    pub fn field<S>(
+        mut self,
+        pattern: impl Into<String>,
+        flags: impl IntoIterator<Item = S>,
+    ) -> Self
+    where
+        S: Into<String>,
+    {
+        let flags = flags.into_iter().map(Into::into).collect();
+        self.fields.push((pattern.into(), flags));
+        self
+    }
+
+    /// Allows the names of the variants of the enum `E` in the flag sets
+    /// whose pointer matches `pattern`, as `E` spells them in payloads.
+    pub fn field_enum<E>(self, pattern: impl Into<String>) -> Self
+    where
+        E: for<'de> Deserialize<'de>,
+    {
+        let descriptor = describe::<E>();
+        let variants = match &descriptor.root {
+            Shape::Named(name) => match descriptor.types.get(name) {
+                Some(Container::Enum(variants)) => {
+                    variants.iter().map(|v| v.name.clone()).collect()
+                }
+                _ => Vec::new(),
+            },
+            _ => Vec::new(),
+        };
+        self.field(pattern, variants)
+    }
+
+    /// Sets the severity of the findings, [`Severity::Error`] by default.
+    pub fn severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    fn flags_at(&self, at: &str) -> Option<&[String]> {
+        self.fields
+            .iter()
+            .rev()
+            .find(|(pattern, _)| pointer::matches(pattern, at))
+            .map(|(_, flags)| flags.as_slice())
+    }
+
+    fn check(&self, at: &str, flag: &str, allowed: &[String], findings: &mut Vec<InvalidParam>) {
+        if allowed.iter().any(|allowed| allowed == flag) {
+            return;
+        }
+        let expected: Vec<_> = allowed.iter().map(|flag| format!("`{flag}`")).collect();
+        let reason = format!(
+            "Unknown flag `{flag}`, expected one of {}",
+            expected.join(", ")
+        );
+        let finding = InvalidParam::new(at, code::FLAG_UNKNOWN, reason)
+            .expected(ExpectedOrActual::new("flag", "string"))
+            .actual(ExpectedOrActual::new("string", "string"))
+            .severity(self.severity);
+        findings.push(finding);
+    }
+}
+
+impl Pass for Flags {
+    fn run(&self, value: &Value, findings: &mut Vec<InvalidParam>) {
+        pointer::walk(value, |at, value| {
+            let Some(allowed) = self.flags_at(at) else {
+                return;
+            };
+            match value {
+                Value::String(text) => {
+                    let flags = text
+                        .split(|c: char| c == ',' || c.is_whitespace())
+                        .filter(|flag| !flag.is_empty());
+                    for flag in flags {
+                        self.check(at, flag, allowed, findings);
+                    }
+                }
+                Value::Array(elements) => {
+                    for (index, element) in elements.iter().enumerate() {
+                        if let Value::String(flag) = element {
+                            let at = format!("{at}/{index}");
+                            self.check(&at, flag, allowed, findings);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        });
+    }
+}
@@ -0,0 +1,317 @@
+//! Validation of [GeoJSON](https://datatracker.ietf.org/doc/html/rfc7946)
+//! documents.
+//!
+//! [`GeoJson`] checks object and geometry types, position arity, the minimum
+//! number of positions of lines and rings, ring closure, and longitude and
+//! latitude ranges. Every finding points at the exact offending member, down
+//! to a single coordinate.
+//!
+//! ```
+//! use serde_json::json;
+//! use serdify::{code, geojson::GeoJson};
+//!
+//! let error = GeoJson::new()
+//!     .validate(&json!({
+//!         "type": "FeatureCollection",
+//!         "features": [{
+//!             "type": "Feature",
+//!             "properties": null,
+//!             "geometry": { "type": "Point", "coordinates": [2.35, 148.85] }
+//!         }]
+//!     }))
+//!     .unwrap_err();
+//!
+//! let param = &error.invalid_params[0];
+//! assert_eq!(param.pointer, "#/features/0/geometry/coordinates/1");
+//! assert_eq!(param.code, code::RANGE_OVERFLOW);
+//! ```
+
+use serde_json::{Map, Value};
+
+use crate::pass::Pass;
+use crate::{Error, ExpectedOrActual, InvalidParam, Result, code, pointer};
+
+/// Types of geometry objects.
+const GEOMETRIES: &[&str] = &[
+    "Point",
+    "MultiPoint",
+    "LineString",
+    "MultiLineString",
+    "Polygon",
+    "MultiPolygon",
+    "GeometryCollection",
+];
+
+/// Pass validating a GeoJSON object.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GeoJson {
+    _private: (),
+}
+
+impl GeoJson {
+    /// Creates a GeoJSON validator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Validates a GeoJSON object, reporting every violation.
+    pub fn validate(&self, value: &Value) -> Result<()> {
+        let mut findings = Vec::new();
+        self.run(value, &mut findings);
+        if findings.is_empty() {
+            Result::Ok(())
+        } else {
+            Result::Err(Error::new(findings))
+        }
+    }
+}
+
+impl Pass for GeoJson {
+    fn run(&self, value: &Value, findings: &mut Vec<InvalidParam>) {
+        let mut checker = Checker { findings };
+        checker.object(value, "#", &[]);
+    }
+}
+
+struct Checker<'a> {
+    findings: &'a mut Vec<InvalidParam>,
+}
+
+impl Checker<'_> {
+    /// Checks a GeoJSON object whose type is one of `allowed`, or any type
+    /// if `allowed` is empty.
+    fn object(&mut self, value: &Value, at: &str, allowed: &[&str]) {
+        let Some(object) = self.as_object(value, at) else {
+            return;
+        };
+        let Some(ty) = self.member(object, at, "type", Value::as_str, "string") else {
+            return;
+        };
+        let known = GEOMETRIES.contains(&ty) || ty == "Feature" || ty == "FeatureCollection";
+        if !known || !(allowed.is_empty() || allowed.contains(&ty)) {
+            let allowed = match allowed {
+                [] => "a GeoJSON type",
+                [ty] => ty,
+                _ => "a geometry type",
+            };
+            self.findings.push(
+                InvalidParam::new(
+                    pointer::join(at, "type"),
+                    code::VARIANT_UNKNOWN,
+                    format!("Expected {allowed}, found `{ty}`"),
+                )
+                .expected(ExpectedOrActual::new(allowed, "string"))
+                .actual(ExpectedOrActual::new("string", "string")),
+            );
+            return;
+        }
+        match ty {
+            "FeatureCollection" => {
+                let features_at = pointer::join(at, "features");
+                if let Some(features) =
+                    self.member(object, at, "features", Value::as_array, "array")
+                {
+                    for (index, feature) in features.iter().enumerate() {
+                        self.object(feature, &format!("{features_at}/{index}"), &["Feature"]);
+                    }
+                }
+            }
+            "Feature" => {
+                match object.get("geometry") {
+                    Some(Value::Null) => {}
+                    Some(geometry) => {
+                        self.object(geometry, &pointer::join(at, "geometry"), GEOMETRIES)
+                    }
+                    None => self.missing(at, "geometry", "object"),
+                }
+                match object.get("properties") {
+                    Some(Value::Null | Value::Object(_)) => {}
+                    Some(properties) => {
+                        self.mismatch(&pointer::join(at, "properties"), "object", properties)
+                    }
+                    None => self.missing(at, "properties", "object"),
+                }
+            }
+            "GeometryCollection" => {
+                let geometries_at = pointer::join(at, "geometries");
+                if let Some(geometries) =
+                    self.member(object, at, "geometries", Value::as_array, "array")
+                {
+                    for (index, geometry) in geometries.iter().enumerate() {
+                        self.object(geometry, &format!("{geometries_at}/{index}"), GEOMETRIES);
+                    }
+                }
+            }
+            ty => {
+                let coordinates_at = pointer::join(at, "coordinates");
+                let Some(coordinates) = object.get("coordinates") else {
+                    self.missing(at, "coordinates", "array");
+                    return;
+                };
+                let depth = match ty {
+                    "Point" => 0,
+                    "MultiPoint" | "LineString" => 1,
+                    "MultiLineString" | "Polygon" => 2,
+                    _ => 3,
+                };
+                self.coordinates(ty, coordinates, &coordinates_at, depth);
+            }
+        }
+    }
+
+    /// Checks nested arrays of positions, `depth` levels above positions.
+    fn coordinates(&mut self, ty: &str, value: &Value, at: &str, depth: usize) {
+        if depth == 0 {
+            self.position(value, at);
+            return;
+        }
+        let Some(array) = self.as_array(value, at) else {
+            return;
+        };
+        for (index, item) in array.iter().enumerate() {
+            self.coordinates(ty, item, &format!("{at}/{index}"), depth - 1);
+        }
+        match (ty, depth) {
+            ("LineString", 1) | ("MultiLineString", 1) => {
+                self.min_len(array, at, 2, "line");
+            }
+            ("Polygon", 1) | ("MultiPolygon", 1) => self.ring(array, at),
+            _ => {}
+        }
+    }
+
+    fn ring(&mut self, ring: &[Value], at: &str) {
+        if !self.min_len(ring, at, 4, "linear ring") || ring.first() == ring.last() {
+            return;
+        }
+        let last = ring.len() - 1;
+        self.findings.push(
+            InvalidParam::new(
+                format!("{at}/{last}"),
+                code::VALUE_INVALID,
+                "Expected the last position of a linear ring to equal the first",
+            )
+            .expected(ExpectedOrActual::new("position", "array"))
+            .actual(ExpectedOrActual::new("position", "array")),
+        );
+    }
+
+    fn position(&mut self, value: &Value, at: &str) {
+        let Some(position) = self.as_array(value, at) else {
+            return;
+        };
+        if !(2..=3).contains(&position.len()) {
+            self.findings.push(
+                InvalidParam::new(
+                    at,
+                    code::LENGTH_INVALID,
+                    format!(
+                        "Expected a position of 2 or 3 numbers, found {}",
+                        position.len()
+                    ),
+                )
+                .expected(ExpectedOrActual::new("position", "array"))
+                .actual(ExpectedOrActual::of(value)),
+            );
+        }
+        for (index, number) in position.iter().enumerate() {
+            let number_at = format!("{at}/{index}");
+            let Some(number) = number.as_f64() else {
+                self.mismatch(&number_at, "number", number);
+                continue;
+            };
+            let (name, limit) = match index {
+                0 => ("longitude", 180.0),
+                1 => ("latitude", 90.0),
+                _ => continue,
+            };
+            if !(-limit..=limit).contains(&number) {
+                self.findings.push(
+                    InvalidParam::new(
+                        number_at,
+                        code::RANGE_OVERFLOW,
+                        format!(
+                            "Value {number} is out of range for {name}. Expected range: -{limit} to {limit}"
+                        ),
+                    )
+                    .expected(ExpectedOrActual::new(name, "number"))
+                    .actual(ExpectedOrActual::new("f64", "number")),
+                );
+            }
+        }
+    }
+
+    /// Reports `array` if it is shorter than `min`, returning `true` if it
+    /// is not.
+    fn min_len(&mut self, array: &[Value], at: &str, min: usize, what: &str) -> bool {
+        if array.len() >= min {
+            return true;
+        }
+        self.findings.push(
+            InvalidParam::new(
+                at,
+                code::LENGTH_INVALID,
+                format!(
+                    "Expected a {what} of at least {min} positions, found {}",
+                    array.len()
+                ),
+            )
+            .expected(ExpectedOrActual::new(what, "array"))
+            .actual(ExpectedOrActual::new("array", "array")),
+        );
+        false
+    }
+
+    fn member<'v, T: ?Sized>(
+        &mut self,
+        object: &'v Map<String, Value>,
+        at: &str,
+        key: &str,
+        cast: impl Fn(&'v Value) -> Option<&'v T>,
+        format: &str,
+    ) -> Option<&'v T> {
+        let Some(value) = object.get(key) else {
+            self.missing(at, key, format);
+            return None;
+        };
+        let cast = cast(value);
+        if cast.is_none() {
+            self.mismatch(&pointer::join(at, key), format, value);
+        }
+        cast
+    }
+
+    fn as_object<'v>(&mut self, value: &'v Value, at: &str) -> Option<&'v Map<String, Value>> {
+        let object = value.as_object();
+        if object.is_none() {
+            self.mismatch(at, "object", value);
+        }
+        object
+    }
+
+    fn as_array<'v>(&mut self, value: &'v Value, at: &str) -> Option<&'v Vec<Value>> {
+        let array = value.as_array();
+        if array.is_none() {
+            self.mismatch(at, "array", value);
+        }
+        array
+    }
+
+    fn missing(&mut self, at: &str, key: &str, format: &str) {
+        self.findings
+            .push(InvalidParam::missing(at, key).expected(ExpectedOrActual::new(format, format)));
+    }
+
+    fn mismatch(&mut self, at: &str, format: &str, value: &Value) {
+        let actual = ExpectedOrActual::of(value);
+        self.findings.push(
+            InvalidParam::new(
+                at,
+                code::TYPE_MISMATCH,
+                format!("Expected {format}, found {}", actual.format),
+            )
+            .expected(ExpectedOrActual::new(format, format))
+            .actual(actual),
+        );
+    }
+}
@@ -0,0 +1,64 @@
+//! Human-readable durations and timestamps, for use with `#[serde(with)]`.
+//!
+//! Parse failures are reported like any other validation error, at the
+//! pointer of the offending field.
+//!
+//! ```
+//! use std::time::{Duration, SystemTime};
+//!
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Job {
+//!     #[serde(with = "serdify::humantime::duration")]
+//!     timeout: Duration,
+//!     #[serde(with = "serdify::humantime::system_time")]
+//!     starts_at: SystemTime,
+//! }
+//!
+//! let job: Job = serdify::from_str(r#"{"timeout": "1m 30s", "starts_at": "2024-01-01T00:00:00Z"}"#).unwrap();
+//! assert_eq!(job.timeout, Duration::from_secs(90));
+//!
+//! let error = serdify::from_str::<Job>(r#"{"timeout": "soon", "starts_at": "tomorrow"}"#).unwrap_err();
+//! assert_eq!(error.invalid_params.len(), 2);
+//! ```
+
+/// `#[serde(with)]` module for [`Duration`](std::time::Duration) values
+/// written like `"1h 30m"` or `"250ms"`.
+pub mod duration {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer, de};
+
+    /// Serializes a duration in human-readable form.
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&humantime::format_duration(*duration))
+    }
+
+    /// Deserializes a duration from its human-readable form.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let text = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        humantime::parse_duration(&text)
+            .map_err(|err| de::Error::custom(format!("invalid duration `{text}`: {err}")))
+    }
+}
+
+/// `#[serde(with)]` module for [`SystemTime`](std::time::SystemTime) values
+/// written as RFC 3339 timestamps.
+pub mod system_time {
+    use std::time::SystemTime;
+
+    use serde::{Deserialize, Deserializer, Serializer, de};
+
+    /// Serializes a timestamp in RFC 3339 form.
+    pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&humantime::format_rfc3339(*time))
+    }
+
+    /// Deserializes a timestamp from its RFC 3339 form.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        let text = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        humantime::parse_rfc3339_weak(&text)
+            .map_err(|err| de::Error::custom(format!("invalid timestamp `{text}`: {err}")))
+    }
+}
@@ -0,0 +1,283 @@
+//! Sanity checks for JSON-LD documents, such as schema.org structured data.
+//!
+//! [`JsonLd`] is a [`Pass`] checking that a document declares the expected
+//! `@context`, that every `@type` is well formed and uses prefixes the
+//! context defines, and that nodes carry the properties required for their
+//! declared type. Findings are [warnings](Severity::Warning) by default,
+//! since structured data usually degrades gracefully.
+//!
+//! ```
+//! use serdify::{Options, code, jsonld::JsonLd};
+//!
+//! let options = Options::new().pass(JsonLd::schema_org());
+//! let outcome = options.from_str::<serde_json::Value>(
+//!     r#"{
+//!         "@context": "https://schema.org",
+//!         "@type": "Product",
+//!         "offers": { "@type": "Offer", "price": "9.99" }
+//!     }"#,
+//! );
+//!
+//! let missing: Vec<_> = outcome
+//!     .warnings
+//!     .iter()
+//!     .map(|w| (w.pointer.as_str(), w.name.as_str()))
+//!     .collect();
+//! assert_eq!(missing, [("#", "name"), ("#/offers", "priceCurrency")]);
+//! assert_eq!(outcome.warnings[0].code, code::FIELD_MISSING);
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::{Map, Value};
+
+use crate::pass::Pass;
+use crate::{ExpectedOrActual, InvalidParam, Severity, code, pointer};
+
+/// Properties Google requires for the schema.org types it builds rich
+/// results from.
+const SCHEMA_ORG: &[(&str, &[&str])] = &[
+    ("Article", &["headline"]),
+    ("BreadcrumbList", &["itemListElement"]),
+    ("Event", &["name", "startDate", "location"]),
+    (
+        "JobPosting",
+        &["title", "description", "datePosted", "hiringOrganization"],
+    ),
+    ("ListItem", &["position"]),
+    ("LocalBusiness", &["name", "address"]),
+    ("Offer", &["price", "priceCurrency"]),
+    ("Organization", &["name"]),
+    ("Person", &["name"]),
+    ("Product", &["name"]),
+    ("Recipe", &["name", "image"]),
+    ("Review", &["author", "reviewRating"]),
+];
+
+/// Pass checking JSON-LD `@context`, `@type` and required properties.
+#[derive(Debug, Clone)]
+pub struct JsonLd {
+    context: Option<String>,
+    required: BTreeMap<String, Vec<String>>,
+    severity: Severity,
+}
+
+impl Default for JsonLd {
+    fn default() -> Self {
+        Self {
+            context: None,
+            required: BTreeMap::new(),
+            severity: Severity::Warning,
+        }
+    }
+}
+
+impl JsonLd {
+    /// Creates a pass checking only `@type` well-formedness.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a pass expecting the schema.org context and the properties
+    /// Google requires for common rich result types.
+    pub fn schema_org() -> Self {
+        SCHEMA_ORG.iter().fold(
+            Self::new().context("https://schema.org"),
+            |pass, (ty, properties)| pass.require(*ty, properties.iter().copied()),
+        )
+    }
+
+    /// Requires the top-level `@context` to reference `context`. The `http`
+    /// and `https` schemes and a trailing slash are treated as equivalent.
+    pub fn context(mut self, context: impl Into<String>) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+
+    /// Requires nodes of type `ty` to have every one of `properties`.
+    pub fn require<I>(mut self, ty: impl Into<String>, properties: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.required
+            .entry(ty.into())
+            .or_default()
+            .extend(properties.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets the severity of the findings.
+    pub fn severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    fn finding(&self, at: impl Into<String>, code: &str, reason: String) -> InvalidParam {
+        InvalidParam::new(at, code, reason).severity(self.severity)
+    }
+
+    fn check_context(&self, root: &Value, findings: &mut Vec<InvalidParam>) {
+        let Some(expected) = &self.context else {
+            return;
+        };
+        let Some(context) = root.get("@context") else {
+            findings.push(
+                self.finding(
+                    "#/@context",
+                    code::JSONLD_CONTEXT,
+                    format!("Missing `@context`, expected `{expected}`"),
+                )
+                .expected(ExpectedOrActual::new(expected.clone(), "string"))
+                .actual(ExpectedOrActual::new("undefined", "missing")),
+            );
+            return;
+        };
+        let references = match context {
+            Value::Array(contexts) => contexts.iter().any(|c| references(c, expected)),
+            context => references(context, expected),
+        };
+        if !references {
+            findings.push(
+                self.finding(
+                    "#/@context",
+                    code::JSONLD_CONTEXT,
+                    format!("`@context` does not reference `{expected}`"),
+                )
+                .expected(ExpectedOrActual::new(expected.clone(), "string"))
+                .actual(ExpectedOrActual::of(context)),
+            );
+        }
+    }
+
+    fn check_node(
+        &self,
+        at: &str,
+        node: &Map<String, Value>,
+        prefixes: &BTreeSet<String>,
+        findings: &mut Vec<InvalidParam>,
+    ) {
+        let Some(ty) = node.get("@type") else {
+            return;
+        };
+        let type_at = pointer::join(at, "@type");
+        let types: Vec<(String, &str)> = match ty {
+            Value::String(ty) => vec![(type_at, ty.as_str())],
+            Value::Array(types) if !types.is_empty() => {
+                let mut strings = Vec::with_capacity(types.len());
+                for (index, ty) in types.iter().enumerate() {
+                    let item_at = format!("{type_at}/{index}");
+                    match ty.as_str() {
+                        Some(ty) => strings.push((item_at, ty)),
+                        None => findings.push(self.type_mismatch(item_at, ty)),
+                    }
+                }
+                strings
+            }
+            ty => {
+                findings.push(self.type_mismatch(type_at, ty));
+                return;
+            }
+        };
+        for (type_at, ty) in types {
+            if ty.is_empty() {
+                findings.push(
+                    self.finding(type_at, code::JSONLD_TYPE, "Empty `@type`".to_owned())
+                        .actual(ExpectedOrActual::new("string", "string")),
+                );
+                continue;
+            }
+            if let Some((prefix, _)) = ty.split_once(':')
+                && !is_absolute(ty)
+                && !prefixes.contains(prefix)
+            {
+                findings.push(
+                    self.finding(
+                        type_at,
+                        code::JSONLD_TYPE,
+                        format!("`@type` uses the prefix `{prefix}`, which no `@context` defines"),
+                    )
+                    .actual(ExpectedOrActual::new("string", "string")),
+                );
+            }
+            let Some(required) = self.required.get(local_name(ty)) else {
+                continue;
+            };
+            for property in required {
+                if !node.contains_key(property) {
+                    findings.push(InvalidParam::missing(at, property).severity(self.severity));
+                }
+            }
+        }
+    }
+
+    fn type_mismatch(&self, at: String, ty: &Value) -> InvalidParam {
+        self.finding(
+            at,
+            code::JSONLD_TYPE,
+            "Expected `@type` to be a string or a non-empty array of strings".to_owned(),
+        )
+        .expected(ExpectedOrActual::new("string", "string"))
+        .actual(ExpectedOrActual::of(ty))
+    }
+}
+
+impl Pass for JsonLd {
+    fn run(&self, value: &Value, findings: &mut Vec<InvalidParam>) {
+        self.check_context(value, findings);
+        let mut prefixes = BTreeSet::new();
+        pointer::walk(value, |_, value| {
+            if let Some(context) = value.get("@context") {
+                collect_prefixes(context, &mut prefixes);
+            }
+        });
+        pointer::walk(value, |at, value| {
+            if let Value::Object(node) = value {
+                self.check_node(at, node, &prefixes, findings);
+            }
+        });
+    }
+}
+
+/// Returns `true` if a single `@context` entry references `expected`.
+fn references(context: &Value, expected: &str) -> bool {
+    let normalize = |iri: &str| {
+        let iri = iri.trim_end_matches('/');
+        iri.strip_prefix("https://")
+            .or_else(|| iri.strip_prefix("http://"))
+            .unwrap_or(iri)
+            .to_owned()
+    };
+    match context {
+        Value::String(iri) => normalize(iri) == normalize(expected),
+        Value::Object(object) => object
+            .get("@vocab")
+            .and_then(Value::as_str)
+            .is_some_and(|vocab| normalize(vocab) == normalize(expected)),
+        _ => false,
+    }
+}
+
+/// Collects the prefixes an embedded `@context` defines.
+fn collect_prefixes(context: &Value, prefixes: &mut BTreeSet<String>) {
+    match context {
+        Value::Array(contexts) => contexts
+            .iter()
+            .for_each(|context| collect_prefixes(context, prefixes)),
+        Value::Object(object) => {
+            prefixes.extend(object.keys().filter(|key| !key.starts_with('@')).cloned())
+        }
+        _ => {}
+    }
+}
+
+fn is_absolute(iri: &str) -> bool {
+    ["http://", "https://", "urn:", "_:"]
+        .iter()
+        .any(|scheme| iri.starts_with(scheme))
+}
+
+/// Returns the type name without its vocabulary IRI or prefix.
+fn local_name(ty: &str) -> &str {
+    ty.rsplit(['/', '#', ':']).next().unwrap_or(ty)
+}
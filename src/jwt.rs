@@ -0,0 +1,245 @@
+//! Validation of JSON Web Token claims.
+//!
+//! [`validate_jwt_claims`] decodes the base64url claims segment of a token
+//! and deserializes it like [`from_str`](crate::from_str) does, additionally
+//! checking the registered claims of
+//! [RFC 7519](https://datatracker.ietf.org/doc/html/rfc7519#section-4.1)
+//! whatever the target type: dates must be numeric, `iss`, `sub` and `aud`
+//! must be strings or URIs, and `nbf` may not come after `exp`. Every problem
+//! is reported at once.
+//!
+//! This only checks the shape of the claims. Verifying the signature and
+//! comparing the dates with the current time is left to the JWT library.
+//!
+//! ```
+//! use serde::Deserialize;
+//! use serdify::jwt::validate_jwt_claims;
+//!
+//! #[derive(Debug, Deserialize)]
+//! struct Claims {
+//!     sub: String,
+//!     exp: u64,
+//! }
+//!
+//! // {"sub": 42, "exp": "tomorrow", "aud": ["api", 7]}
+//! let payload = "eyJzdWIiOiA0MiwgImV4cCI6ICJ0b21vcnJvdyIsICJhdWQiOiBbImFwaSIsIDddfQ";
+//! let error = validate_jwt_claims::<Claims>(payload).unwrap_err();
+//!
+//! let pointers: Vec<_> = error.invalid_params.iter().map(|p| p.pointer.as_str()).collect();
+//! assert_eq!(pointers, ["#/exp", "#/sub", "#/aud/1"]);
+//! ```
+
+use base64::Engine;
+use base64::engine::DecodePaddingMode;
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::pass::Pass;
+use crate::{Error, ExpectedOrActual, InvalidParam, Result, code, de, pointer};
+
+/// Base64url decoding accepting both padded and unpadded input.
+const BASE64URL: GeneralPurpose = GeneralPurpose::new(
+    &base64::alphabet::URL_SAFE,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Claims holding a NumericDate.
+const DATES: &[&str] = &["exp", "nbf", "iat"];
+
+/// Claims holding a StringOrURI.
+const NAMES: &[&str] = &["iss", "sub"];
+
+/// Decodes and validates the claims of a JWT.
+///
+/// `payload` is the base64url-encoded claims segment. A complete compact
+/// token (`header.payload.signature`) is accepted too, in which case only
+/// its middle segment is read.
+///
+/// Input that is neither, or whose claims are not base64url, is rejected
+/// without invalid parameters and a detail saying which.
+///
+/// ```
+/// use serdify::jwt::validate_jwt_claims;
+///
+/// let error = validate_jwt_claims::<serde_json::Value>("header.claims").unwrap_err();
+/// assert_eq!(
+///     error.detail.as_deref(),
+///     Some("JWT has 2 segments; expected a claims segment or a compact token of 3.")
+/// );
+///
+/// let error = validate_jwt_claims::<serde_json::Value>("e30.c!aims.sig").unwrap_err();
+/// assert!(error.detail.unwrap().starts_with("JWT claims are not valid base64url: "));
+/// ```
+pub fn validate_jwt_claims<T>(payload: &str) -> Result<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let segments: Vec<_> = payload.trim().split('.').collect();
+    let segment = match segments[..] {
+        [_, claims, _] | [claims] => claims,
+        _ => {
+            return Result::Err(malformed(format!(
+                "JWT has {} segments; expected a claims segment or a compact token of 3.",
+                segments.len()
+            )));
+        }
+    };
+    let json = match BASE64URL.decode(segment) {
+        Ok(json) => json,
+        Err(err) => {
+            return Result::Err(malformed(format!(
+                "JWT claims are not valid base64url: {err}."
+            )));
+        }
+    };
+    let value: Value = match serde_json::from_slice(&json) {
+        Ok(value) => value,
+        Err(err) => return Result::Err(Error::syntax(&err)),
+    };
+    let mut claims = Vec::new();
+    RegisteredClaims.run(&value, &mut claims);
+    match de::from_value(&value) {
+        Result::Ok(_) if !claims.is_empty() => Result::Err(Error::new(claims)),
+        Result::Err(mut error) => {
+            // The target type already reported claims it expected in another
+            // shape; keep its finding rather than repeating the problem.
+            claims.retain(|claim| {
+                !error
+                    .invalid_params
+                    .iter()
+                    .any(|param| param.pointer == claim.pointer)
+            });
+            error.invalid_params.extend(claims);
+            Result::Err(error)
+        }
+        result => result,
+    }
+}
+
+fn malformed(detail: String) -> Error {
+    Error {
+        detail: Some(detail),
+        ..Error::new(Vec::new())
+    }
+}
+
+/// Pass checking the registered claims of RFC 7519, for use with
+/// [`Options`](crate::Options) when the claims are already decoded.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RegisteredClaims;
+
+impl Pass for RegisteredClaims {
+    fn run(&self, value: &Value, findings: &mut Vec<InvalidParam>) {
+        let Some(claims) = value.as_object() else {
+            return;
+        };
+        for &name in DATES {
+            if let Some(date) = claims.get(name) {
+                numeric_date(&pointer::join("#", name), date, findings);
+            }
+        }
+        for &name in NAMES {
+            if let Some(value) = claims.get(name) {
+                string_or_uri(&pointer::join("#", name), value, findings);
+            }
+        }
+        match claims.get("aud") {
+            Some(Value::Array(audiences)) => {
+                for (index, audience) in audiences.iter().enumerate() {
+                    string_or_uri(&format!("#/aud/{index}"), audience, findings);
+                }
+            }
+            Some(audience) => string_or_uri("#/aud", audience, findings),
+            None => {}
+        }
+        if let (Some(nbf), Some(exp)) = (
+            claims.get("nbf").and_then(Value::as_f64),
+            claims.get("exp").and_then(Value::as_f64),
+        ) && nbf > exp
+        {
+            findings.push(
+                InvalidParam::new(
+                    "#/nbf",
+                    code::VALUE_INVALID,
+                    format!("Not-before time {nbf} is after the expiration time {exp}"),
+                )
+                .expected(ExpectedOrActual::new("NumericDate", "number"))
+                .actual(ExpectedOrActual::of(&claims["nbf"])),
+            );
+        }
+    }
+}
+
+fn numeric_date(at: &str, value: &Value, findings: &mut Vec<InvalidParam>) {
+    let actual = ExpectedOrActual::of(value);
+    let expected = ExpectedOrActual::new("NumericDate", "number");
+    match value.as_f64() {
+        Some(seconds) if seconds >= 0.0 => {}
+        Some(seconds) => findings.push(
+            InvalidParam::new(
+                at,
+                code::RANGE_OVERFLOW,
+                format!("Expected seconds since the epoch, found {seconds}"),
+            )
+            .expected(expected)
+            .actual(actual),
+        ),
+        None => findings.push(
+            InvalidParam::new(
+                at,
+                code::TYPE_MISMATCH,
+                format!(
+                    "Expected a NumericDate in seconds since the epoch, found {}",
+                    actual.format
+                ),
+            )
+            .expected(expected)
+            .actual(actual),
+        ),
+    }
+}
+
+fn string_or_uri(at: &str, value: &Value, findings: &mut Vec<InvalidParam>) {
+    let actual = ExpectedOrActual::of(value);
+    let expected = ExpectedOrActual::new("StringOrURI", "string");
+    let Some(string) = value.as_str() else {
+        findings.push(
+            InvalidParam::new(
+                at,
+                code::TYPE_MISMATCH,
+                format!("Expected a string or URI, found {}", actual.format),
+            )
+            .expected(expected)
+            .actual(actual),
+        );
+        return;
+    };
+    // RFC 7519, section 2: any value containing a colon must be a URI.
+    if string.contains(':') && !is_uri(string) {
+        findings.push(
+            InvalidParam::new(
+                at,
+                code::VALUE_INVALID,
+                format!("Expected `{string}` to be a URI, since it contains a colon"),
+            )
+            .expected(expected)
+            .actual(actual),
+        );
+    }
+}
+
+/// Checks the syntax of a URI scheme and the absence of characters URIs
+/// never contain.
+fn is_uri(string: &str) -> bool {
+    let Some((scheme, rest)) = string.split_once(':') else {
+        return false;
+    };
+    let mut chars = scheme.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        && !rest.is_empty()
+        && !rest
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || matches!(c, '<' | '>' | '"'))
+}
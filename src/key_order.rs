@@ -0,0 +1,158 @@
+use std::sync::Arc;
+
+use serde::Deserialize;
+
+use crate::descriptor::{self, Descriptor};
+use crate::token::{self, Visitor};
+use crate::{ExpectedOrActual, InvalidParam, code, pointer};
+
+/// An order object keys must follow, for payloads whose signature covers
+/// their exact text. See [`Options::key_order`](crate::Options::key_order).
+///
+/// Parsing loses the order of keys, so it is checked on the raw JSON text.
+/// Each key appearing after a key it should precede is reported at its own
+/// pointer, with its byte offset.
+///
+/// ```
+/// use serde::Deserialize;
+/// use serdify::{KeyOrder, Options, code};
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Transfer {
+///     from: String,
+///     to: String,
+///     amount: u64,
+/// }
+///
+/// let options = Options::new().key_order(KeyOrder::declared::<Transfer>());
+/// let json = r#"{"from": "alice", "amount": 5, "to": "bob"}"#;
+/// let error = options.from_str::<Transfer>(json).result.unwrap_err();
+///
+/// assert_eq!(error.invalid_params[0].pointer, "#/to");
+/// assert_eq!(error.invalid_params[0].code, code::FIELD_ORDER);
+/// assert_eq!(error.invalid_params[0].offset, Some(31));
+/// assert_eq!(
+///     error.invalid_params[0].reason.as_deref(),
+///     Some("Key `to` must come before `amount`")
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct KeyOrder {
+    order: Order,
+}
+
+#[derive(Debug, Clone)]
+enum Order {
+    Lexicographic,
+    Declared(Arc<Descriptor>),
+}
+
+impl KeyOrder {
+    /// Requires the keys of every object to be sorted by their Unicode code
+    /// points.
+    ///
+    /// ```
+    /// use serdify::{KeyOrder, Options};
+    ///
+    /// let options = Options::new().key_order(KeyOrder::lexicographic());
+    /// let outcome = options.from_str::<serde_json::Value>(r#"{"a": {"y": 1, "x": 2}, "b": 3}"#);
+    ///
+    /// let error = outcome.result.unwrap_err();
+    /// assert_eq!(error.invalid_params.len(), 1);
+    /// assert_eq!(error.invalid_params[0].pointer, "#/a/x");
+    /// ```
+    pub fn lexicographic() -> Self {
+        Self {
+            order: Order::Lexicographic,
+        }
+    }
+
+    /// Requires the fields of every struct of `T` to appear in declaration
+    /// order. Keys the struct does not declare may appear anywhere, and
+    /// objects that are not structs of `T`, such as maps, are not checked.
+    pub fn declared<T>() -> Self
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        Self {
+            order: Order::Declared(Arc::new(descriptor::describe::<T>())),
+        }
+    }
+
+    /// Returns the rank of `key` in the object at `object`, keys of lower
+    /// rank coming first, or `None` if the key may appear anywhere.
+    fn rank<'a>(&self, object: &str, key: &'a str) -> Option<Rank<'a>> {
+        match &self.order {
+            Order::Lexicographic => Some(Rank::Key(key)),
+            Order::Declared(descriptor) => descriptor
+                .fields_at(object)?
+                .iter()
+                .position(|field| field.name == key)
+                .map(Rank::Index),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Rank<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// Reports every key of the first JSON document in `json` out of `order`.
+pub(crate) fn check(json: &[u8], order: &KeyOrder) -> Vec<InvalidParam> {
+    let mut checker = Checker {
+        order,
+        objects: Vec::new(),
+        findings: Vec::new(),
+    };
+    token::walk(json, &mut checker);
+    checker.findings.sort_by_key(|finding| finding.offset);
+    checker.findings
+}
+
+struct Checker<'a> {
+    order: &'a KeyOrder,
+    /// Keys of the objects being read, with their offsets.
+    objects: Vec<Vec<(String, usize)>>,
+    findings: Vec<InvalidParam>,
+}
+
+impl Visitor for Checker<'_> {
+    fn object(&mut self, _at: &str) {
+        self.objects.push(Vec::new());
+    }
+
+    fn key(&mut self, _object: &str, key: &str, offset: usize) {
+        if let Some(keys) = self.objects.last_mut() {
+            keys.push((key.to_owned(), offset));
+        }
+    }
+
+    fn object_end(&mut self, at: &str) {
+        let Some(keys) = self.objects.pop() else {
+            return;
+        };
+        let mut last: Option<(Rank<'_>, &str)> = None;
+        for (key, offset) in &keys {
+            let Some(rank) = self.order.rank(at, key) else {
+                continue;
+            };
+            match last {
+                Some((last_rank, last_key)) if rank < last_rank => {
+                    self.findings.push(
+                        InvalidParam::new(
+                            pointer::join(at, key),
+                            code::FIELD_ORDER,
+                            format!("Key `{key}` must come before `{last_key}`"),
+                        )
+                        .offset(*offset)
+                        .expected(ExpectedOrActual::new("ordered key", "string"))
+                        .actual(ExpectedOrActual::new("out-of-order key", "string")),
+                    );
+                }
+                _ => last = Some((rank, key)),
+            }
+        }
+    }
+}
@@ -0,0 +1,240 @@
+//! # Serdify
+//!
+//! [RFC 7807](https://datatracker.ietf.org/doc/html/rfc7807) compliant error
+//! handling for JSON deserialization.
+//!
+//! Where `serde_json` stops at the first problem, serdify keeps going and
+//! reports **every** validation error in the payload, each located with an
+//! [RFC 6901](https://datatracker.ietf.org/doc/html/rfc6901) JSON pointer.
+//!
+//! ```
+//! use serde::Deserialize;
+//! use serdify::{Result, from_str};
+//!
+//! #[derive(Debug, Deserialize)]
+//! struct Person {
+//!     name: String,
+//!     age: u8,
+//!     salary: u32,
+//! }
+//!
+//! let json = r#"{"name": "John Doe", "age": 430, "salary": 5000000000}"#;
+//!
+//! let Result::Err(error) = from_str::<Person>(json) else {
+//!     panic!("both numbers are out of range");
+//! };
+//! let pointers: Vec<_> = error.invalid_params.iter().map(|p| p.pointer.as_str()).collect();
+//! assert_eq!(pointers, ["#/age", "#/salary"]);
+//! ```
+//!
+//! Any type implementing [`serde::Deserialize`] can be used, across the whole
+//! serde data model: structs, tuples, fixed-size arrays, maps with non-string
+//! keys, every enum representation, `Option`, `Duration`, `SystemTime`,
+//! `Bound` and so on. Shapes that serde buffers internally (untagged and
+//! internally tagged enums, `#[serde(flatten)]`) are reported at the pointer
+//! of the buffered value rather than at the exact field.
+//!
+//! Unlike `serde_json`, serdify rejects keys repeated within an object, maps
+//! included, rather than keeping the last value; see [`from_str`].
+
+pub mod avro;
+#[cfg(feature = "binary")]
+pub mod binary;
+pub mod cache;
+pub mod canonical;
+pub mod code;
+pub mod compat;
+pub mod config;
+pub mod coverage;
+mod de;
+pub mod descriptor;
+pub mod diff;
+#[cfg(feature = "difftest")]
+pub mod difftest;
+mod digest;
+mod disclosure;
+mod duplicate;
+mod envelope;
+mod error;
+pub mod exchange;
+mod expect;
+pub mod filter;
+pub mod fixtures;
+pub mod flags;
+#[cfg(feature = "geojson")]
+pub mod geojson;
+#[cfg(feature = "humantime")]
+pub mod humantime;
+pub mod jsonld;
+#[cfg(feature = "jwt")]
+pub mod jwt;
+mod key_order;
+pub mod money;
+mod number;
+pub mod observer;
+pub mod openapi;
+mod options;
+mod outcome;
+mod page;
+pub mod pass;
+pub mod pipeline;
+pub mod pointer;
+mod report;
+mod result;
+mod revalidate;
+mod root_cause;
+pub mod security;
+pub mod stream;
+#[cfg(feature = "strict")]
+pub mod strict;
+mod suppression;
+pub mod temporal;
+pub mod text;
+mod token;
+mod trailing;
+pub mod units;
+mod version;
+
+use serde::Deserialize;
+
+use duplicate::Checked;
+
+pub use config::configure;
+pub use diff::explain_diff;
+pub use disclosure::{Caller, Disclosure};
+pub use envelope::Envelope;
+pub use error::{Error, ExpectedOrActual, InvalidParam, Severity};
+pub use expect::{ExpectedError, ExpectedErrors};
+pub use key_order::KeyOrder;
+pub use number::NumberLimits;
+pub use options::Options;
+pub use outcome::Outcome;
+pub use page::Page;
+pub use report::{ExitCodes, Policy, Report};
+pub use result::Result;
+pub use revalidate::{Revalidation, revalidate_at};
+pub use root_cause::RootCause;
+pub use suppression::Suppression;
+pub use version::{SCHEMA_VERSION, VersionPolicy};
+
+/// Deserializes `T` from a JSON string, collecting every validation error.
+///
+/// Data after the JSON document is reported as a
+/// [`SYNTAX_TRAILING`](code::SYNTAX_TRAILING) finding, with its offset and
+/// the first characters of the data, alongside the errors of the document.
+///
+/// Keys repeated within an object are reported as
+/// [`FIELD_DUPLICATE`](code::FIELD_DUPLICATE) findings at the pointer of the
+/// member. `serde_json` only rejects them for the fields of a struct and
+/// keeps the last value otherwise; serdify rejects them in maps too, since
+/// other parsers may keep the first value of the same payload.
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use serdify::{code, from_str};
+///
+/// let error = from_str::<BTreeMap<String, u8>>(r#"{"p": 1, "q": 2, "q": 3}"#).unwrap_err();
+///
+/// assert_eq!(error.invalid_params[0].pointer, "#/q");
+/// assert_eq!(error.invalid_params[0].code, code::FIELD_DUPLICATE);
+/// ```
+///
+/// ```
+/// use serdify::{code, from_str};
+///
+/// let error = from_str::<Vec<u8>>("[1, 300] ]garbage").unwrap_err();
+///
+/// assert_eq!(error.invalid_params[0].pointer, "#/1");
+/// assert_eq!(error.invalid_params[1].code, code::SYNTAX_TRAILING);
+/// assert_eq!(error.invalid_params[1].offset, Some(9));
+/// assert_eq!(
+///     error.invalid_params[1].reason.as_deref(),
+///     Some(r#"Unexpected data after the JSON document at byte 9: "]garbage""#)
+/// );
+/// ```
+pub fn from_str<T>(json: &str) -> Result<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    from_slice(json.as_bytes())
+}
+
+/// Deserializes `T` from JSON bytes, collecting every validation error.
+pub fn from_slice<T>(json: &[u8]) -> Result<T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    match trailing::parse::<Checked>(json, Default::default()) {
+        Ok((checked, trailing)) => {
+            let findings = findings(checked.duplicates, trailing);
+            if findings.is_empty() {
+                return from_value(&checked.value);
+            }
+            let mut error = match from_value::<T>(&checked.value) {
+                Result::Ok(_) => Error::new(Vec::new()),
+                Result::Err(error) => error,
+            };
+            error.invalid_params.extend(findings);
+            Result::Err(error)
+        }
+        Err(err) => Result::Err(Error::syntax(&err)),
+    }
+}
+
+/// Validates a JSON string as `T` without keeping the value, returning
+/// every validation error, or `None` if the payload is valid.
+///
+/// Meant for endpoints that only answer whether a payload is valid and why
+/// not. `T`'s own `Deserialize` impl still runs, as nothing else knows what
+/// it accepts, but its value is dropped as soon as it is built and no
+/// [`Result`] is assembled around it.
+///
+/// ```
+/// use serde::Deserialize;
+/// use serdify::check;
+///
+/// #[derive(Deserialize)]
+/// struct Order {
+///     id: u64,
+///     quantity: u8,
+/// }
+///
+/// assert!(check::<Order>(r#"{"id": 1, "quantity": 2}"#).is_none());
+///
+/// let error = check::<Order>(r#"{"id": -1, "quantity": 300}"#).unwrap();
+/// assert_eq!(error.invalid_params.len(), 2);
+/// ```
+pub fn check<T>(json: &str) -> Option<Error>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    match trailing::parse::<Checked>(json.as_bytes(), Default::default()) {
+        Ok((checked, trailing)) => {
+            let findings = findings(checked.duplicates, trailing);
+            if findings.is_empty() {
+                return de::check::<T>(&checked.value);
+            }
+            let mut error =
+                de::check::<T>(&checked.value).unwrap_or_else(|| Error::new(Vec::new()));
+            error.invalid_params.extend(findings);
+            Some(error)
+        }
+        Err(err) => Some(Error::syntax(&err)),
+    }
+}
+
+/// Gathers the findings about the text of a document.
+fn findings(duplicates: Vec<InvalidParam>, trailing: Option<InvalidParam>) -> Vec<InvalidParam> {
+    let mut findings = duplicates;
+    findings.extend(trailing);
+    findings
+}
+
+/// Deserializes `T` from an already parsed [`serde_json::Value`], collecting
+/// every validation error.
+pub fn from_value<'de, T>(value: &'de serde_json::Value) -> Result<T>
+where
+    T: Deserialize<'de>,
+{
+    de::from_value(value)
+}
@@ -0,0 +1,414 @@
+//! Validation of monetary amounts.
+//!
+//! [`Money`] is a [`Pass`] checking money objects such as
+//! `{"amount": "10.50", "currency": "USD"}` at pointers matching glob
+//! patterns: the amount must be a decimal string, the currency an active
+//! ISO 4217 code, and the amount may not have more decimal places than the
+//! currency's minor unit. Every violation is reported beneath the money
+//! object's pointer.
+//!
+//! ```
+//! use serdify::{Options, code, money::Money};
+//!
+//! let options = Options::new().pass(Money::new().at("#/items/*/price"));
+//! let outcome = options.from_str::<serde_json::Value>(
+//!     r#"{"items": [
+//!         {"price": {"amount": "10.50", "currency": "USD"}},
+//!         {"price": {"amount": "1.5", "currency": "JPY"}},
+//!         {"price": {"amount": 3, "currency": "ABC"}}
+//!     ]}"#,
+//! );
+//!
+//! let error = outcome.result.unwrap_err();
+//! let found: Vec<_> = error
+//!     .invalid_params
+//!     .iter()
+//!     .map(|p| (p.pointer.as_str(), p.code.as_str()))
+//!     .collect();
+//! assert_eq!(
+//!     found,
+//!     [
+//!         ("#/items/1/price/amount", code::VALUE_INVALID),
+//!         ("#/items/2/price/amount", code::TYPE_MISMATCH),
+//!         ("#/items/2/price/currency", code::VARIANT_UNKNOWN),
+//!     ]
+//! );
+//! ```
+
+use serde_json::Value;
+
+use crate::pass::Pass;
+use crate::{ExpectedOrActual, InvalidParam, code, pointer};
+
+/// Active ISO 4217 currency codes with their minor unit, the number of
+/// decimal places amounts may have. `None` marks codes without a minor unit,
+/// such as precious metals.
+const CURRENCIES: &[(&str, Option<u32>)] = &[
+    ("AED", Some(2)),
+    ("AFN", Some(2)),
+    ("ALL", Some(2)),
+    ("AMD", Some(2)),
+    ("ANG", Some(2)),
+    ("AOA", Some(2)),
+    ("ARS", Some(2)),
+    ("AUD", Some(2)),
+    ("AWG", Some(2)),
+    ("AZN", Some(2)),
+    ("BAM", Some(2)),
+    ("BBD", Some(2)),
+    ("BDT", Some(2)),
+    ("BGN", Some(2)),
+    ("BHD", Some(3)),
+    ("BIF", Some(0)),
+    ("BMD", Some(2)),
+    ("BND", Some(2)),
+    ("BOB", Some(2)),
+    ("BOV", Some(2)),
+    ("BRL", Some(2)),
+    ("BSD", Some(2)),
+    ("BTN", Some(2)),
+    ("BWP", Some(2)),
+    ("BYN", Some(2)),
+    ("BZD", Some(2)),
+    ("CAD", Some(2)),
+    ("CDF", Some(2)),
+    ("CHE", Some(2)),
+    ("CHF", Some(2)),
+    ("CHW", Some(2)),
+    ("CLF", Some(4)),
+    ("CLP", Some(0)),
+    ("CNY", Some(2)),
+    ("COP", Some(2)),
+    ("COU", Some(2)),
+    ("CRC", Some(2)),
+    ("CUP", Some(2)),
+    ("CVE", Some(2)),
+    ("CZK", Some(2)),
+    ("DJF", Some(0)),
+    ("DKK", Some(2)),
+    ("DOP", Some(2)),
+    ("DZD", Some(2)),
+    ("EGP", Some(2)),
+    ("ERN", Some(2)),
+    ("ETB", Some(2)),
+    ("EUR", Some(2)),
+    ("FJD", Some(2)),
+    ("FKP", Some(2)),
+    ("GBP", Some(2)),
+    ("GEL", Some(2)),
+    ("GHS", Some(2)),
+    ("GIP", Some(2)),
+    ("GMD", Some(2)),
+    ("GNF", Some(0)),
+    ("GTQ", Some(2)),
+    ("GYD", Some(2)),
+    ("HKD", Some(2)),
+    ("HNL", Some(2)),
+    ("HTG", Some(2)),
+    ("HUF", Some(2)),
+    ("IDR", Some(2)),
+    ("ILS", Some(2)),
+    ("INR", Some(2)),
+    ("IQD", Some(3)),
+    ("IRR", Some(2)),
+    ("ISK", Some(0)),
+    ("JMD", Some(2)),
+    ("JOD", Some(3)),
+    ("JPY", Some(0)),
+    ("KES", Some(2)),
+    ("KGS", Some(2)),
+    ("KHR", Some(2)),
+    ("KMF", Some(0)),
+    ("KPW", Some(2)),
+    ("KRW", Some(0)),
+    ("KWD", Some(3)),
+    ("KYD", Some(2)),
+    ("KZT", Some(2)),
+    ("LAK", Some(2)),
+    ("LBP", Some(2)),
+    ("LKR", Some(2)),
+    ("LRD", Some(2)),
+    ("LSL", Some(2)),
+    ("LYD", Some(3)),
+    ("MAD", Some(2)),
+    ("MDL", Some(2)),
+    ("MGA", Some(2)),
+    ("MKD", Some(2)),
+    ("MMK", Some(2)),
+    ("MNT", Some(2)),
+    ("MOP", Some(2)),
+    ("MRU", Some(2)),
+    ("MUR", Some(2)),
+    ("MVR", Some(2)),
+    ("MWK", Some(2)),
+    ("MXN", Some(2)),
+    ("MXV", Some(2)),
+    ("MYR", Some(2)),
+    ("MZN", Some(2)),
+    ("NAD", Some(2)),
+    ("NGN", Some(2)),
+    ("NIO", Some(2)),
+    ("NOK", Some(2)),
+    ("NPR", Some(2)),
+    ("NZD", Some(2)),
+    ("OMR", Some(3)),
+    ("PAB", Some(2)),
+    ("PEN", Some(2)),
+    ("PGK", Some(2)),
+    ("PHP", Some(2)),
+    ("PKR", Some(2)),
+    ("PLN", Some(2)),
+    ("PYG", Some(0)),
+    ("QAR", Some(2)),
+    ("RON", Some(2)),
+    ("RSD", Some(2)),
+    ("RUB", Some(2)),
+    ("RWF", Some(0)),
+    ("SAR", Some(2)),
+    ("SBD", Some(2)),
+    ("SCR", Some(2)),
+    ("SDG", Some(2)),
+    ("SEK", Some(2)),
+    ("SGD", Some(2)),
+    ("SHP", Some(2)),
+    ("SLE", Some(2)),
+    ("SOS", Some(2)),
+    ("SRD", Some(2)),
+    ("SSP", Some(2)),
+    ("STN", Some(2)),
+    ("SVC", Some(2)),
+    ("SYP", Some(2)),
+    ("SZL", Some(2)),
+    ("THB", Some(2)),
+    ("TJS", Some(2)),
+    ("TMT", Some(2)),
+    ("TND", Some(3)),
+    ("TOP", Some(2)),
+    ("TRY", Some(2)),
+    ("TTD", Some(2)),
+    ("TWD", Some(2)),
+    ("TZS", Some(2)),
+    ("UAH", Some(2)),
+    ("UGX", Some(0)),
+    ("USD", Some(2)),
+    ("USN", Some(2)),
+    ("UYI", Some(0)),
+    ("UYU", Some(2)),
+    ("UYW", Some(4)),
+    ("UZS", Some(2)),
+    ("VED", Some(2)),
+    ("VES", Some(2)),
+    ("VND", Some(0)),
+    ("VUV", Some(0)),
+    ("WST", Some(2)),
+    ("XAF", Some(0)),
+    ("XAG", None),
+    ("XAU", None),
+    ("XBA", None),
+    ("XBB", None),
+    ("XBC", None),
+    ("XBD", None),
+    ("XCD", Some(2)),
+    ("XCG", Some(2)),
+    ("XDR", None),
+    ("XOF", Some(0)),
+    ("XPD", None),
+    ("XPF", Some(0)),
+    ("XPT", None),
+    ("XSU", None),
+    ("XUA", None),
+    ("YER", Some(2)),
+    ("ZAR", Some(2)),
+    ("ZMW", Some(2)),
+    ("ZWG", Some(2)),
+];
+
+/// Returns the minor unit of an ISO 4217 currency: `Some(Some(2))` for
+/// `USD`, `Some(None)` for codes without a minor unit such as `XAU`, and
+/// `None` for unknown codes.
+pub fn minor_unit(currency: &str) -> Option<Option<u32>> {
+    CURRENCIES
+        .binary_search_by(|(code, _)| (*code).cmp(currency))
+        .ok()
+        .map(|index| CURRENCIES[index].1)
+}
+
+/// Pass validating money objects.
+#[derive(Debug, Clone)]
+pub struct Money {
+    patterns: Vec<String>,
+    amount: String,
+    currency: String,
+    currencies: Option<Vec<String>>,
+    negative: bool,
+}
+
+impl Default for Money {
+    fn default() -> Self {
+        Self {
+            patterns: Vec::new(),
+            amount: "amount".to_owned(),
+            currency: "currency".to_owned(),
+            currencies: None,
+            negative: true,
+        }
+    }
+}
+
+impl Money {
+    /// Creates a validator; add the locations of money objects with
+    /// [`at`](Self::at).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Validates the money objects at pointers matching `pattern`.
+    pub fn at(mut self, pattern: impl Into<String>) -> Self {
+        self.patterns.push(pattern.into());
+        self
+    }
+
+    /// Reads the amount and currency from fields other than `amount` and
+    /// `currency`.
+    pub fn fields(mut self, amount: impl Into<String>, currency: impl Into<String>) -> Self {
+        self.amount = amount.into();
+        self.currency = currency.into();
+        self
+    }
+
+    /// Only accepts the given currencies.
+    pub fn currencies<I>(mut self, currencies: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.currencies = Some(currencies.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Rejects negative amounts.
+    pub fn non_negative(mut self) -> Self {
+        self.negative = false;
+        self
+    }
+
+    fn check(&self, at: &str, value: &Value, findings: &mut Vec<InvalidParam>) {
+        let Some(object) = value.as_object() else {
+            findings.push(mismatch(at, "money", "object", value));
+            return;
+        };
+        let amount_at = pointer::join(at, &self.amount);
+        let currency_at = pointer::join(at, &self.currency);
+        // The currency decides the scale of the amount, but is reported
+        // after it, matching the usual field order.
+        let mut currency_findings = Vec::new();
+        let scale = match object.get(&self.currency) {
+            None => {
+                currency_findings.push(missing(at, &self.currency));
+                None
+            }
+            Some(Value::String(currency)) => {
+                self.currency(&currency_at, currency, &mut currency_findings)
+            }
+            Some(currency) => {
+                currency_findings.push(mismatch(&currency_at, "currency", "string", currency));
+                None
+            }
+        };
+        match object.get(&self.amount) {
+            None => findings.push(missing(at, &self.amount)),
+            Some(Value::String(amount)) => self.amount(&amount_at, amount, scale, findings),
+            Some(amount) => findings.push(mismatch(&amount_at, "decimal", "string", amount)),
+        }
+        findings.append(&mut currency_findings);
+    }
+
+    /// Checks a currency code, returning its minor unit if known.
+    fn currency(&self, at: &str, currency: &str, findings: &mut Vec<InvalidParam>) -> Option<u32> {
+        let allowed = match &self.currencies {
+            Some(currencies) => currencies.iter().any(|allowed| allowed == currency),
+            None => true,
+        };
+        match minor_unit(currency) {
+            Some(scale) if allowed => scale,
+            known => {
+                let reason = match known {
+                    Some(_) => format!("Currency `{currency}` is not accepted"),
+                    None => format!("Unknown ISO 4217 currency code `{currency}`"),
+                };
+                findings.push(
+                    InvalidParam::new(at, code::VARIANT_UNKNOWN, reason)
+                        .expected(ExpectedOrActual::new("currency", "string"))
+                        .actual(ExpectedOrActual::new("string", "string")),
+                );
+                None
+            }
+        }
+    }
+
+    fn amount(&self, at: &str, amount: &str, scale: Option<u32>, findings: &mut Vec<InvalidParam>) {
+        let invalid = |reason: String| {
+            InvalidParam::new(at, code::VALUE_INVALID, reason)
+                .expected(ExpectedOrActual::new("decimal", "string"))
+                .actual(ExpectedOrActual::new("string", "string"))
+        };
+        let (negative, digits) = match amount.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, amount),
+        };
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        let is_digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+        if whole.is_empty()
+            || !is_digits(whole)
+            || !is_digits(fraction)
+            || (digits.contains('.') && fraction.is_empty())
+        {
+            findings.push(invalid(format!(
+                "Expected a decimal number such as `10.50`, found `{amount}`"
+            )));
+            return;
+        }
+        if negative && !self.negative {
+            findings.push(invalid(format!(
+                "Expected a non-negative amount, found `{amount}`"
+            )));
+        }
+        if let Some(scale) = scale
+            && fraction.len() > scale as usize
+        {
+            findings.push(invalid(format!(
+                "Amount `{amount}` has {} decimal places, more than the currency allows ({scale})",
+                fraction.len()
+            )));
+        }
+    }
+}
+
+impl Pass for Money {
+    fn run(&self, value: &Value, findings: &mut Vec<InvalidParam>) {
+        pointer::walk(value, |at, value| {
+            if self
+                .patterns
+                .iter()
+                .any(|pattern| pointer::matches(pattern, at))
+            {
+                self.check(at, value, findings);
+            }
+        });
+    }
+}
+
+fn missing(at: &str, field: &str) -> InvalidParam {
+    InvalidParam::missing(at, field).expected(ExpectedOrActual::new("string", "string"))
+}
+
+fn mismatch(at: &str, ty: &str, format: &str, value: &Value) -> InvalidParam {
+    let actual = ExpectedOrActual::of(value);
+    InvalidParam::new(
+        at,
+        code::TYPE_MISMATCH,
+        format!("Expected {format}, found {}", actual.format),
+    )
+    .expected(ExpectedOrActual::new(ty, format))
+    .actual(actual)
+}
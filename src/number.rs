@@ -0,0 +1,145 @@
+use crate::token::{self, Visitor};
+use crate::{ExpectedOrActual, InvalidParam, code};
+
+/// Bounds on the size of numeric tokens, checked on the raw JSON text before
+/// it is parsed. See [`Options::number_limits`](crate::Options::number_limits).
+///
+/// Tokens with thousands of digits or exponents such as `1e999999999` are
+/// valid JSON, but arbitrary-precision decimal types can spend a long time
+/// on them, or allocate a lot of memory. Payloads with such tokens are
+/// rejected without deserializing anything, each token being reported with
+/// its pointer and byte offset.
+///
+/// ```
+/// use serdify::{NumberLimits, Options, code};
+///
+/// let options = Options::new().number_limits(NumberLimits::new().max_digits(20));
+/// let json = format!(r#"{{"price": 1{}, "rate": 1e-99999}}"#, "0".repeat(30));
+/// let error = options.from_str::<serde_json::Value>(&json).result.unwrap_err();
+///
+/// assert_eq!(error.invalid_params[0].pointer, "#/price");
+/// assert_eq!(error.invalid_params[0].code, code::NUMBER_DIGITS);
+/// assert_eq!(error.invalid_params[0].offset, Some(10));
+/// assert_eq!(error.invalid_params[1].pointer, "#/rate");
+/// assert_eq!(error.invalid_params[1].code, code::NUMBER_EXPONENT);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberLimits {
+    max_digits: usize,
+    max_exponent: u64,
+}
+
+impl NumberLimits {
+    /// Creates limits of 100 significand digits and exponents within ±400,
+    /// enough for every `f64`.
+    pub fn new() -> Self {
+        Self {
+            max_digits: 100,
+            max_exponent: 400,
+        }
+    }
+
+    /// Sets the most digits of a number, fraction included.
+    pub fn max_digits(mut self, max_digits: usize) -> Self {
+        self.max_digits = max_digits;
+        self
+    }
+
+    /// Sets the largest magnitude of an exponent.
+    pub fn max_exponent(mut self, max_exponent: u64) -> Self {
+        self.max_exponent = max_exponent;
+        self
+    }
+}
+
+impl Default for NumberLimits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reports every number of the first JSON document in `json` exceeding
+/// `limits`.
+pub(crate) fn scan(json: &[u8], limits: &NumberLimits) -> Vec<InvalidParam> {
+    let mut checker = Checker {
+        limits,
+        findings: Vec::new(),
+    };
+    token::walk(json, &mut checker);
+    checker.findings
+}
+
+struct Checker<'a> {
+    limits: &'a NumberLimits,
+    findings: Vec<InvalidParam>,
+}
+
+impl Visitor for Checker<'_> {
+    fn number(&mut self, at: &str, token: &[u8], offset: usize) {
+        check(at, token, offset, self.limits, &mut self.findings);
+    }
+}
+
+fn check(
+    at: &str,
+    token: &[u8],
+    offset: usize,
+    limits: &NumberLimits,
+    findings: &mut Vec<InvalidParam>,
+) {
+    let (significand, exponent) = match token.iter().position(|byte| matches!(byte, b'e' | b'E')) {
+        Some(at) => (&token[..at], Some(&token[at + 1..])),
+        None => (token, None),
+    };
+    let digits = significand
+        .iter()
+        .filter(|byte| byte.is_ascii_digit())
+        .count();
+    let (code, reason, expected) = if digits > limits.max_digits {
+        (
+            code::NUMBER_DIGITS,
+            format!(
+                "Number has {digits} digits, more than the limit of {}",
+                limits.max_digits
+            ),
+            format!("number of at most {} digits", limits.max_digits),
+        )
+    } else if let Some(exponent) = exponent.filter(|exponent| {
+        magnitude(exponent).is_none_or(|magnitude| magnitude > limits.max_exponent)
+    }) {
+        let exponent = String::from_utf8_lossy(exponent);
+        let exponent = if exponent.len() > 20 {
+            format!("of {} digits", exponent.len())
+        } else {
+            exponent.into_owned()
+        };
+        (
+            code::NUMBER_EXPONENT,
+            format!(
+                "Number has exponent {exponent}, beyond the limit of ±{}",
+                limits.max_exponent
+            ),
+            format!("exponent within ±{}", limits.max_exponent),
+        )
+    } else {
+        return;
+    };
+    findings.push(
+        InvalidParam::new(at, code, reason)
+            .offset(offset)
+            .expected(ExpectedOrActual::new(expected, "number"))
+            .actual(ExpectedOrActual::new("number", "number")),
+    );
+}
+
+/// Returns the magnitude of an exponent, or `None` if it overflows.
+fn magnitude(exponent: &[u8]) -> Option<u64> {
+    exponent
+        .iter()
+        .filter(|byte| byte.is_ascii_digit())
+        .try_fold(0u64, |magnitude, byte| {
+            magnitude
+                .checked_mul(10)?
+                .checked_add(u64::from(byte - b'0'))
+        })
+}
@@ -0,0 +1,119 @@
+//! Hooks notified of what serdify does, for logging and monitoring.
+//!
+//! Every method has an empty default implementation, so observers only
+//! implement the notifications they care about.
+//!
+//! Observers set with [`Options::observer`](crate::Options::observer) are
+//! sent an [`Event`] at each step of every validation, to drive progress
+//! displays, dashboards or adaptive limits. Besides implementing
+//! [`Observer`], events can be received by a closure wrapped with
+//! [`from_fn`] or an [`mpsc::Sender`]:
+//!
+//! ```
+//! use std::sync::mpsc;
+//! use serdify::Options;
+//! use serdify::observer::Event;
+//!
+//! let (sender, receiver) = mpsc::channel();
+//! let options = Options::new().observer(sender);
+//! let _ = options.from_str::<Vec<u8>>("[1, 300]");
+//!
+//! let events: Vec<_> = receiver.try_iter().collect();
+//! assert_eq!(
+//!     events[0],
+//!     Event::ArrayStarted { pointer: "#".to_owned(), length: 2 }
+//! );
+//! assert!(matches!(&events[1], Event::Finding(param) if param.pointer == "#/1"));
+//! assert_eq!(events[2], Event::Finished { errors: 1, warnings: 0 });
+//! ```
+
+use std::sync::mpsc;
+
+use serde::Serialize;
+
+use crate::{Error, InvalidParam};
+
+/// Receives notifications about validation.
+pub trait Observer: Send + Sync {
+    /// Called when `error` was withheld from a caller, who only received
+    /// `reference`. Log both so support can find the full error from the
+    /// reference the caller quotes.
+    fn redacted(&self, reference: &str, error: &Error) {
+        let _ = (reference, error);
+    }
+
+    /// Called at each step of a validation made through
+    /// [`Options`](crate::Options).
+    fn event(&self, event: &Event) {
+        let _ = event;
+    }
+}
+
+/// A step of a validation.
+///
+/// Containers are announced parents before children, once the payload is
+/// parsed and pre-processed, then every finding is reported in order.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Event {
+    /// An object with `members` members is about to be validated.
+    ObjectStarted { pointer: String, members: usize },
+    /// An array of `length` elements is about to be validated.
+    ArrayStarted { pointer: String, length: usize },
+    /// An error or warning was found.
+    Finding(Box<InvalidParam>),
+    /// Validation ended with `errors` errors and `warnings` warnings.
+    Finished { errors: usize, warnings: usize },
+    /// The outcome for the payload with SHA-256 `digest` was served from
+    /// the [cache](crate::cache), without validating it again.
+    CacheHit { digest: String },
+    /// The payload with SHA-256 `digest` was not in the
+    /// [cache](crate::cache) and is being validated.
+    CacheMiss { digest: String },
+}
+
+/// Creates an observer receiving events with a closure.
+///
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use serdify::Options;
+/// use serdify::observer::{self, Event};
+///
+/// static ELEMENTS: AtomicUsize = AtomicUsize::new(0);
+///
+/// let options = Options::new().observer(observer::from_fn(|event| {
+///     if let Event::ArrayStarted { length, .. } = event {
+///         ELEMENTS.fetch_add(*length, Ordering::Relaxed);
+///     }
+/// }));
+/// let _ = options.from_str::<Vec<Vec<u8>>>("[[1, 2], [3]]");
+/// assert_eq!(ELEMENTS.load(Ordering::Relaxed), 5);
+/// ```
+pub fn from_fn<F>(f: F) -> FnObserver<F>
+where
+    F: Fn(&Event) + Send + Sync,
+{
+    FnObserver { f }
+}
+
+/// Observer created by [`from_fn`].
+pub struct FnObserver<F> {
+    f: F,
+}
+
+impl<F> Observer for FnObserver<F>
+where
+    F: Fn(&Event) + Send + Sync,
+{
+    fn event(&self, event: &Event) {
+        (self.f)(event);
+    }
+}
+
+/// Sends every event down the channel, ignoring a disconnected receiver.
+impl Observer for mpsc::Sender<Event> {
+    fn event(&self, event: &Event) {
+        let _ = self.send(event.clone());
+    }
+}
@@ -0,0 +1,290 @@
+//! Validation of request bodies against OpenAPI operation schemas.
+//!
+//! [`Operation`] looks up an operation by its `operationId`, compiles the
+//! JSON schema of its request body and reports violations as serdify
+//! errors, with pointers into the body. This lets gateways validate traffic
+//! from an existing API description instead of Rust types.
+//!
+//! Schemas are evaluated as JSON Schema 2020-12, the dialect of OpenAPI 3.1.
+//! OpenAPI 3.0 documents work as long as they avoid the keywords 3.0 adds
+//! on top of JSON Schema, such as `nullable`.
+//!
+//! ```
+//! use serde_json::json;
+//! use serdify::openapi::Operation;
+//!
+//! let document = json!({
+//!     "openapi": "3.1.0",
+//!     "paths": {
+//!         "/pets": {
+//!             "post": {
+//!                 "operationId": "createPet",
+//!                 "requestBody": {
+//!                     "content": {
+//!                         "application/json": {
+//!                             "schema": { "$ref": "#/components/schemas/Pet" }
+//!                         }
+//!                     }
+//!                 }
+//!             }
+//!         }
+//!     },
+//!     "components": {
+//!         "schemas": {
+//!             "Pet": {
+//!                 "type": "object",
+//!                 "required": ["name"],
+//!                 "properties": {
+//!                     "name": { "type": "string" },
+//!                     "age": { "type": "integer", "minimum": 0 }
+//!                 }
+//!             }
+//!         }
+//!     }
+//! });
+//!
+//! let operation = Operation::new(&document, "createPet").unwrap();
+//! let error = operation.validate(&json!({"age": -1})).unwrap_err();
+//!
+//! let params: Vec<_> = error
+//!     .invalid_params
+//!     .iter()
+//!     .map(|p| (p.pointer.as_str(), p.name.as_str()))
+//!     .collect();
+//! assert_eq!(params, [("#/age", "age"), ("#", "name")]);
+//! ```
+
+use std::fmt;
+
+use jsonschema::error::{TypeKind, ValidationErrorKind};
+use jsonschema::{Resource, ValidationError, Validator};
+use serde_json::{Value, json};
+
+use crate::pass::Pass;
+use crate::{Error, ExpectedOrActual, InvalidParam, Result, code, pointer};
+
+/// Base URI the OpenAPI document is registered under while compiling.
+const BASE: &str = "urn:serdify:openapi";
+
+/// HTTP methods an OpenAPI path item may define operations for.
+const METHODS: &[&str] = &[
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// The compiled request body schema of an OpenAPI operation.
+///
+/// An operation is also a [`Pass`], so it can be added to
+/// [`Options`](crate::Options) to check the payload against the API
+/// description alongside deserialization.
+pub struct Operation {
+    id: String,
+    validator: Validator,
+}
+
+/// Why an [`Operation`] could not be built from an OpenAPI document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpecError {
+    /// No operation has the requested `operationId`.
+    OperationNotFound(String),
+    /// The operation has no request body with a JSON media type.
+    NoJsonBody(String),
+    /// A `$ref` in the document does not resolve.
+    UnresolvedRef(String),
+    /// The schema failed to compile.
+    InvalidSchema(String),
+}
+
+impl Operation {
+    /// Compiles the request body schema of the operation `operation_id`,
+    /// preferring `application/json` over other JSON media types.
+    pub fn new(document: &Value, operation_id: &str) -> std::result::Result<Self, SpecError> {
+        let (at, operation) = find_operation(document, operation_id)
+            .ok_or_else(|| SpecError::OperationNotFound(operation_id.to_owned()))?;
+        let no_body = || SpecError::NoJsonBody(operation_id.to_owned());
+        let (at, body) = resolve(
+            document,
+            pointer::join(&at, "requestBody"),
+            operation.get("requestBody").ok_or_else(no_body)?,
+        )?;
+        let content = body
+            .get("content")
+            .and_then(Value::as_object)
+            .ok_or_else(no_body)?;
+        let media_type = ["application/json"]
+            .into_iter()
+            .find(|media_type| content.contains_key(*media_type))
+            .or_else(|| {
+                content
+                    .keys()
+                    .map(String::as_str)
+                    .find(|media_type| media_type.ends_with("+json") || *media_type == "*/*")
+            })
+            .ok_or_else(no_body)?;
+        let at = pointer::join(&pointer::join(&at, "content"), media_type);
+        if content[media_type].get("schema").is_none() {
+            return Err(no_body());
+        }
+        let at = pointer::join(&at, "schema");
+
+        let resource = Resource::from_contents(document.clone())
+            .map_err(|err| SpecError::InvalidSchema(err.to_string()))?;
+        let schema = json!({ "$ref": format!("{BASE}{}", encode_fragment(&at)) });
+        let validator = jsonschema::options()
+            .with_resource(BASE, resource)
+            .build(&schema)
+            .map_err(|err| SpecError::InvalidSchema(err.to_string()))?;
+        Ok(Self {
+            id: operation_id.to_owned(),
+            validator,
+        })
+    }
+
+    /// Returns the `operationId` of the operation.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Validates a request body, reporting every violation.
+    pub fn validate(&self, body: &Value) -> Result<()> {
+        let mut findings = Vec::new();
+        self.run(body, &mut findings);
+        if findings.is_empty() {
+            Result::Ok(())
+        } else {
+            Result::Err(Error::new(findings))
+        }
+    }
+}
+
+impl Pass for Operation {
+    fn run(&self, value: &Value, findings: &mut Vec<InvalidParam>) {
+        for error in self.validator.iter_errors(value) {
+            convert(&error, findings);
+        }
+    }
+}
+
+impl fmt::Debug for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Operation").field("id", &self.id).finish()
+    }
+}
+
+impl fmt::Display for SpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpecError::OperationNotFound(id) => write!(f, "no operation with id `{id}`"),
+            SpecError::NoJsonBody(id) => {
+                write!(f, "operation `{id}` has no JSON request body schema")
+            }
+            SpecError::UnresolvedRef(reference) => write!(f, "cannot resolve `{reference}`"),
+            SpecError::InvalidSchema(message) => write!(f, "invalid schema: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for SpecError {}
+
+/// Finds the operation with the given id, returning its pointer.
+fn find_operation<'a>(document: &'a Value, operation_id: &str) -> Option<(String, &'a Value)> {
+    let paths = document.get("paths")?.as_object()?;
+    paths.iter().find_map(|(path, item)| {
+        METHODS.iter().find_map(|method| {
+            let operation = item.get(*method)?;
+            (operation.get("operationId")?.as_str()? == operation_id).then(|| {
+                let at = pointer::join(&pointer::join("#/paths", path), method);
+                (at, operation)
+            })
+        })
+    })
+}
+
+/// Follows local `$ref`s to the object they point to.
+fn resolve<'a>(
+    document: &'a Value,
+    mut at: String,
+    mut value: &'a Value,
+) -> std::result::Result<(String, &'a Value), SpecError> {
+    while let Some(reference) = value.get("$ref").and_then(Value::as_str) {
+        value = reference
+            .strip_prefix('#')
+            .and_then(|target| document.pointer(target))
+            .ok_or_else(|| SpecError::UnresolvedRef(reference.to_owned()))?;
+        at = reference.to_owned();
+    }
+    Ok((at, value))
+}
+
+/// Percent-encodes a pointer for use as a URI fragment, as described in
+/// RFC 6901, section 6.
+fn encode_fragment(pointer: &str) -> String {
+    let mut fragment = String::with_capacity(pointer.len());
+    for byte in pointer.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b'#' => {
+                fragment.push(byte as char)
+            }
+            _ => fragment.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    fragment
+}
+
+fn convert(error: &ValidationError<'_>, findings: &mut Vec<InvalidParam>) {
+    let at = format!("#{}", error.instance_path.as_str());
+    let actual = ExpectedOrActual::of(&error.instance);
+    match &error.kind {
+        ValidationErrorKind::Required { property } => {
+            let field = property.as_str().unwrap_or_default();
+            findings.push(InvalidParam::missing(at, field));
+        }
+        ValidationErrorKind::AdditionalProperties { unexpected }
+        | ValidationErrorKind::UnevaluatedProperties { unexpected } => {
+            for field in unexpected {
+                findings.push(
+                    InvalidParam::new(
+                        pointer::join(&at, field),
+                        code::FIELD_UNKNOWN,
+                        format!("Unknown field `{field}`"),
+                    )
+                    .expected(ExpectedOrActual::new("undefined", "missing"))
+                    .actual(
+                        error
+                            .instance
+                            .get(field)
+                            .map_or(actual.clone(), ExpectedOrActual::of),
+                    ),
+                );
+            }
+        }
+        ValidationErrorKind::Type { kind } => {
+            let expected = match kind {
+                TypeKind::Single(ty) => ty.to_string(),
+                TypeKind::Multiple(types) => types
+                    .iter()
+                    .map(|ty| ty.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" or "),
+            };
+            findings.push(
+                InvalidParam::new(at, code::TYPE_MISMATCH, error.to_string())
+                    .expected(ExpectedOrActual::new(expected.clone(), expected))
+                    .actual(actual),
+            );
+        }
+        ValidationErrorKind::Minimum { .. }
+        | ValidationErrorKind::Maximum { .. }
+        | ValidationErrorKind::ExclusiveMinimum { .. }
+        | ValidationErrorKind::ExclusiveMaximum { .. } => {
+            findings.push(
+                InvalidParam::new(at, code::RANGE_OVERFLOW, error.to_string())
+                    .expected(actual.clone())
+                    .actual(actual),
+            );
+        }
+        _ => {
+            findings
+                .push(InvalidParam::new(at, code::VALUE_INVALID, error.to_string()).actual(actual));
+        }
+    }
+}
@@ -0,0 +1,619 @@
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::cache::{Cache, Key};
+use crate::de::Leniency;
+use crate::duplicate::Checked;
+use crate::key_order;
+use crate::number;
+use crate::observer::{Event, Observer};
+use crate::pass::Pass;
+use crate::pipeline::{self, Stage};
+use crate::pointer::{self, Style};
+use crate::trailing::{self, Trailing};
+use crate::{
+    Error, InvalidParam, KeyOrder, NumberLimits, Outcome, Result, Severity, Suppression, canonical,
+    config, de, digest,
+};
+
+/// Configuration for a deserialization call.
+///
+/// `Options` is cheap to clone and meant to be built once and shared.
+///
+/// ```
+/// use serdify::{Options, pipeline::Trim};
+///
+/// let options = Options::new().stage(Trim);
+/// let outcome = options.from_str::<Vec<String>>(r#"["  padded  "]"#);
+///
+/// assert_eq!(outcome.result.unwrap(), ["padded"]);
+/// assert_eq!(outcome.provenance[0].pointer, "#/0");
+/// ```
+#[derive(Clone, Default)]
+pub struct Options {
+    stages: Vec<Arc<dyn Stage>>,
+    passes: Vec<Arc<dyn Pass>>,
+    style: Style,
+    digest: bool,
+    leniency: Leniency,
+    suppress_cascades: bool,
+    suppressions: Vec<Suppression>,
+    trailing: Trailing,
+    numbers: Option<NumberLimits>,
+    key_order: Option<KeyOrder>,
+    #[cfg(feature = "strict")]
+    strict: bool,
+    cache: Option<Arc<dyn Cache>>,
+    observer: Option<Arc<dyn Observer>>,
+    /// Identifies the configuration in cache keys; changed by every builder
+    /// method affecting outcomes.
+    generation: u64,
+}
+
+/// Source of configuration generations, shared by every [`Options`].
+static GENERATION: AtomicU64 = AtomicU64::new(1);
+
+fn next_generation() -> u64 {
+    GENERATION.fetch_add(1, Ordering::Relaxed)
+}
+
+impl Options {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a pre-processing stage, run after the stages already added.
+    pub fn stage(mut self, stage: impl Stage + 'static) -> Self {
+        self.stages.push(Arc::new(stage));
+        self.generation = next_generation();
+        self
+    }
+
+    /// Adds a validation pass, run over the pre-processed payload.
+    pub fn pass(mut self, pass: impl Pass + 'static) -> Self {
+        self.passes.push(Arc::new(pass));
+        self.generation = next_generation();
+        self
+    }
+
+    /// Renders error locations in `style`, in addition to the canonical
+    /// pointer. See [`Error::with_style`].
+    pub fn pointer_style(mut self, style: Style) -> Self {
+        self.style = style;
+        self.generation = next_generation();
+        self
+    }
+
+    /// Records a SHA-256 digest of each accepted payload in
+    /// [`Outcome::digest`].
+    ///
+    /// ```
+    /// use serdify::Options;
+    ///
+    /// let options = Options::new().digest();
+    /// let compact = options.from_str::<serde_json::Value>(r#"{"a":1,"b":2}"#);
+    /// let spaced = options.from_str::<serde_json::Value>(r#"{ "b": 2, "a": 1.0 }"#);
+    ///
+    /// assert_eq!(
+    ///     compact.digest.as_deref(),
+    ///     Some("43258cff783fe7036d8a43033f830adfc60ec037382473548ac742b888292777")
+    /// );
+    /// assert_eq!(compact.digest, spaced.digest);
+    /// ```
+    pub fn digest(mut self) -> Self {
+        self.digest = true;
+        self.generation = next_generation();
+        self
+    }
+
+    /// Accepts `"yes"`/`"no"`, `"on"`/`"off"`, `"y"`/`"n"`, `"true"`/`"false"`
+    /// in any case and `1`/`0` for booleans, as form data and legacy
+    /// clients send them. Each coerced value is reported as a warning.
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use serdify::{Options, code};
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Preferences {
+    ///     newsletter: bool,
+    ///     dark_mode: bool,
+    /// }
+    ///
+    /// let options = Options::new().coerce_bools();
+    /// let outcome = options.from_str::<Preferences>(r#"{"newsletter": "on", "dark_mode": 0}"#);
+    ///
+    /// let preferences = outcome.result.unwrap();
+    /// assert!(preferences.newsletter && !preferences.dark_mode);
+    /// assert_eq!(outcome.warnings.len(), 2);
+    /// assert_eq!(outcome.warnings[0].code, code::COERCED_BOOL);
+    /// ```
+    pub fn coerce_bools(mut self) -> Self {
+        self.leniency.bools = true;
+        self.generation = next_generation();
+        self
+    }
+
+    /// Matches enum variants ignoring case, `-` and `_`, so `"ACTIVE"`,
+    /// `"active"` and `"in-progress"` name the variants `Active` and
+    /// `InProgress`. Each normalized name is reported as an
+    /// [informational](Severity::Info) warning.
+    ///
+    /// Applies to enums represented by their variant name: unit variants and
+    /// externally tagged ones. Names matching several variants are left
+    /// alone.
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use serdify::{Options, Severity};
+    ///
+    /// #[derive(Debug, PartialEq, Deserialize)]
+    /// enum Status {
+    ///     Active,
+    ///     InProgress,
+    /// }
+    ///
+    /// let options = Options::new().lenient_variants();
+    /// let outcome = options.from_str::<Vec<Status>>(r#"["ACTIVE", "in_progress"]"#);
+    ///
+    /// assert_eq!(outcome.result.unwrap(), [Status::Active, Status::InProgress]);
+    /// assert_eq!(outcome.warnings[1].pointer, "#/1");
+    /// assert_eq!(outcome.warnings[1].severity, Severity::Info);
+    /// ```
+    pub fn lenient_variants(mut self) -> Self {
+        self.leniency.variants = true;
+        self.generation = next_generation();
+        self
+    }
+
+    /// Accepts the index of a unit variant, in declaration order, in place of
+    /// its name, for every enum. See [`discriminants`](Self::discriminants)
+    /// for enums numbered otherwise.
+    pub fn variant_indices(mut self) -> Self {
+        self.leniency.indices = true;
+        self.generation = next_generation();
+        self
+    }
+
+    /// Accepts the numbers of `discriminants` in place of the names of the
+    /// unit variants they map to, for the enum named `name`. Other numbers
+    /// are reported with the accepted ones.
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use serdify::Options;
+    ///
+    /// #[derive(Debug, PartialEq, Deserialize)]
+    /// enum Status {
+    ///     Draft,
+    ///     Published,
+    /// }
+    ///
+    /// let options = Options::new().discriminants("Status", [(10, "Draft"), (20, "Published")]);
+    /// let statuses = options.from_str::<Vec<Status>>(r#"[20, "Draft"]"#).result.unwrap();
+    /// assert_eq!(statuses, [Status::Published, Status::Draft]);
+    ///
+    /// let error = options.from_str::<Vec<Status>>("[30]").result.unwrap_err();
+    /// assert_eq!(error.invalid_params[0].pointer, "#/0");
+    /// assert_eq!(
+    ///     error.invalid_params[0].reason.as_deref(),
+    ///     Some("Unknown discriminant 30, expected one of 10 (`Draft`), 20 (`Published`)")
+    /// );
+    /// ```
+    pub fn discriminants<S>(
+        mut self,
+        name: impl Into<String>,
+        discriminants: impl IntoIterator<Item = (i64, S)>,
+    ) -> Self
+    where
+        S: Into<String>,
+    {
+        let discriminants = discriminants
+            .into_iter()
+            .map(|(number, variant)| (number, variant.into()))
+            .collect();
+        Arc::make_mut(&mut self.leniency.discriminants).insert(name.into(), discriminants);
+        self.generation = next_generation();
+        self
+    }
+
+    /// Leaves out errors following from another one, keeping only the root
+    /// causes of each cascade. See [`Error::link_causes`].
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use serdify::Options;
+    /// use serdify::flags::Flags;
+    ///
+    /// #[derive(Debug, Deserialize)]
+    /// struct Token {
+    ///     scope: Vec<String>,
+    /// }
+    ///
+    /// let flags = Flags::new().field("#/scope", ["read", "write"]);
+    /// let json = r#"{"scope": "read delete"}"#;
+    ///
+    /// let noisy = Options::new().pass(flags.clone()).from_str::<Token>(json);
+    /// let error = noisy.result.unwrap_err();
+    /// assert_eq!(error.invalid_params.len(), 2);
+    /// assert_eq!(error.invalid_params[1].caused_by.as_deref(), Some("#/scope"));
+    ///
+    /// let quiet = Options::new().pass(flags).suppress_cascades().from_str::<Token>(json);
+    /// let error = quiet.result.unwrap_err();
+    /// assert_eq!(error.invalid_params.len(), 1);
+    /// assert_eq!(error.invalid_params[0].code, "type.mismatch");
+    /// ```
+    pub fn suppress_cascades(mut self) -> Self {
+        self.suppress_cascades = true;
+        self.generation = next_generation();
+        self
+    }
+
+    /// Silences the findings matching `suppression`, which are then left
+    /// out of the result and the warnings but kept in
+    /// [`Outcome::suppressed`] to still be counted.
+    ///
+    /// Errors raised by the target type itself reject the payload whatever
+    /// the suppressions, since no value can be built; suppressions apply to
+    /// the findings of passes and to warnings.
+    ///
+    /// ```
+    /// use serdify::{Options, Suppression, security::Scan};
+    ///
+    /// let options = Options::new().pass(Scan::new()).suppress(
+    ///     Suppression::new("#/legacy/**", "Old partner escapes HTML itself").code("security.*"),
+    /// );
+    /// let json = r#"{"legacy": {"bio": "<script>"}, "bio": "<script>"}"#;
+    /// let outcome = options.from_str::<serde_json::Value>(json);
+    ///
+    /// assert_eq!(outcome.warnings.len(), 1);
+    /// assert_eq!(outcome.warnings[0].pointer, "#/bio");
+    /// assert_eq!(outcome.suppressed[0].pointer, "#/legacy/bio");
+    /// assert_eq!(outcome.report().suppressed, 1);
+    /// ```
+    pub fn suppress(mut self, suppression: Suppression) -> Self {
+        self.suppressions.push(suppression);
+        self.generation = next_generation();
+        self
+    }
+
+    /// Accepts payloads whose JSON document is followed by more data, as
+    /// tolerant pipelines fed by sloppy producers need. The data is ignored
+    /// and reported as a warning instead of rejecting the payload.
+    ///
+    /// ```
+    /// use serdify::{Options, code};
+    ///
+    /// let options = Options::new().accept_trailing();
+    /// let outcome = options.from_str::<Vec<u8>>("[1, 2]\n\0\0");
+    ///
+    /// assert_eq!(outcome.result.unwrap(), [1, 2]);
+    /// assert_eq!(outcome.warnings[0].code, code::SYNTAX_TRAILING);
+    /// assert_eq!(outcome.warnings[0].offset, Some(7));
+    /// ```
+    pub fn accept_trailing(mut self) -> Self {
+        self.trailing.accept = true;
+        self.generation = next_generation();
+        self
+    }
+
+    /// Quotes at most `len` characters of the data following the JSON
+    /// document in its finding, 16 by default.
+    ///
+    /// ```
+    /// use serdify::Options;
+    ///
+    /// let options = Options::new().trailing_preview(4);
+    /// let error = options.from_str::<u8>("1 2 3 4 5").result.unwrap_err();
+    ///
+    /// assert_eq!(
+    ///     error.invalid_params[0].reason.as_deref(),
+    ///     Some(r#"Unexpected data after the JSON document at byte 2: "2 3 "..."#)
+    /// );
+    /// ```
+    pub fn trailing_preview(mut self, len: usize) -> Self {
+        self.trailing.preview = len;
+        self.generation = next_generation();
+        self
+    }
+
+    /// Rejects payloads with numbers exceeding `limits` before parsing
+    /// them. See [`NumberLimits`].
+    pub fn number_limits(mut self, limits: NumberLimits) -> Self {
+        self.numbers = Some(limits);
+        self.generation = next_generation();
+        self
+    }
+
+    /// Rejects payloads whose object keys do not follow `order`. See
+    /// [`KeyOrder`].
+    pub fn key_order(mut self, order: KeyOrder) -> Self {
+        self.key_order = Some(order);
+        self.generation = next_generation();
+        self
+    }
+
+    /// Rejects JSON text that does not strictly conform to RFC 8259, such as
+    /// integers beyond 2<sup>53</sup>. See [`strict`](crate::strict).
+    #[cfg(feature = "strict")]
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self.generation = next_generation();
+        self
+    }
+
+    /// Stores outcomes of the `*_cached` methods in `cache`, so identical
+    /// payloads are only validated once. See [`cache`](crate::cache).
+    pub fn cache(mut self, cache: impl Cache + 'static) -> Self {
+        self.cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// Sends `observer` an [`Event`] at each step of every validation.
+    /// Outcomes served from the [`cache`](Self::cache) only raise an
+    /// [`Event::CacheHit`].
+    pub fn observer(mut self, observer: impl Observer + 'static) -> Self {
+        self.observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// Deserializes `T` from a JSON string.
+    pub fn from_str<T>(&self, json: &str) -> Outcome<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        self.parse(json.as_bytes())
+    }
+
+    /// Deserializes `T` from JSON bytes.
+    pub fn from_slice<T>(&self, json: &[u8]) -> Outcome<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        self.parse(json)
+    }
+
+    /// Deserializes `T` from an already parsed [`Value`].
+    ///
+    /// Checks on the raw text, such as [`strict`](Self::strict) mode, do not
+    /// apply.
+    pub fn from_value<T>(&self, value: Value) -> Outcome<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        self.validate(value, Vec::new())
+    }
+
+    /// Like [`from_str`](Self::from_str), returning a clone of the earlier
+    /// outcome when the same payload was already validated as `T`.
+    pub fn from_str_cached<T>(&self, json: &str) -> Outcome<T>
+    where
+        T: for<'de> Deserialize<'de> + Clone + Send + Sync + 'static,
+    {
+        self.cached(json.as_bytes(), || self.from_str(json))
+    }
+
+    /// Like [`from_slice`](Self::from_slice), returning a clone of the
+    /// earlier outcome when the same payload was already validated as `T`.
+    pub fn from_slice_cached<T>(&self, json: &[u8]) -> Outcome<T>
+    where
+        T: for<'de> Deserialize<'de> + Clone + Send + Sync + 'static,
+    {
+        self.cached(json, || self.from_slice(json))
+    }
+
+    fn cached<T>(&self, json: &[u8], validate: impl FnOnce() -> Outcome<T>) -> Outcome<T>
+    where
+        T: for<'de> Deserialize<'de> + Clone + Send + Sync + 'static,
+    {
+        let Some(cache) = &self.cache else {
+            return validate();
+        };
+        let key = Key::new::<T>(digest::sha256(json), self.generation);
+        let hit = cache.get(&key);
+        if let Some(outcome) = hit
+            .as_deref()
+            .and_then(|entry| entry.downcast_ref::<Outcome<T>>())
+        {
+            self.notify(Event::CacheHit {
+                digest: key.digest().to_owned(),
+            });
+            return outcome.clone();
+        }
+        self.notify(Event::CacheMiss {
+            digest: key.digest().to_owned(),
+        });
+        let outcome = validate();
+        cache.insert(key, Arc::new(outcome.clone()));
+        outcome
+    }
+
+    fn parse<T>(&self, json: &[u8]) -> Outcome<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        if let Some(limits) = &self.numbers {
+            let numbers = number::scan(json, limits);
+            if !numbers.is_empty() {
+                let error = self.finish_error(Error::new(numbers));
+                self.finish(&error.invalid_params, &[]);
+                return Outcome::new(Result::Err(error));
+            }
+        }
+        let mut findings = self.scan(json);
+        if let Some(order) = &self.key_order {
+            findings.extend(key_order::check(json, order));
+        }
+        match trailing::parse::<Checked>(json, self.trailing) {
+            Ok((checked, trailing)) => {
+                // Strict mode reports duplicate keys already.
+                let duplicates = checked.duplicates.into_iter().filter(|duplicate| {
+                    !findings.iter().any(|finding| {
+                        finding.pointer == duplicate.pointer && finding.code == duplicate.code
+                    })
+                });
+                let duplicates: Vec<_> = duplicates.collect();
+                findings.extend(duplicates);
+                findings.extend(trailing);
+                self.validate(checked.value, findings)
+            }
+            Err(err) => {
+                let error = Error {
+                    invalid_params: findings,
+                    ..Error::syntax(&err)
+                }
+                .with_style(self.style);
+                self.finish(&error.invalid_params, &[]);
+                Outcome::new(Result::Err(error))
+            }
+        }
+    }
+
+    /// Links the cascades of `error`, dropping them if asked to, caps their
+    /// number and renders its pointers.
+    fn finish_error(&self, error: Error) -> Error {
+        let mut error = error.link_causes();
+        if self.suppress_cascades {
+            error
+                .invalid_params
+                .retain(|param| param.caused_by.is_none());
+        }
+        if let Some(limit) = config::current().max_errors {
+            error.invalid_params.truncate(limit);
+        }
+        error.with_style(self.style)
+    }
+
+    /// Moves the findings matching a suppression out of `findings`.
+    fn suppressed(&self, findings: &mut Vec<InvalidParam>) -> Vec<InvalidParam> {
+        if self.suppressions.is_empty() {
+            return Vec::new();
+        }
+        let now = SystemTime::now();
+        let (suppressed, kept) = std::mem::take(findings).into_iter().partition(|finding| {
+            self.suppressions
+                .iter()
+                .any(|suppression| suppression.matches_at(finding, now))
+        });
+        *findings = kept;
+        suppressed
+    }
+
+    /// Sends `event` to the observer, if any.
+    fn notify(&self, event: Event) {
+        if let Some(observer) = &self.observer {
+            observer.event(&event);
+        }
+    }
+
+    /// Notifies the observer of the findings and the end of a validation.
+    fn finish(&self, errors: &[InvalidParam], warnings: &[InvalidParam]) {
+        let Some(observer) = &self.observer else {
+            return;
+        };
+        for finding in errors.iter().chain(warnings) {
+            observer.event(&Event::Finding(Box::new(finding.clone())));
+        }
+        observer.event(&Event::Finished {
+            errors: errors.len(),
+            warnings: warnings.len(),
+        });
+    }
+
+    #[cfg(feature = "strict")]
+    fn scan(&self, json: &[u8]) -> Vec<InvalidParam> {
+        if self.strict {
+            crate::strict::scan(json)
+        } else {
+            Vec::new()
+        }
+    }
+
+    #[cfg(not(feature = "strict"))]
+    fn scan(&self, _json: &[u8]) -> Vec<InvalidParam> {
+        Vec::new()
+    }
+
+    fn validate<T>(&self, mut value: Value, mut findings: Vec<InvalidParam>) -> Outcome<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let mut provenance = Vec::new();
+        for stage in &self.stages {
+            pipeline::run(stage.as_ref(), &mut value, &mut provenance);
+        }
+        if let Some(observer) = &self.observer {
+            pointer::walk(&value, |at, value| match value {
+                Value::Object(object) => observer.event(&Event::ObjectStarted {
+                    pointer: at.to_owned(),
+                    members: object.len(),
+                }),
+                Value::Array(array) => observer.event(&Event::ArrayStarted {
+                    pointer: at.to_owned(),
+                    length: array.len(),
+                }),
+                _ => {}
+            });
+        }
+        for pass in &self.passes {
+            pass.run(&value, &mut findings);
+        }
+        let mut suppressed = self.suppressed(&mut findings);
+        let (errors, mut warnings) = findings
+            .into_iter()
+            .partition::<Vec<_>, _>(|finding| finding.severity == Severity::Error);
+        let (result, notes) = de::from_value_lenient(&value, &self.leniency);
+        let result = match result {
+            Result::Ok(_) if !errors.is_empty() => {
+                Result::Err(self.finish_error(Error::new(errors)))
+            }
+            Result::Err(mut error) => {
+                error.invalid_params.extend(errors);
+                Result::Err(self.finish_error(error))
+            }
+            result => result,
+        };
+        warnings.extend(notes);
+        suppressed.extend(self.suppressed(&mut warnings));
+        for warning in &mut warnings {
+            warning.set_style(self.style);
+        }
+        let errors = match &result {
+            Result::Ok(_) => &[][..],
+            Result::Err(error) => &error.invalid_params,
+        };
+        self.finish(errors, &warnings);
+        let digest = (self.digest && result.is_ok())
+            .then(|| digest::sha256(canonical::to_string(&value).as_bytes()));
+        Outcome {
+            provenance,
+            warnings,
+            suppressed,
+            digest,
+            ..Outcome::new(result)
+        }
+    }
+}
+
+impl fmt::Debug for Options {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stages: Vec<_> = self.stages.iter().map(|stage| stage.name()).collect();
+        f.debug_struct("Options")
+            .field("stages", &stages)
+            .field("passes", &self.passes.len())
+            .field("style", &self.style)
+            .field("digest", &self.digest)
+            .field("leniency", &self.leniency)
+            .field("suppress_cascades", &self.suppress_cascades)
+            .field("suppressions", &self.suppressions)
+            .field("cache", &self.cache.is_some())
+            .field("observer", &self.observer.is_some())
+            .finish()
+    }
+}
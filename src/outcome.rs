@@ -0,0 +1,52 @@
+use crate::pipeline::Provenance;
+use crate::{InvalidParam, Report, Result};
+
+/// Everything a call made through [`Options`](crate::Options) produced: the
+/// result itself and the metadata gathered along the way.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Outcome<T> {
+    /// The deserialized value, or every error found.
+    pub result: Result<T>,
+    /// Changes made to the payload by pre-processing stages.
+    pub provenance: Vec<Provenance>,
+    /// Findings below [`Severity::Error`](crate::Severity::Error), which do
+    /// not reject the payload.
+    pub warnings: Vec<InvalidParam>,
+    /// Findings silenced by a [`Suppression`](crate::Suppression), left out
+    /// of the result and the warnings.
+    pub suppressed: Vec<InvalidParam>,
+    /// SHA-256 of the accepted payload in [canonical](crate::canonical)
+    /// form, as lowercase hex, when requested with
+    /// [`Options::digest`](crate::Options::digest).
+    ///
+    /// The digest covers the payload after pre-processing, so it identifies
+    /// exactly what passed validation however the client formatted it.
+    pub digest: Option<String>,
+}
+
+impl<T> Outcome<T> {
+    pub(crate) fn new(result: Result<T>) -> Self {
+        Self {
+            result,
+            provenance: Vec::new(),
+            warnings: Vec::new(),
+            suppressed: Vec::new(),
+            digest: None,
+        }
+    }
+
+    /// Discards the metadata, keeping only the result.
+    pub fn into_result(self) -> Result<T> {
+        self.result
+    }
+
+    /// Builds a report of every finding, errors and warnings alike, counting
+    /// the suppressed ones.
+    pub fn report(&self) -> Report {
+        let mut report = Report::from(&self.result);
+        report.findings.extend(self.warnings.iter().cloned());
+        report.suppressed = self.suppressed.len();
+        report
+    }
+}
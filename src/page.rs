@@ -0,0 +1,77 @@
+use serde::Serialize;
+
+use crate::{Error, InvalidParam};
+
+/// A window over the validation errors of an [`Error`], for tools that page
+/// through thousands of findings instead of rendering them all at once.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Page<'a> {
+    /// Index of the first error in this page.
+    pub offset: usize,
+    /// Maximum number of errors in a page.
+    pub limit: usize,
+    /// Total number of errors across all pages.
+    pub total: usize,
+    /// The errors in this page.
+    pub invalid_params: &'a [InvalidParam],
+}
+
+impl Page<'_> {
+    /// Returns the offset of the following page, if there is one.
+    pub fn next_offset(&self) -> Option<usize> {
+        let next = self.offset.saturating_add(self.limit);
+        (self.limit > 0 && next < self.total).then_some(next)
+    }
+
+    /// Returns the offset of the preceding page, if there is one.
+    pub fn prev_offset(&self) -> Option<usize> {
+        (self.offset > 0).then(|| self.offset.saturating_sub(self.limit))
+    }
+
+    /// Returns the total number of pages for this page size.
+    pub fn page_count(&self) -> usize {
+        if self.limit == 0 {
+            0
+        } else {
+            self.total.div_ceil(self.limit)
+        }
+    }
+}
+
+impl Error {
+    /// Returns the total number of validation errors.
+    pub fn total(&self) -> usize {
+        self.invalid_params.len()
+    }
+
+    /// Returns up to `limit` validation errors starting at `offset`.
+    ///
+    /// ```
+    /// # use serdify::from_str;
+    /// let error = from_str::<Vec<u8>>("[256, 257, 258, 259, 260]").unwrap_err();
+    ///
+    /// let page = error.page(4, 2);
+    /// assert_eq!(page.total, 5);
+    /// assert_eq!(page.invalid_params[0].pointer, "#/4");
+    /// assert_eq!(page.next_offset(), None);
+    /// ```
+    pub fn page(&self, offset: usize, limit: usize) -> Page<'_> {
+        let total = self.total();
+        let start = offset.min(total);
+        let end = start.saturating_add(limit).min(total);
+        Page {
+            offset,
+            limit,
+            total,
+            invalid_params: &self.invalid_params[start..end],
+        }
+    }
+
+    /// Iterates over every page of `limit` validation errors.
+    pub fn pages(&self, limit: usize) -> impl Iterator<Item = Page<'_>> {
+        let limit = limit.max(1);
+        (0..self.total())
+            .step_by(limit)
+            .map(move |offset| self.page(offset, limit))
+    }
+}
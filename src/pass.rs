@@ -0,0 +1,25 @@
+//! Validation passes run over the payload alongside deserialization.
+//!
+//! A pass inspects the parsed [`Value`] and reports findings of its own.
+//! Findings of [`Severity::Error`](crate::Severity::Error) reject the payload
+//! together with the deserialization errors; anything less severe is
+//! returned as a warning in [`Outcome::warnings`](crate::Outcome::warnings).
+
+use serde_json::Value;
+
+use crate::InvalidParam;
+
+/// A validation pass over the parsed payload.
+pub trait Pass: Send + Sync {
+    /// Inspects `value` and appends any findings to `findings`.
+    fn run(&self, value: &Value, findings: &mut Vec<InvalidParam>);
+}
+
+impl<F> Pass for F
+where
+    F: Fn(&Value, &mut Vec<InvalidParam>) + Send + Sync,
+{
+    fn run(&self, value: &Value, findings: &mut Vec<InvalidParam>) {
+        self(value, findings)
+    }
+}
@@ -0,0 +1,204 @@
+//! Pre-processing applied to the parsed payload before deserialization.
+//!
+//! Stages run in the order they were added to [`Options`](crate::Options),
+//! each transforming the parsed [`Value`] in place. Every change a stage
+//! makes is recorded as a [`Provenance`] entry, so audits can reconstruct
+//! exactly what was accepted versus what the client sent.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::pointer;
+
+/// A transformation applied to the payload before it is deserialized.
+pub trait Stage: Send + Sync {
+    /// Name of the stage, recorded in [`Provenance::stage`].
+    fn name(&self) -> &str;
+
+    /// Transforms the payload in place.
+    fn apply(&self, value: &mut Value);
+}
+
+/// A single change made to the payload by a [`Stage`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Provenance {
+    /// Pointer to the changed value, as seen by the stage that changed it.
+    pub pointer: String,
+    /// Name of the stage that made the change.
+    pub stage: String,
+    /// Value sent by the client, or `None` if the stage added it.
+    pub original: Option<Value>,
+    /// Value after the stage ran, or `None` if the stage removed it.
+    pub transformed: Option<Value>,
+}
+
+/// Creates a stage from a closure.
+///
+/// ```
+/// use serdify::{Options, pipeline};
+///
+/// let options = Options::new().stage(pipeline::from_fn("lowercase-email", |value| {
+///     if let Some(email) = value.pointer_mut("/email") {
+///         if let Some(lower) = email.as_str().map(str::to_lowercase) {
+///             *email = lower.into();
+///         }
+///     }
+/// }));
+///
+/// let outcome = options.from_str::<serde_json::Value>(r#"{"email": "Ada@Example.com"}"#);
+/// assert_eq!(outcome.provenance[0].pointer, "#/email");
+/// assert_eq!(outcome.provenance[0].stage, "lowercase-email");
+/// ```
+pub fn from_fn<F>(name: impl Into<String>, f: F) -> FnStage<F>
+where
+    F: Fn(&mut Value) + Send + Sync,
+{
+    FnStage {
+        name: name.into(),
+        f,
+    }
+}
+
+/// Stage created by [`from_fn`].
+pub struct FnStage<F> {
+    name: String,
+    f: F,
+}
+
+impl<F> Stage for FnStage<F>
+where
+    F: Fn(&mut Value) + Send + Sync,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn apply(&self, value: &mut Value) {
+        (self.f)(value)
+    }
+}
+
+impl<F> fmt::Debug for FnStage<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FnStage").field("name", &self.name).finish()
+    }
+}
+
+/// Sanitizer trimming leading and trailing whitespace from every string.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Trim;
+
+impl Stage for Trim {
+    fn name(&self) -> &str {
+        "trim"
+    }
+
+    fn apply(&self, value: &mut Value) {
+        match value {
+            Value::String(string) => {
+                let trimmed = string.trim();
+                if trimmed.len() != string.len() {
+                    *string = trimmed.to_owned();
+                }
+            }
+            Value::Array(array) => array.iter_mut().for_each(|value| self.apply(value)),
+            Value::Object(object) => object.values_mut().for_each(|value| self.apply(value)),
+            _ => {}
+        }
+    }
+}
+
+/// Key mapping renaming object keys at every depth, for clients whose naming
+/// convention differs from the target type's.
+#[derive(Debug, Clone, Default)]
+pub struct RenameKeys {
+    renames: Vec<(String, String)>,
+}
+
+impl RenameKeys {
+    /// Creates an empty key mapping.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renames every key `from` to `to`.
+    pub fn rename(mut self, from: impl Into<String>, to: impl Into<String>) -> Self {
+        self.renames.push((from.into(), to.into()));
+        self
+    }
+}
+
+impl Stage for RenameKeys {
+    fn name(&self) -> &str {
+        "rename-keys"
+    }
+
+    fn apply(&self, value: &mut Value) {
+        match value {
+            Value::Array(array) => array.iter_mut().for_each(|value| self.apply(value)),
+            Value::Object(object) => {
+                for (from, to) in &self.renames {
+                    if object.contains_key(to) {
+                        continue;
+                    }
+                    if let Some(value) = object.remove(from) {
+                        object.insert(to.clone(), value);
+                    }
+                }
+                object.values_mut().for_each(|value| self.apply(value));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Runs `stage` over `value`, recording every change it makes.
+pub(crate) fn run(stage: &dyn Stage, value: &mut Value, provenance: &mut Vec<Provenance>) {
+    let original = value.clone();
+    stage.apply(value);
+    diff(stage.name(), "#", &original, value, provenance);
+}
+
+fn diff(stage: &str, at: &str, before: &Value, after: &Value, out: &mut Vec<Provenance>) {
+    if before == after {
+        return;
+    }
+    match (before, after) {
+        (Value::Object(before), Value::Object(after)) => {
+            for (key, old) in before {
+                let at = pointer::join(at, key);
+                match after.get(key) {
+                    Some(new) => diff(stage, &at, old, new, out),
+                    None => out.push(change(stage, at, Some(old), None)),
+                }
+            }
+            for (key, new) in after {
+                if !before.contains_key(key) {
+                    out.push(change(stage, pointer::join(at, key), None, Some(new)));
+                }
+            }
+        }
+        (Value::Array(before), Value::Array(after)) if before.len() == after.len() => {
+            for (index, (old, new)) in before.iter().zip(after).enumerate() {
+                diff(stage, &format!("{at}/{index}"), old, new, out);
+            }
+        }
+        _ => out.push(change(stage, at.to_owned(), Some(before), Some(after))),
+    }
+}
+
+fn change(
+    stage: &str,
+    pointer: String,
+    original: Option<&Value>,
+    transformed: Option<&Value>,
+) -> Provenance {
+    Provenance {
+        pointer,
+        stage: stage.to_owned(),
+        original: original.cloned(),
+        transformed: transformed.cloned(),
+    }
+}
@@ -0,0 +1,219 @@
+//! Helpers for the JSON pointers serdify reports.
+//!
+//! Pointers are written in URI fragment form (`#/items/0/id`), but every
+//! function here also accepts the plain RFC 6901 form (`/items/0/id`).
+
+use std::borrow::Cow;
+use std::fmt::Write;
+
+use serde_json::Value;
+
+/// Location of the value being deserialized, kept as a cheap linked list so
+/// the pointer string is only built when an error is recorded.
+#[derive(Clone, Copy)]
+pub(crate) enum Path<'a> {
+    Root,
+    Key(&'a Path<'a>, &'a str),
+    Index(&'a Path<'a>, usize),
+}
+
+impl Path<'_> {
+    /// Renders the path as a URI fragment JSON pointer (`#/a/0`).
+    pub(crate) fn pointer(&self) -> String {
+        let mut pointer = String::from("#");
+        self.write(&mut pointer);
+        pointer
+    }
+
+    /// Returns the last segment of the path, used as the parameter name.
+    pub(crate) fn name(&self) -> String {
+        match self {
+            Path::Root => String::new(),
+            Path::Key(_, key) => (*key).to_owned(),
+            Path::Index(_, index) => index.to_string(),
+        }
+    }
+
+    fn write(&self, pointer: &mut String) {
+        match self {
+            Path::Root => {}
+            Path::Key(parent, key) => {
+                parent.write(pointer);
+                pointer.push('/');
+                pointer.push_str(&escape(key));
+            }
+            Path::Index(parent, index) => {
+                parent.write(pointer);
+                let _ = write!(pointer, "/{index}");
+            }
+        }
+    }
+}
+
+/// Escapes a reference token as described in RFC 6901, section 3.
+pub fn escape(token: &str) -> Cow<'_, str> {
+    if token.contains(['~', '/']) {
+        Cow::Owned(token.replace('~', "~0").replace('/', "~1"))
+    } else {
+        Cow::Borrowed(token)
+    }
+}
+
+/// Appends a reference token to a pointer.
+pub(crate) fn join(pointer: &str, token: &str) -> String {
+    format!("{pointer}/{}", escape(token))
+}
+
+/// Reverses [`escape`].
+pub fn unescape(token: &str) -> Cow<'_, str> {
+    if token.contains('~') {
+        Cow::Owned(token.replace("~1", "/").replace("~0", "~"))
+    } else {
+        Cow::Borrowed(token)
+    }
+}
+
+/// Splits a pointer into its unescaped reference tokens.
+pub fn tokens(pointer: &str) -> impl Iterator<Item = Cow<'_, str>> {
+    let pointer = pointer.strip_prefix('#').unwrap_or(pointer);
+    let pointer = pointer.strip_prefix('/');
+    pointer
+        .into_iter()
+        .flat_map(|pointer| pointer.split('/'))
+        .map(unescape)
+}
+
+/// Notation used to render a pointer for clients.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Style {
+    /// URI fragment JSON pointer, `#/items/2/price`.
+    #[default]
+    Pointer,
+    /// Dotted path, `items.2.price`, as used by JavaScript form libraries.
+    Dotted,
+    /// Bracketed path, `items[2].price`, as used by Java bean validation.
+    /// Keys that are not identifiers are quoted: `headers["x-id"]`.
+    Bracket,
+}
+
+/// Renders a pointer in the given notation.
+///
+/// Only [`Style::Pointer`] is lossless: a dotted path cannot tell a key
+/// containing a dot from two nested keys, and neither style can tell an
+/// array index from an object key made of digits.
+///
+/// ```
+/// use serdify::pointer::{Style, render};
+///
+/// assert_eq!(render("#/user/address/street", Style::Dotted), "user.address.street");
+/// assert_eq!(render("#/items/2/price", Style::Bracket), "items[2].price");
+/// assert_eq!(render("#/headers/x-id", Style::Bracket), r#"headers["x-id"]"#);
+/// ```
+pub fn render(pointer: &str, style: Style) -> String {
+    match style {
+        Style::Pointer => match pointer.strip_prefix('#') {
+            Some(_) => pointer.to_owned(),
+            None => format!("#{pointer}"),
+        },
+        Style::Dotted => tokens(pointer).collect::<Vec<_>>().join("."),
+        Style::Bracket => {
+            let mut path = String::new();
+            for token in tokens(pointer) {
+                if !token.is_empty() && token.bytes().all(|byte| byte.is_ascii_digit()) {
+                    let _ = write!(path, "[{token}]");
+                } else if is_identifier(&token) {
+                    if !path.is_empty() {
+                        path.push('.');
+                    }
+                    path.push_str(&token);
+                } else {
+                    let quoted = Value::from(token.as_ref()).to_string();
+                    let _ = write!(path, "[{quoted}]");
+                }
+            }
+            path
+        }
+    }
+}
+
+fn is_identifier(token: &str) -> bool {
+    let mut chars = token.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_alphabetic() || first == '_' || first == '$')
+        && chars.all(|char| char.is_alphanumeric() || char == '_' || char == '$')
+}
+
+/// Returns the last reference token of a pointer, or an empty string for the
+/// root.
+pub fn last_token(pointer: &str) -> String {
+    tokens(pointer)
+        .last()
+        .map(Cow::into_owned)
+        .unwrap_or_default()
+}
+
+/// Matches a pointer against a glob pattern.
+///
+/// In the pattern, `*` stands for exactly one reference token and `**` for
+/// any number of them, including none.
+///
+/// ```
+/// use serdify::pointer::matches;
+///
+/// assert!(matches("#/items/*/price", "#/items/2/price"));
+/// assert!(matches("#/user/**", "#/user/address/street"));
+/// assert!(!matches("#/items/*", "#/items/2/price"));
+/// ```
+pub fn matches(pattern: &str, pointer: &str) -> bool {
+    let pattern: Vec<_> = tokens(pattern).collect();
+    let pointer: Vec<_> = tokens(pointer).collect();
+    glob(&pattern, &pointer)
+}
+
+fn glob(pattern: &[Cow<'_, str>], pointer: &[Cow<'_, str>]) -> bool {
+    match pattern.split_first() {
+        None => pointer.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=pointer.len()).any(|skip| glob(rest, &pointer[skip..]))
+        }
+        Some((first, rest)) => match pointer.split_first() {
+            Some((token, remaining)) => (first == "*" || first == token) && glob(rest, remaining),
+            None => false,
+        },
+    }
+}
+
+/// Returns `true` if `pointer` is `ancestor` or lies beneath it.
+pub fn starts_with(pointer: &str, ancestor: &str) -> bool {
+    let mut pointer = tokens(pointer);
+    tokens(ancestor).all(|token| pointer.next().is_some_and(|next| next == token))
+}
+
+/// Calls `f` with the pointer of every value in `value`, parents before
+/// children.
+pub fn walk(value: &Value, mut f: impl FnMut(&str, &Value)) {
+    fn visit(at: &mut String, value: &Value, f: &mut dyn FnMut(&str, &Value)) {
+        f(at, value);
+        let len = at.len();
+        match value {
+            Value::Array(array) => {
+                for (index, value) in array.iter().enumerate() {
+                    let _ = write!(at, "/{index}");
+                    visit(at, value, f);
+                    at.truncate(len);
+                }
+            }
+            Value::Object(object) => {
+                for (key, value) in object {
+                    at.push('/');
+                    at.push_str(&escape(key));
+                    visit(at, value, f);
+                    at.truncate(len);
+                }
+            }
+            _ => {}
+        }
+    }
+    visit(&mut String::from("#"), value, &mut f);
+}
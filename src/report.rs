@@ -0,0 +1,288 @@
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, InvalidParam, Result, Severity};
+
+/// Every finding about a payload, whether or not it was accepted.
+///
+/// Unlike [`Error`], a report can hold warnings and notes alongside errors,
+/// and leaves the accept/reject decision to a [`Policy`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Report {
+    /// JSON syntax error, if the payload could not be parsed at all.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Every finding, errors and warnings alike.
+    #[serde(default)]
+    pub findings: Vec<InvalidParam>,
+    /// Number of findings silenced by a [`Suppression`](crate::Suppression)
+    /// and left out of [`findings`](Self::findings).
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub suppressed: usize,
+}
+
+impl Report {
+    /// Creates an empty report.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a finding to the report.
+    pub fn push(&mut self, finding: InvalidParam) {
+        self.findings.push(finding);
+    }
+
+    /// Returns `true` if the payload could not be parsed as JSON.
+    pub fn is_syntax_error(&self) -> bool {
+        self.detail.is_some()
+    }
+
+    /// Returns the findings `policy` considers fatal.
+    pub fn fatal<'a>(&'a self, policy: &'a Policy) -> impl Iterator<Item = &'a InvalidParam> {
+        self.findings
+            .iter()
+            .filter(move |finding| policy.is_fatal(finding))
+    }
+
+    /// Returns `true` if `policy` accepts the payload described by this
+    /// report.
+    ///
+    /// ```
+    /// use serdify::{Policy, Report, from_str, code};
+    ///
+    /// let error = from_str::<Vec<u8>>("[1, 300]").unwrap_err();
+    /// let report = Report::from(&error);
+    ///
+    /// assert!(!report.passes(&Policy::new()));
+    /// assert!(report.passes(&Policy::new().tolerate(code::RANGE_OVERFLOW)));
+    /// ```
+    pub fn passes(&self, policy: &Policy) -> bool {
+        !self.is_syntax_error() && self.fatal(policy).next().is_none()
+    }
+
+    /// Builds the problem document listing the findings `policy` considers
+    /// fatal, or `None` if the report passes.
+    pub fn to_error(&self, policy: &Policy) -> Option<Error> {
+        if self.passes(policy) {
+            return None;
+        }
+        Some(Error {
+            detail: self.detail.clone(),
+            ..Error::new(self.fatal(policy).cloned().collect())
+        })
+    }
+
+    /// Returns the process exit code standing for this report under
+    /// `policy`, for command-line and batch tools. See [`ExitCodes`].
+    ///
+    /// ```
+    /// use std::process::ExitCode;
+    ///
+    /// use serdify::{ExitCodes, Policy, Report, from_str};
+    ///
+    /// let invalid = Report::from(&from_str::<Vec<u8>>("[300]").unwrap_err());
+    /// let syntax = Report::from(&from_str::<Vec<u8>>("[300").unwrap_err());
+    ///
+    /// assert_eq!(Report::new().exit_code(&Policy::new()), 0);
+    /// assert_eq!(invalid.exit_code(&Policy::new()), 1);
+    /// assert_eq!(syntax.exit_code(&Policy::new()), 2);
+    ///
+    /// let policy = Policy::new().exit_codes(ExitCodes { invalid: 65, ..ExitCodes::default() });
+    /// let code = ExitCode::from(invalid.exit_code(&policy));
+    /// assert_eq!(code, ExitCode::from(65));
+    /// ```
+    pub fn exit_code(&self, policy: &Policy) -> u8 {
+        let codes = policy.codes();
+        if self.is_syntax_error() {
+            codes.syntax
+        } else if self.passes(policy) {
+            codes.ok
+        } else {
+            codes.invalid
+        }
+    }
+}
+
+impl From<&Error> for Report {
+    fn from(error: &Error) -> Self {
+        Self {
+            detail: error.is_syntax().then(|| error.detail.clone()).flatten(),
+            findings: error.invalid_params.clone(),
+            suppressed: 0,
+        }
+    }
+}
+
+impl From<Error> for Report {
+    fn from(error: Error) -> Self {
+        Self {
+            detail: error.is_syntax().then_some(error.detail).flatten(),
+            findings: error.invalid_params,
+            suppressed: 0,
+        }
+    }
+}
+
+impl<T> From<&Result<T>> for Report {
+    fn from(result: &Result<T>) -> Self {
+        match result {
+            Result::Ok(_) => Self::new(),
+            Result::Err(error) => error.into(),
+        }
+    }
+}
+
+/// Decides which findings of a [`Report`] are fatal and which are tolerated.
+///
+/// By default every finding of [`Severity::Error`] is fatal and everything
+/// else is tolerated. Rules keyed by [`code`](crate::code) override the
+/// severity threshold; when several rules match a code, the last one added
+/// wins. A code ending in `*` matches every code starting with the rest.
+#[derive(Debug, Clone)]
+pub struct Policy {
+    threshold: Severity,
+    rules: Vec<Rule>,
+    now: Option<SystemTime>,
+    codes: ExitCodes,
+}
+
+/// Process exit codes for the outcomes of a validation, see
+/// [`Report::exit_code`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ExitCodes {
+    /// The payload is acceptable. 0 by default.
+    pub ok: u8,
+    /// The payload has fatal findings. 1 by default.
+    pub invalid: u8,
+    /// The payload is not valid JSON. 2 by default.
+    pub syntax: u8,
+    /// The tool failed for reasons of its own, such as an unreadable file.
+    /// 3 by default; never returned by serdify itself.
+    pub internal: u8,
+}
+
+impl Default for ExitCodes {
+    fn default() -> Self {
+        Self {
+            ok: 0,
+            invalid: 1,
+            syntax: 2,
+            internal: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    code: String,
+    fatal: bool,
+    until: Option<SystemTime>,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            threshold: Severity::Error,
+            rules: Vec::new(),
+            now: None,
+            codes: ExitCodes::default(),
+        }
+    }
+}
+
+impl Policy {
+    /// Creates a policy rejecting errors and tolerating everything else.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a policy rejecting every finding, warnings and notes included.
+    pub fn strict() -> Self {
+        Self::new().threshold(Severity::Info)
+    }
+
+    /// Makes every finding at or above `severity` fatal.
+    pub fn threshold(mut self, severity: Severity) -> Self {
+        self.threshold = severity;
+        self
+    }
+
+    /// Tolerates findings with the given code.
+    pub fn tolerate(mut self, code: impl Into<String>) -> Self {
+        self.rules.push(Rule {
+            code: code.into(),
+            fatal: false,
+            until: None,
+        });
+        self
+    }
+
+    /// Tolerates findings with the given code until `until`, after which
+    /// they become fatal. Useful to phase out deprecated input.
+    pub fn tolerate_until(mut self, code: impl Into<String>, until: SystemTime) -> Self {
+        self.rules.push(Rule {
+            code: code.into(),
+            fatal: false,
+            until: Some(until),
+        });
+        self
+    }
+
+    /// Makes findings with the given code fatal whatever their severity.
+    pub fn deny(mut self, code: impl Into<String>) -> Self {
+        self.rules.push(Rule {
+            code: code.into(),
+            fatal: true,
+            until: None,
+        });
+        self
+    }
+
+    /// Evaluates time-limited rules at `now` instead of the system clock.
+    pub fn at(mut self, now: SystemTime) -> Self {
+        self.now = Some(now);
+        self
+    }
+
+    /// Maps the outcomes of a validation to `codes` instead of the default
+    /// exit codes.
+    pub fn exit_codes(mut self, codes: ExitCodes) -> Self {
+        self.codes = codes;
+        self
+    }
+
+    /// Returns the exit codes of this policy.
+    pub fn codes(&self) -> &ExitCodes {
+        &self.codes
+    }
+
+    /// Returns `true` if `finding` makes the payload unacceptable.
+    pub fn is_fatal(&self, finding: &InvalidParam) -> bool {
+        let rule = self
+            .rules
+            .iter()
+            .rev()
+            .find(|rule| code_matches(&rule.code, &finding.code));
+        match rule {
+            Some(rule) => match rule.until {
+                Some(until) => rule.fatal || self.now.unwrap_or_else(SystemTime::now) >= until,
+                None => rule.fatal,
+            },
+            None => finding.severity >= self.threshold,
+        }
+    }
+}
+
+/// Matches a code against a rule pattern, where a trailing `*` matches any
+/// suffix.
+pub(crate) fn code_matches(pattern: &str, code: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => code.starts_with(prefix),
+        None => pattern == code,
+    }
+}
+
+fn is_zero(count: &usize) -> bool {
+    *count == 0
+}
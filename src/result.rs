@@ -0,0 +1,258 @@
+use crate::Error;
+
+/// The outcome of a serdify deserialization.
+///
+/// This mirrors [`std::result::Result`] with the error type fixed to
+/// [`Error`], and converts to and from it freely.
+#[must_use = "this `Result` may be an `Err` variant, which should be handled"]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Result<T> {
+    /// The payload was valid and deserialized into `T`.
+    Ok(T),
+    /// The payload was invalid; every problem found is listed in the error.
+    Err(Error),
+}
+
+impl<T> Result<T> {
+    /// Returns `true` if the result is [`Ok`](Result::Ok).
+    pub fn is_ok(&self) -> bool {
+        matches!(self, Result::Ok(_))
+    }
+
+    /// Returns `true` if the result is [`Ok`](Result::Ok) and the value
+    /// matches the predicate.
+    pub fn is_ok_and(self, f: impl FnOnce(T) -> bool) -> bool {
+        match self {
+            Result::Ok(value) => f(value),
+            Result::Err(_) => false,
+        }
+    }
+
+    /// Returns `true` if the result is [`Err`](Result::Err).
+    pub fn is_err(&self) -> bool {
+        !self.is_ok()
+    }
+
+    /// Returns `true` if the result is [`Err`](Result::Err) and the error
+    /// matches the predicate.
+    pub fn is_err_and(self, f: impl FnOnce(Error) -> bool) -> bool {
+        match self {
+            Result::Ok(_) => false,
+            Result::Err(error) => f(error),
+        }
+    }
+
+    /// Converts into an [`Option`], discarding the error.
+    pub fn ok(self) -> Option<T> {
+        match self {
+            Result::Ok(value) => Some(value),
+            Result::Err(_) => None,
+        }
+    }
+
+    /// Converts into an [`Option`], discarding the value.
+    pub fn err(self) -> Option<Error> {
+        match self {
+            Result::Ok(_) => None,
+            Result::Err(error) => Some(error),
+        }
+    }
+
+    /// Borrows the contents of the result.
+    pub fn as_ref(&self) -> std::result::Result<&T, &Error> {
+        match self {
+            Result::Ok(value) => Ok(value),
+            Result::Err(error) => Err(error),
+        }
+    }
+
+    /// Mutably borrows the contents of the result.
+    pub fn as_mut(&mut self) -> std::result::Result<&mut T, &mut Error> {
+        match self {
+            Result::Ok(value) => Ok(value),
+            Result::Err(error) => Err(error),
+        }
+    }
+
+    /// Maps the success value, leaving an error untouched.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Result<U> {
+        match self {
+            Result::Ok(value) => Result::Ok(f(value)),
+            Result::Err(error) => Result::Err(error),
+        }
+    }
+
+    /// Maps the success value, or returns `default` on error.
+    pub fn map_or<U>(self, default: U, f: impl FnOnce(T) -> U) -> U {
+        match self {
+            Result::Ok(value) => f(value),
+            Result::Err(_) => default,
+        }
+    }
+
+    /// Maps the success value, or computes a fallback from the error.
+    pub fn map_or_else<U>(self, default: impl FnOnce(Error) -> U, f: impl FnOnce(T) -> U) -> U {
+        match self {
+            Result::Ok(value) => f(value),
+            Result::Err(error) => default(error),
+        }
+    }
+
+    /// Maps the error, producing a standard library result.
+    pub fn map_err<F>(self, f: impl FnOnce(Error) -> F) -> std::result::Result<T, F> {
+        match self {
+            Result::Ok(value) => Ok(value),
+            Result::Err(error) => Err(f(error)),
+        }
+    }
+
+    /// Calls `f` with a reference to the success value, if any.
+    pub fn inspect(self, f: impl FnOnce(&T)) -> Self {
+        if let Result::Ok(value) = &self {
+            f(value);
+        }
+        self
+    }
+
+    /// Calls `f` with a reference to the error, if any.
+    pub fn inspect_err(self, f: impl FnOnce(&Error)) -> Self {
+        if let Result::Err(error) = &self {
+            f(error);
+        }
+        self
+    }
+
+    /// Returns an iterator over the success value, if any.
+    pub fn iter(&self) -> std::option::IntoIter<&T> {
+        self.as_ref().ok().into_iter()
+    }
+
+    /// Returns the success value, panicking with `msg` on error.
+    #[track_caller]
+    pub fn expect(self, msg: &str) -> T {
+        match self {
+            Result::Ok(value) => value,
+            Result::Err(error) => panic!("{msg}: {error:?}"),
+        }
+    }
+
+    /// Returns the success value, panicking on error.
+    #[track_caller]
+    pub fn unwrap(self) -> T {
+        match self {
+            Result::Ok(value) => value,
+            Result::Err(error) => {
+                panic!("called `Result::unwrap()` on an `Err` value: {error:?}")
+            }
+        }
+    }
+
+    /// Returns the error, panicking with `msg` on success.
+    #[track_caller]
+    pub fn expect_err(self, msg: &str) -> Error {
+        match self {
+            Result::Ok(_) => panic!("{msg}"),
+            Result::Err(error) => error,
+        }
+    }
+
+    /// Returns the error, panicking on success.
+    #[track_caller]
+    pub fn unwrap_err(self) -> Error {
+        match self {
+            Result::Ok(_) => panic!("called `Result::unwrap_err()` on an `Ok` value"),
+            Result::Err(error) => error,
+        }
+    }
+
+    /// Returns the success value or `default`.
+    pub fn unwrap_or(self, default: T) -> T {
+        match self {
+            Result::Ok(value) => value,
+            Result::Err(_) => default,
+        }
+    }
+
+    /// Returns the success value or computes one from the error.
+    pub fn unwrap_or_else(self, f: impl FnOnce(Error) -> T) -> T {
+        match self {
+            Result::Ok(value) => value,
+            Result::Err(error) => f(error),
+        }
+    }
+
+    /// Returns the success value or `T::default()`.
+    pub fn unwrap_or_default(self) -> T
+    where
+        T: Default,
+    {
+        match self {
+            Result::Ok(value) => value,
+            Result::Err(_) => T::default(),
+        }
+    }
+
+    /// Returns `res` if the result is [`Ok`](Result::Ok), otherwise the error.
+    pub fn and<U>(self, res: Result<U>) -> Result<U> {
+        match self {
+            Result::Ok(_) => res,
+            Result::Err(error) => Result::Err(error),
+        }
+    }
+
+    /// Chains another fallible operation on the success value.
+    pub fn and_then<U>(self, f: impl FnOnce(T) -> Result<U>) -> Result<U> {
+        match self {
+            Result::Ok(value) => f(value),
+            Result::Err(error) => Result::Err(error),
+        }
+    }
+
+    /// Returns `res` if the result is [`Err`](Result::Err), otherwise the value.
+    pub fn or(self, res: Result<T>) -> Result<T> {
+        match self {
+            Result::Ok(value) => Result::Ok(value),
+            Result::Err(_) => res,
+        }
+    }
+
+    /// Recovers from an error with another fallible operation.
+    pub fn or_else(self, f: impl FnOnce(Error) -> Result<T>) -> Result<T> {
+        match self {
+            Result::Ok(value) => Result::Ok(value),
+            Result::Err(error) => f(error),
+        }
+    }
+
+    /// Converts into a standard library result, enabling the `?` operator.
+    pub fn into_std(self) -> std::result::Result<T, Error> {
+        self.into()
+    }
+}
+
+impl<T> From<Result<T>> for std::result::Result<T, Error> {
+    fn from(result: Result<T>) -> Self {
+        match result {
+            Result::Ok(value) => Ok(value),
+            Result::Err(error) => Err(error),
+        }
+    }
+}
+
+impl<T> From<std::result::Result<T, Error>> for Result<T> {
+    fn from(result: std::result::Result<T, Error>) -> Self {
+        match result {
+            Ok(value) => Result::Ok(value),
+            Err(error) => Result::Err(error),
+        }
+    }
+}
+
+impl<T> IntoIterator for Result<T> {
+    type Item = T;
+    type IntoIter = std::option::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ok().into_iter()
+    }
+}
@@ -0,0 +1,118 @@
+use serde::Deserialize;
+
+use crate::{Error, ExpectedOrActual, InvalidParam, Result, code, from_str, pointer};
+
+/// Result of [`revalidate_at`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Revalidation<F> {
+    /// The replacement fragment, or its errors located within the whole
+    /// payload.
+    pub fragment: Result<F>,
+    /// Every error left in the whole payload, or `None` once all are fixed.
+    pub error: Option<Error>,
+}
+
+/// Validates `fragment` as the replacement of the value at `pointer`, and
+/// updates the errors of the whole payload accordingly.
+///
+/// `F` is the type of the field at `pointer` and `previous` the error of the
+/// last validation of the whole payload, if any. Errors at or beneath
+/// `pointer` are replaced by those of the fragment, in place, so a form can
+/// give feedback one field at a time without validating everything again.
+///
+/// ```
+/// use serde::Deserialize;
+/// use serdify::{from_str, revalidate_at};
+///
+/// #[derive(Debug, Deserialize)]
+/// struct Address {
+///     street: String,
+///     zip: u32,
+/// }
+///
+/// #[derive(Debug, Deserialize)]
+/// struct User {
+///     age: u8,
+///     address: Address,
+/// }
+///
+/// let error = from_str::<User>(r#"{"age": 300, "address": {"street": 1, "zip": "x"}}"#).unwrap_err();
+/// assert_eq!(error.invalid_params.len(), 3);
+///
+/// let fixed = revalidate_at::<Address>(Some(&error), "#/address", r#"{"street": "Main", "zip": 1234}"#);
+/// assert_eq!(fixed.fragment.unwrap().zip, 1234);
+///
+/// let remaining = fixed.error.unwrap();
+/// assert_eq!(remaining.invalid_params.len(), 1);
+/// assert_eq!(remaining.invalid_params[0].pointer, "#/age");
+///
+/// let partial = revalidate_at::<Address>(None, "#/address", r#"{"street": "Main"}"#);
+/// let missing = &partial.error.unwrap().invalid_params[0];
+/// assert_eq!((missing.pointer.as_str(), missing.name.as_str()), ("#/address", "zip"));
+/// ```
+pub fn revalidate_at<F>(previous: Option<&Error>, pointer: &str, fragment: &str) -> Revalidation<F>
+where
+    F: for<'de> Deserialize<'de>,
+{
+    let fragment = match from_str::<F>(fragment) {
+        Result::Ok(value) => Result::Ok(value),
+        Result::Err(error) => Result::Err(rebase(error, pointer)),
+    };
+    let mut params = Vec::new();
+    let mut replaced_at = None;
+    for param in previous.into_iter().flat_map(|error| &error.invalid_params) {
+        if pointer::starts_with(&param.pointer, pointer) {
+            replaced_at.get_or_insert(params.len());
+        } else {
+            params.push(param.clone());
+        }
+    }
+    if let Result::Err(error) = &fragment {
+        let index = replaced_at.unwrap_or(params.len());
+        params.splice(index..index, error.invalid_params.iter().cloned());
+    }
+    let error = (!params.is_empty()).then(|| Error::new(params));
+    Revalidation { fragment, error }
+}
+
+/// Moves the errors of a fragment beneath `pointer`, turning a syntax error
+/// into an error located there.
+///
+/// Errors about the fragment itself are renamed after `at`, except missing
+/// fields, which are named after the field.
+pub(crate) fn rebase(error: Error, at: &str) -> Error {
+    if error.is_syntax() {
+        let reason = error.detail.unwrap_or_default();
+        let param = InvalidParam::new(at, code::SYNTAX, reason)
+            .expected(ExpectedOrActual::new("json", "json"))
+            .actual(ExpectedOrActual::new("text", "string"));
+        return Error::new(vec![param]);
+    }
+    let params = error
+        .invalid_params
+        .into_iter()
+        .map(|param| {
+            let rebase = |pointer: &str| {
+                let relative = pointer.strip_prefix('#').unwrap_or(pointer);
+                format!("{at}{relative}")
+            };
+            let pointer = rebase(&param.pointer);
+            let name = if param.pointer == "#" && param.code != code::FIELD_MISSING {
+                pointer::last_token(&pointer)
+            } else {
+                param.name
+            };
+            InvalidParam {
+                name,
+                caused_by: param.caused_by.as_deref().map(rebase),
+                pointer,
+                path: None,
+                ..param
+            }
+        })
+        .collect();
+    Error {
+        invalid_params: params,
+        ..error
+    }
+}
@@ -0,0 +1,77 @@
+use serde::Serialize;
+
+use crate::error::is_root_cause;
+use crate::{Error, InvalidParam, Severity};
+
+/// An invalid parameter that follows from no other one, with the errors
+/// following from it. See [`Error::root_causes`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct RootCause<'a> {
+    /// The invalid parameter at the root of the cascade.
+    pub param: &'a InvalidParam,
+    /// Number of invalid parameters following from it.
+    pub consequences: usize,
+    /// How likely this is the problem to fix first, between 0 and 1.
+    pub confidence: f64,
+}
+
+impl Error {
+    /// Collapses cascades of errors into their root causes, most likely
+    /// culprit first, for display in UIs and logs.
+    ///
+    /// Causes are linked as by [`link_causes`](Self::link_causes). A value
+    /// of the wrong type ranks above an error about a value's contents, an
+    /// error above a warning, and the more errors follow from a cause the
+    /// higher it ranks. Causes of equal confidence keep their order.
+    ///
+    /// ```
+    /// use serdify::{Error, InvalidParam, Severity, code};
+    ///
+    /// let error = Error::new(vec![
+    ///     InvalidParam::new("#/name", code::SECURITY_SCRIPT, "Contains <script>")
+    ///         .severity(Severity::Warning),
+    ///     InvalidParam::new("#/age", code::RANGE_OVERFLOW, "Value 300 is out of range"),
+    ///     InvalidParam::new("#/address", code::TYPE_MISMATCH, "Expected object, found array"),
+    ///     InvalidParam::new("#/address/0", code::VALUE_INVALID, "Expected a street"),
+    /// ]);
+    ///
+    /// let causes = error.root_causes();
+    /// let pointers: Vec<_> = causes.iter().map(|cause| cause.param.pointer.as_str()).collect();
+    /// assert_eq!(pointers, ["#/address", "#/age", "#/name"]);
+    /// assert_eq!(causes[0].consequences, 1);
+    /// ```
+    pub fn root_causes(&self) -> Vec<RootCause<'_>> {
+        let causes = self.causes();
+        let mut roots: Vec<_> = self
+            .invalid_params
+            .iter()
+            .zip(&causes)
+            .filter(|(_, cause)| cause.is_none())
+            .map(|(param, _)| {
+                let consequences = causes
+                    .iter()
+                    .filter(|cause| cause.as_deref() == Some(param.pointer.as_str()))
+                    .count();
+                RootCause {
+                    param,
+                    consequences,
+                    confidence: confidence(param, consequences),
+                }
+            })
+            .collect();
+        roots.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        roots
+    }
+}
+
+/// Scores `param` by its severity and kind, approaching 1 as more errors
+/// follow from it.
+fn confidence(param: &InvalidParam, consequences: usize) -> f64 {
+    let base = match param.severity {
+        Severity::Error if is_root_cause(&param.code) => 0.8,
+        Severity::Error => 0.5,
+        Severity::Warning => 0.25,
+        Severity::Info => 0.1,
+    };
+    1.0 - (1.0 - base) / (consequences as f64 + 1.0)
+}
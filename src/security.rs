@@ -0,0 +1,159 @@
+//! Basic input hygiene signals for string content.
+//!
+//! [`Scan`] is a [`Pass`] flagging strings that look like injection attempts:
+//! embedded `<script>` tags, SQL meta-sequences and extremely long tokens.
+//! Findings are reported as [warnings](Severity::Warning) by default; they
+//! are heuristics, not a substitute for escaping output or parameterized
+//! queries.
+//!
+//! ```
+//! use serdify::{Options, security::Scan};
+//!
+//! let options = Options::new().pass(Scan::new());
+//! let outcome = options.from_str::<serde_json::Value>(
+//!     r#"{"comment": "<script>alert(1)</script>", "name": "x' OR 1=1 --"}"#,
+//! );
+//!
+//! assert!(outcome.result.is_ok());
+//! assert_eq!(outcome.warnings[0].pointer, "#/comment");
+//! assert_eq!(outcome.warnings[1].pointer, "#/name");
+//! ```
+
+use serde_json::Value;
+
+use crate::pass::Pass;
+use crate::{ExpectedOrActual, InvalidParam, Severity, code, pointer};
+
+/// Markers of script injection, matched case-insensitively.
+const SCRIPT_MARKERS: &[&str] = &["<script", "javascript:", "onerror=", "onload=", "<iframe"];
+
+/// Markers of SQL injection, matched case-insensitively.
+const SQL_MARKERS: &[&str] = &[
+    "' or ",
+    "\" or ",
+    "'--",
+    "' --",
+    "1=1",
+    ";--",
+    "/*",
+    "union select",
+    "; drop ",
+    ";drop ",
+    "xp_cmdshell",
+];
+
+/// Which checks apply to a string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rules {
+    /// Flag embedded `<script>` tags and similar script vectors.
+    pub script: bool,
+    /// Flag SQL meta-sequences such as `' OR 1=1 --`.
+    pub sql: bool,
+    /// Flag whitespace-separated tokens longer than this many characters.
+    pub max_token_len: Option<usize>,
+    /// Severity of the findings.
+    pub severity: Severity,
+}
+
+impl Rules {
+    /// Rules with every check disabled.
+    pub const NONE: Rules = Rules {
+        script: false,
+        sql: false,
+        max_token_len: None,
+        severity: Severity::Warning,
+    };
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            script: true,
+            sql: true,
+            max_token_len: Some(256),
+            severity: Severity::Warning,
+        }
+    }
+}
+
+/// Pass flagging suspicious string content, configurable per field.
+#[derive(Debug, Clone, Default)]
+pub struct Scan {
+    default: Rules,
+    fields: Vec<(String, Rules)>,
+}
+
+impl Scan {
+    /// Creates a scan applying the default [`Rules`] to every string.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the rules applied to strings no field override matches.
+    pub fn rules(mut self, rules: Rules) -> Self {
+        self.default = rules;
+        self
+    }
+
+    /// Applies `rules` to strings whose pointer matches `pattern`, a glob as
+    /// accepted by [`pointer::matches`]. Later overrides take precedence.
+    pub fn field(mut self, pattern: impl Into<String>, rules: Rules) -> Self {
+        self.fields.push((pattern.into(), rules));
+        self
+    }
+
+    /// Disables every check for strings whose pointer matches `pattern`.
+    pub fn ignore(self, pattern: impl Into<String>) -> Self {
+        self.field(pattern, Rules::NONE)
+    }
+
+    fn rules_at(&self, at: &str) -> &Rules {
+        self.fields
+            .iter()
+            .rev()
+            .find(|(pattern, _)| pointer::matches(pattern, at))
+            .map_or(&self.default, |(_, rules)| rules)
+    }
+}
+
+impl Pass for Scan {
+    fn run(&self, value: &Value, findings: &mut Vec<InvalidParam>) {
+        pointer::walk(value, |at, value| {
+            let Value::String(string) = value else {
+                return;
+            };
+            let rules = self.rules_at(at);
+            let lower = string.to_lowercase();
+            let finding = |code: &str, reason: String| {
+                InvalidParam::new(at, code, reason)
+                    .expected(ExpectedOrActual::new("string", "plain text"))
+                    .actual(ExpectedOrActual::new("string", "string"))
+                    .severity(rules.severity)
+            };
+            if rules.script
+                && let Some(marker) = SCRIPT_MARKERS.iter().find(|m| lower.contains(*m))
+            {
+                let reason = format!("String contains the script injection marker `{marker}`");
+                findings.push(finding(code::SECURITY_SCRIPT, reason));
+            }
+            if rules.sql
+                && let Some(marker) = SQL_MARKERS.iter().find(|m| lower.contains(*m))
+            {
+                let reason = format!("String contains the SQL meta-sequence `{}`", marker.trim());
+                findings.push(finding(code::SECURITY_SQL, reason));
+            }
+            if let Some(max) = rules.max_token_len {
+                let longest = string
+                    .split_whitespace()
+                    .map(|token| token.chars().count())
+                    .max();
+                if let Some(len) = longest.filter(|len| *len > max) {
+                    let reason = format!(
+                        "String contains a token of {len} characters, longer than the limit of {max}"
+                    );
+                    findings.push(finding(code::SECURITY_TOKEN, reason));
+                }
+            }
+        });
+    }
+}
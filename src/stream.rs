@@ -0,0 +1,111 @@
+//! Streams of concatenated JSON documents.
+//!
+//! Some loggers write several top-level values one after the other, with or
+//! without whitespace between them, as in JSON Lines. Parsing such input as
+//! one document reports every document after the first as trailing data;
+//! the functions here yield each document in turn instead, with the offset
+//! it starts at.
+//!
+//! ```
+//! use serdify::stream;
+//!
+//! let input = "[1, 2]\n[3, 300] [4]{\"a\": 1}";
+//! let documents: Vec<_> = stream::from_str::<Vec<u8>>(input).collect();
+//!
+//! assert_eq!(documents.len(), 4);
+//! assert_eq!(documents[1].offset, 7);
+//! assert_eq!(documents[1].result.as_ref().unwrap_err().invalid_params[0].pointer, "#/1");
+//! assert_eq!(documents[2].result.as_ref().unwrap(), &[4]);
+//! assert_eq!(documents[3].offset, 19);
+//! ```
+//!
+//! A syntax error ends the stream, since the start of the next document
+//! cannot be told anymore.
+
+use std::marker::PhantomData;
+
+use serde::Deserialize;
+use serde_json::Deserializer;
+use serde_json::de::{SliceRead, StreamDeserializer};
+
+use crate::duplicate::Checked;
+use crate::{Error, Result, from_value};
+
+/// One document of a stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document<T> {
+    /// Byte offset of the first character of the document in the input.
+    pub offset: usize,
+    /// The deserialized document, or every error found in it.
+    pub result: Result<T>,
+}
+
+/// Iterator over the documents of a stream, see [`from_slice`].
+pub struct Documents<'a, T> {
+    input: &'a [u8],
+    values: StreamDeserializer<'a, SliceRead<'a>, Checked>,
+    done: bool,
+    marker: PhantomData<fn() -> T>,
+}
+
+/// Deserializes each `T` of a stream of concatenated JSON documents.
+pub fn from_str<T>(input: &str) -> Documents<'_, T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    from_slice(input.as_bytes())
+}
+
+/// Deserializes each `T` of a stream of concatenated JSON documents given
+/// as bytes.
+pub fn from_slice<T>(input: &[u8]) -> Documents<'_, T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    Documents {
+        input,
+        values: Deserializer::from_slice(input).into_iter(),
+        done: false,
+        marker: PhantomData,
+    }
+}
+
+impl<T> Iterator for Documents<'_, T>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    type Item = Document<T>;
+
+    fn next(&mut self) -> Option<Document<T>> {
+        if self.done {
+            return None;
+        }
+        let offset = skip_whitespace(self.input, self.values.byte_offset());
+        let result = match self.values.next()? {
+            Ok(checked) if checked.duplicates.is_empty() => from_value(&checked.value),
+            Ok(checked) => {
+                let mut error = match from_value::<T>(&checked.value) {
+                    Result::Ok(_) => Error::new(Vec::new()),
+                    Result::Err(error) => error,
+                };
+                error.invalid_params.extend(checked.duplicates);
+                Result::Err(error)
+            }
+            Err(err) => {
+                self.done = true;
+                Result::Err(Error::syntax(&err))
+            }
+        };
+        Some(Document { offset, result })
+    }
+}
+
+/// Returns the offset of the first non-whitespace byte at or after `offset`.
+pub(crate) fn skip_whitespace(input: &[u8], offset: usize) -> usize {
+    let rest = input.get(offset..).unwrap_or_default();
+    offset
+        + rest
+            .iter()
+            .take_while(|byte| matches!(byte, b' ' | b'\t' | b'\n' | b'\r'))
+            .count()
+}
@@ -0,0 +1,122 @@
+//! Strict RFC 8259 conformance checks on raw JSON text.
+//!
+//! `serde_json` accepts some input that RFC 8259 leaves undefined or warns
+//! against: when a key is repeated it silently keeps the last value, and
+//! integers beyond 2<sup>53</sup> lose precision in most other parsers. It
+//! also rejects escaped lone surrogates with a bare syntax error. [`scan`]
+//! reads the text itself and reports each of these as a finding with a
+//! pointer, for boundaries that must certify strict JSON compliance.
+//!
+//! Duplicate keys are reported by every call, strict or not; [`scan`]
+//! reports them as well for callers using it on its own.
+//!
+//! Enable it per call with [`Options::strict`](crate::Options::strict):
+//!
+//! ```
+//! use serdify::{Options, code};
+//!
+//! let outcome = Options::new()
+//!     .strict()
+//!     .from_str::<serde_json::Value>(r#"{"user": {"id": 1, "id": 2}}"#);
+//!
+//! let error = outcome.result.unwrap_err();
+//! assert_eq!(error.invalid_params.len(), 1);
+//! assert_eq!(error.invalid_params[0].pointer, "#/user/id");
+//! assert_eq!(error.invalid_params[0].code, code::FIELD_DUPLICATE);
+//! ```
+
+use std::collections::HashSet;
+
+use crate::token::{self, Visitor};
+use crate::{ExpectedOrActual, InvalidParam, code, pointer};
+
+/// Largest integer every IEEE 754 double-based parser represents exactly,
+/// see RFC 8259, section 6.
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// Scans JSON text for constructs strict RFC 8259 conformance rejects.
+///
+/// Syntax errors are not reported: scanning simply stops there, leaving
+/// them to the parser.
+pub fn scan(json: &[u8]) -> Vec<InvalidParam> {
+    let mut checker = Checker {
+        keys: Vec::new(),
+        findings: Vec::new(),
+    };
+    token::walk(json, &mut checker);
+    checker.findings
+}
+
+struct Checker {
+    /// Keys met so far in each enclosing object, innermost last.
+    keys: Vec<HashSet<String>>,
+    findings: Vec<InvalidParam>,
+}
+
+impl Visitor for Checker {
+    fn object(&mut self, _at: &str) {
+        self.keys.push(HashSet::new());
+    }
+
+    fn key(&mut self, object: &str, key: &str, _offset: usize) {
+        let Some(keys) = self.keys.last_mut() else {
+            return;
+        };
+        if !keys.insert(key.to_owned()) {
+            self.findings.push(
+                InvalidParam::new(
+                    pointer::join(object, key),
+                    code::FIELD_DUPLICATE,
+                    format!("Duplicate key `{key}`; parsers disagree on which value wins"),
+                )
+                .expected(ExpectedOrActual::new("unique key", "string"))
+                .actual(ExpectedOrActual::new("duplicate key", "string")),
+            );
+        }
+    }
+
+    fn object_end(&mut self, _at: &str) {
+        self.keys.pop();
+    }
+
+    fn string(&mut self, at: &str, token: &[u8], _offset: usize) {
+        let Some((_, Some(unit))) = token::decode(token) else {
+            return;
+        };
+        self.findings.push(
+            InvalidParam::new(
+                at,
+                code::STRICT_SURROGATE,
+                format!("String contains the unpaired surrogate \\u{unit:04x}"),
+            )
+            .expected(ExpectedOrActual::new("string", "string"))
+            .actual(ExpectedOrActual::new("string", "string")),
+        );
+    }
+
+    fn number(&mut self, at: &str, token: &[u8], _offset: usize) {
+        let Ok(number) = std::str::from_utf8(token) else {
+            return;
+        };
+        if number.contains(['.', 'e', 'E']) {
+            return;
+        }
+        let magnitude = number.trim_start_matches('-');
+        let unsafe_integer = magnitude
+            .parse::<u64>()
+            .map_or(true, |magnitude| magnitude > MAX_SAFE_INTEGER);
+        if unsafe_integer {
+            self.findings.push(
+                InvalidParam::new(
+                    at,
+                    code::STRICT_NUMBER,
+                    format!(
+                        "Integer {number} is outside ±{MAX_SAFE_INTEGER} and loses precision in many parsers"
+                    ),
+                )
+                .expected(ExpectedOrActual::new("safe integer", "integer"))
+                .actual(ExpectedOrActual::new("integer", "integer")),
+            );
+        }
+    }
+}
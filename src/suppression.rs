@@ -0,0 +1,85 @@
+use std::time::SystemTime;
+
+use crate::InvalidParam;
+use crate::pointer;
+use crate::report::code_matches;
+
+/// A known, benign finding to silence, such as a deprecated field still
+/// sent by one legacy client. See [`Options::suppress`](crate::Options::suppress).
+///
+/// A suppression matches findings whose pointer matches its
+/// [`pointer::matches`] pattern and whose code matches one of its codes, or
+/// any code if it has none. Codes ending in `*` match every code starting
+/// with the rest. Every suppression carries a comment saying why it exists,
+/// and may expire so it does not outlive its reason.
+///
+/// ```
+/// use std::time::{Duration, SystemTime};
+///
+/// use serdify::{InvalidParam, Suppression, code};
+///
+/// let suppression = Suppression::new("#/items/*/sku", "Partner X still sends SKUs, see #142")
+///     .code(code::FIELD_UNKNOWN)
+///     .until(SystemTime::UNIX_EPOCH + Duration::from_secs(1_900_000_000));
+///
+/// let finding = InvalidParam::new("#/items/3/sku", code::FIELD_UNKNOWN, "Unknown field");
+/// let before = SystemTime::UNIX_EPOCH + Duration::from_secs(1_800_000_000);
+/// let after = SystemTime::UNIX_EPOCH + Duration::from_secs(2_000_000_000);
+///
+/// assert!(suppression.matches_at(&finding, before));
+/// assert!(!suppression.matches_at(&finding, after));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suppression {
+    pointer: String,
+    codes: Vec<String>,
+    until: Option<SystemTime>,
+    comment: String,
+}
+
+impl Suppression {
+    /// Suppresses findings at pointers matching `pointer`, for the reason
+    /// given in `comment`.
+    pub fn new(pointer: impl Into<String>, comment: impl Into<String>) -> Self {
+        Self {
+            pointer: pointer.into(),
+            codes: Vec::new(),
+            until: None,
+            comment: comment.into(),
+        }
+    }
+
+    /// Restricts the suppression to findings with `code`, in addition to
+    /// the codes already given.
+    pub fn code(mut self, code: impl Into<String>) -> Self {
+        self.codes.push(code.into());
+        self
+    }
+
+    /// Stops suppressing anything at `until`.
+    pub fn until(mut self, until: SystemTime) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    /// Returns why the suppression exists.
+    pub fn comment(&self) -> &str {
+        &self.comment
+    }
+
+    /// Returns `true` if the suppression silences `finding` now.
+    pub fn matches(&self, finding: &InvalidParam) -> bool {
+        self.matches_at(finding, SystemTime::now())
+    }
+
+    /// Returns `true` if the suppression silences `finding` at `now`.
+    pub fn matches_at(&self, finding: &InvalidParam, now: SystemTime) -> bool {
+        self.until.is_none_or(|until| now < until)
+            && pointer::matches(&self.pointer, &finding.pointer)
+            && (self.codes.is_empty()
+                || self
+                    .codes
+                    .iter()
+                    .any(|code| code_matches(code, &finding.code)))
+    }
+}
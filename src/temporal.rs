@@ -0,0 +1,369 @@
+//! Constraints on dates and times, evaluated against an injectable clock.
+//!
+//! [`Temporal`] is a [`Pass`] checking RFC 3339 date-times (`2024-05-01T12:00:00+02:00`)
+//! and full dates (`2024-05-01`) found at pointers matching glob patterns.
+//! Values are validated against the calendar, so `2023-02-29` is rejected,
+//! and time-zone offsets are applied before comparing. Rules relative to
+//! "now" ask the [`Clock`], which tests can pin to a fixed instant.
+//!
+//! ```
+//! use std::time::{Duration, UNIX_EPOCH};
+//! use serdify::{Options, code, temporal::{Bound, Temporal}};
+//!
+//! // 2024-05-01T00:00:00Z
+//! let now = UNIX_EPOCH + Duration::from_secs(1_714_521_600);
+//! let temporal = Temporal::new()
+//!     .clock(now)
+//!     .after("#/starts_at", Bound::Now)
+//!     .within_days("#/starts_at", 30)
+//!     .before("#/birthday", Bound::Now);
+//!
+//! let outcome = Options::new().pass(temporal).from_str::<serde_json::Value>(
+//!     r#"{"starts_at": "2024-07-01T09:00:00+02:00", "birthday": "2023-02-29"}"#,
+//! );
+//!
+//! let error = outcome.result.unwrap_err();
+//! assert_eq!(error.invalid_params[0].pointer, "#/birthday");
+//! assert_eq!(error.invalid_params[0].code, code::VALUE_INVALID);
+//! assert_eq!(error.invalid_params[1].pointer, "#/starts_at");
+//! assert_eq!(error.invalid_params[1].code, code::RANGE_OVERFLOW);
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_json::Value;
+
+use crate::pass::Pass;
+use crate::{ExpectedOrActual, InvalidParam, code, pointer};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Source of the current time.
+pub trait Clock: Send + Sync {
+    /// Returns the current time.
+    fn now(&self) -> SystemTime;
+}
+
+/// Clock reading the system time.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A fixed instant is a clock that never moves, for tests and replays.
+impl Clock for SystemTime {
+    fn now(&self) -> SystemTime {
+        *self
+    }
+}
+
+/// Instant a temporal rule compares against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    /// The time reported by the [`Clock`] when the pass runs.
+    Now,
+    /// A fixed instant.
+    At(SystemTime),
+}
+
+impl From<SystemTime> for Bound {
+    fn from(at: SystemTime) -> Self {
+        Bound::At(at)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Rule {
+    After(Bound),
+    Before(Bound),
+    WithinDays(u32),
+}
+
+/// Pass checking dates and times against temporal rules.
+#[derive(Clone)]
+pub struct Temporal {
+    clock: Arc<dyn Clock>,
+    rules: Vec<(String, Rule)>,
+}
+
+impl Default for Temporal {
+    fn default() -> Self {
+        Self {
+            clock: Arc::new(SystemClock),
+            rules: Vec::new(),
+        }
+    }
+}
+
+impl Temporal {
+    /// Creates a pass without rules, reading the system clock.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Evaluates [`Bound::Now`] against `clock` instead of the system clock.
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Requires values at pointers matching `pattern` to be strictly after
+    /// `bound`.
+    pub fn after(mut self, pattern: impl Into<String>, bound: impl Into<Bound>) -> Self {
+        self.rules.push((pattern.into(), Rule::After(bound.into())));
+        self
+    }
+
+    /// Requires values at pointers matching `pattern` to be strictly before
+    /// `bound`.
+    pub fn before(mut self, pattern: impl Into<String>, bound: impl Into<Bound>) -> Self {
+        self.rules
+            .push((pattern.into(), Rule::Before(bound.into())));
+        self
+    }
+
+    /// Requires values at pointers matching `pattern` to lie within `days`
+    /// days of now, in the past or the future.
+    pub fn within_days(mut self, pattern: impl Into<String>, days: u32) -> Self {
+        self.rules.push((pattern.into(), Rule::WithinDays(days)));
+        self
+    }
+
+    fn check(&self, at: &str, value: &Value, now: SystemTime, findings: &mut Vec<InvalidParam>) {
+        let mut rules = self
+            .rules
+            .iter()
+            .filter(|(pattern, _)| pointer::matches(pattern, at))
+            .map(|(_, rule)| *rule)
+            .peekable();
+        if rules.peek().is_none() {
+            return;
+        }
+        let expected = ExpectedOrActual::new("date-time", "string");
+        let Some(string) = value.as_str() else {
+            let actual = ExpectedOrActual::of(value);
+            findings.push(
+                InvalidParam::new(
+                    at,
+                    code::TYPE_MISMATCH,
+                    format!("Expected an RFC 3339 date-time, found {}", actual.format),
+                )
+                .expected(expected)
+                .actual(actual),
+            );
+            return;
+        };
+        let actual = ExpectedOrActual::of(value);
+        let time = match parse(string) {
+            Ok(time) => time,
+            Err(reason) => {
+                findings.push(
+                    InvalidParam::new(at, code::VALUE_INVALID, reason)
+                        .expected(expected)
+                        .actual(actual),
+                );
+                return;
+            }
+        };
+        let resolve = |bound| match bound {
+            Bound::Now => now,
+            Bound::At(at) => at,
+        };
+        for rule in rules {
+            let reason = match rule {
+                Rule::After(bound) if time <= resolve(bound) => {
+                    format!("Expected a time after {}, found {string}", describe(bound))
+                }
+                Rule::Before(bound) if time >= resolve(bound) => {
+                    format!("Expected a time before {}, found {string}", describe(bound))
+                }
+                Rule::WithinDays(days) if !within(time, now, days) => {
+                    format!("Expected a time within {days} days of now, found {string}")
+                }
+                _ => continue,
+            };
+            findings.push(
+                InvalidParam::new(at, code::RANGE_OVERFLOW, reason)
+                    .expected(expected.clone())
+                    .actual(actual.clone()),
+            );
+        }
+    }
+}
+
+impl Pass for Temporal {
+    fn run(&self, value: &Value, findings: &mut Vec<InvalidParam>) {
+        let now = self.clock.now();
+        pointer::walk(value, |at, value| self.check(at, value, now, findings));
+    }
+}
+
+impl fmt::Debug for Temporal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Temporal")
+            .field("rules", &self.rules)
+            .finish_non_exhaustive()
+    }
+}
+
+fn within(time: SystemTime, now: SystemTime, days: u32) -> bool {
+    let distance = time
+        .duration_since(now)
+        .or_else(|_| now.duration_since(time))
+        .unwrap_or_default();
+    distance <= Duration::from_secs(u64::from(days) * SECONDS_PER_DAY)
+}
+
+fn describe(bound: Bound) -> String {
+    match bound {
+        Bound::Now => "now".to_owned(),
+        Bound::At(at) => format(at),
+    }
+}
+
+/// Formats a time as an RFC 3339 date-time in UTC, to the second.
+fn format(time: SystemTime) -> String {
+    let seconds = match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => since.as_secs() as i64,
+        Err(err) => -(err.duration().as_secs_f64().ceil() as i64),
+    };
+    let (days, second_of_day) = (
+        seconds.div_euclid(SECONDS_PER_DAY as i64),
+        seconds.rem_euclid(SECONDS_PER_DAY as i64),
+    );
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        second_of_day / 3600,
+        second_of_day / 60 % 60,
+        second_of_day % 60
+    )
+}
+
+/// Parses an RFC 3339 date-time or full date, the latter at midnight UTC.
+fn parse(string: &str) -> Result<SystemTime, String> {
+    let invalid = || format!("Expected an RFC 3339 date-time, found `{string}`");
+    let bytes = string.as_bytes();
+    let digits = |range: std::ops::Range<usize>| -> Result<u32, String> {
+        let slice = bytes.get(range).ok_or_else(invalid)?;
+        if !slice.iter().all(u8::is_ascii_digit) {
+            return Err(invalid());
+        }
+        Ok(slice
+            .iter()
+            .fold(0, |n, digit| n * 10 + u32::from(digit - b'0')))
+    };
+    if bytes.get(4) != Some(&b'-') || bytes.get(7) != Some(&b'-') {
+        return Err(invalid());
+    }
+    let (year, month, day) = (digits(0..4)?, digits(5..7)?, digits(8..10)?);
+    if !(1..=12).contains(&month) {
+        return Err(format!("Month {month} does not exist, in `{string}`"));
+    }
+    if day == 0 || day > days_in_month(year, month) {
+        return Err(format!(
+            "Day {day} does not exist in {year}-{month:02}, in `{string}`"
+        ));
+    }
+    let mut seconds = days_from_civil(year, month, day) * SECONDS_PER_DAY as i64;
+    let mut nanos = 0;
+    if bytes.len() > 10 {
+        if !matches!(bytes[10], b'T' | b't' | b' ')
+            || bytes.get(13) != Some(&b':')
+            || bytes.get(16) != Some(&b':')
+        {
+            return Err(invalid());
+        }
+        let (hour, minute, second) = (digits(11..13)?, digits(14..16)?, digits(17..19)?);
+        // A leap second is accepted and counted as the last second of the minute.
+        if hour > 23 || minute > 59 || second > 60 {
+            return Err(format!("Time of day does not exist, in `{string}`"));
+        }
+        seconds += i64::from(hour * 3600 + minute * 60 + second.min(59));
+        let mut rest = &string[19..];
+        if let Some(fraction) = rest.strip_prefix('.') {
+            let len = fraction.bytes().take_while(u8::is_ascii_digit).count();
+            if len == 0 {
+                return Err(invalid());
+            }
+            nanos = fraction[..len.min(9)]
+                .bytes()
+                .chain(std::iter::repeat(b'0'))
+                .take(9)
+                .fold(0, |n, digit| n * 10 + u32::from(digit - b'0'));
+            rest = &fraction[len..];
+        }
+        match rest.as_bytes() {
+            [b'Z' | b'z'] => {}
+            [sign @ (b'+' | b'-'), h1, h2, b':', m1, m2] => {
+                let offset = [h1, h2, m1, m2];
+                if !offset.iter().all(|digit| digit.is_ascii_digit()) {
+                    return Err(invalid());
+                }
+                let [h1, h2, m1, m2] = offset.map(|digit| i64::from(digit - b'0'));
+                let (hours, minutes) = (h1 * 10 + h2, m1 * 10 + m2);
+                if hours > 23 || minutes > 59 {
+                    return Err(invalid());
+                }
+                let offset = hours * 3600 + minutes * 60;
+                seconds -= if *sign == b'+' { offset } else { -offset };
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    let time = if seconds >= 0 {
+        UNIX_EPOCH + Duration::new(seconds as u64, nanos)
+    } else {
+        UNIX_EPOCH - Duration::from_secs(seconds.unsigned_abs())
+            + Duration::from_nanos(u64::from(nanos))
+    };
+    Ok(time)
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => {
+            29
+        }
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date, after Howard
+/// Hinnant's `days_from_civil`.
+fn days_from_civil(year: u32, month: u32, day: u32) -> i64 {
+    let year = i64::from(year) - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Reverses [`days_from_civil`].
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
@@ -0,0 +1,317 @@
+//! String policies for user-facing fields.
+//!
+//! [`Strings`] is a [`Pass`] enforcing [`Rules`] on string values: a
+//! maximum length counted in grapheme clusters, as readers count
+//! characters, a single line, and printable characters only. Byte and
+//! `char` counts both overcount emoji and letters with combining accents,
+//! so `"👍🏽"` and `"é"` written as `e` and U+0301 are one grapheme each.
+//!
+//! ```
+//! use serdify::Options;
+//! use serdify::text::{Rules, Strings};
+//!
+//! let nickname = Rules {
+//!     max_graphemes: Some(3),
+//!     single_line: true,
+//!     ..Rules::NONE
+//! };
+//! let options = Options::new().pass(Strings::new().field("#/nickname", nickname));
+//!
+//! let fits = options.from_str::<serde_json::Value>(r#"{"nickname": "👨‍👩‍👧🇪🇸é"}"#);
+//! assert!(fits.result.is_ok());
+//!
+//! let error = options
+//!     .from_str::<serde_json::Value>(r#"{"nickname": "ab\ncd"}"#)
+//!     .result
+//!     .unwrap_err();
+//! let codes: Vec<_> = error.invalid_params.iter().map(|p| p.code.as_str()).collect();
+//! assert_eq!(codes, ["text.length", "text.multiline"]);
+//! ```
+//!
+//! Grapheme clusters follow the extended rules of
+//! [UAX #29](https://www.unicode.org/reports/tr29/) with built-in tables
+//! covering combining marks of the common scripts, emoji sequences, flags
+//! and Hangul syllables.
+
+use serde_json::Value;
+
+use crate::pass::Pass;
+use crate::{ExpectedOrActual, InvalidParam, Severity, code, pointer};
+
+/// Which checks apply to a string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rules {
+    /// Reject strings longer than this many grapheme clusters.
+    pub max_graphemes: Option<usize>,
+    /// Reject line breaks, including U+2028 and U+2029.
+    pub single_line: bool,
+    /// Reject control characters and invisible formatting characters such
+    /// as bidirectional overrides.
+    pub printable: bool,
+    /// Severity of the findings.
+    pub severity: Severity,
+}
+
+impl Rules {
+    /// Rules with every check disabled.
+    pub const NONE: Rules = Rules {
+        max_graphemes: None,
+        single_line: false,
+        printable: false,
+        severity: Severity::Error,
+    };
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+/// Pass enforcing [`Rules`] on strings, configurable per field.
+#[derive(Debug, Clone, Default)]
+pub struct Strings {
+    default: Rules,
+    fields: Vec<(String, Rules)>,
+}
+
+impl Strings {
+    /// Creates a pass applying no rules until some are set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the rules applied to strings no field override matches.
+    pub fn rules(mut self, rules: Rules) -> Self {
+        self.default = rules;
+        self
+    }
+
+    /// Applies `rules` to strings whose pointer matches `pattern`, a glob as
+    /// accepted by [`pointer::matches`]. Later overrides take precedence.
+    pub fn field(mut self, pattern: impl Into<String>, rules: Rules) -> Self {
+        self.fields.push((pattern.into(), rules));
+        self
+    }
+
+    fn rules_at(&self, at: &str) -> &Rules {
+        self.fields
+            .iter()
+            .rev()
+            .find(|(pattern, _)| pointer::matches(pattern, at))
+            .map_or(&self.default, |(_, rules)| rules)
+    }
+}
+
+impl Pass for Strings {
+    fn run(&self, value: &Value, findings: &mut Vec<InvalidParam>) {
+        pointer::walk(value, |at, value| {
+            let Value::String(string) = value else {
+                return;
+            };
+            let rules = self.rules_at(at);
+            let finding = |code: &str, expected: &str, reason: String| {
+                InvalidParam::new(at, code, reason)
+                    .expected(ExpectedOrActual::new("string", expected))
+                    .actual(ExpectedOrActual::new("string", "string"))
+                    .severity(rules.severity)
+            };
+            if let Some(max) = rules.max_graphemes {
+                let len = graphemes(string);
+                if len > max {
+                    let reason =
+                        format!("String is {len} characters long, longer than the limit of {max}");
+                    findings.push(finding(code::TEXT_LENGTH, "short text", reason));
+                }
+            }
+            if rules.single_line && string.chars().any(is_line_break) {
+                let reason = "String spans several lines".to_owned();
+                findings.push(finding(code::TEXT_MULTILINE, "single line", reason));
+            }
+            if rules.printable
+                && let Some(c) = string.chars().find(|c| !is_printable(*c))
+            {
+                let reason = format!(
+                    "String contains the unprintable character U+{:04X}",
+                    c as u32
+                );
+                findings.push(finding(code::TEXT_UNPRINTABLE, "printable text", reason));
+            }
+        });
+    }
+}
+
+/// Counts the extended grapheme clusters of `text`.
+pub fn graphemes(text: &str) -> usize {
+    let mut count = 0;
+    let mut previous: Option<char> = None;
+    // Whether the cluster so far is an emoji followed by extenders, and how
+    // many regional indicators end it, for GB11 and GB12/13.
+    let mut pictographic = false;
+    let mut indicators = 0;
+    for c in text.chars() {
+        let class = Class::of(c);
+        let joined = previous.is_some_and(|previous| {
+            let before = Class::of(previous);
+            match (before, class) {
+                (Class::Cr, Class::Lf) => true,
+                (Class::Control | Class::Cr | Class::Lf, _) => false,
+                (_, Class::Control | Class::Cr | Class::Lf) => false,
+                (Class::L, Class::L | Class::V | Class::Lv | Class::Lvt) => true,
+                (Class::Lv | Class::V, Class::V | Class::T) => true,
+                (Class::Lvt | Class::T, Class::T) => true,
+                (_, Class::Extend | Class::Zwj) => true,
+                (Class::Zwj, Class::Pictographic) => pictographic,
+                (Class::RegionalIndicator, Class::RegionalIndicator) => indicators % 2 == 1,
+                _ => false,
+            }
+        });
+        if !joined {
+            count += 1;
+            pictographic = false;
+            indicators = 0;
+        }
+        match class {
+            Class::Pictographic => pictographic = true,
+            Class::Extend | Class::Zwj => {}
+            Class::RegionalIndicator => {
+                indicators += 1;
+                pictographic = false;
+            }
+            _ => pictographic = false,
+        }
+        previous = Some(c);
+    }
+    count
+}
+
+fn is_line_break(c: char) -> bool {
+    matches!(
+        c,
+        '\n' | '\r' | '\u{0B}' | '\u{0C}' | '\u{85}' | '\u{2028}' | '\u{2029}'
+    )
+}
+
+fn is_printable(c: char) -> bool {
+    !c.is_control()
+        && !matches!(
+            c,
+            '\u{200B}'
+                | '\u{200E}'..='\u{200F}'
+                | '\u{2028}'..='\u{202E}'
+                | '\u{2060}'..='\u{2064}'
+                | '\u{2066}'..='\u{2069}'
+                | '\u{FEFF}'
+                | '\u{FFF9}'..='\u{FFFB}'
+        )
+}
+
+/// Grapheme cluster break property of a character, as far as the rules
+/// implemented by [`graphemes`] need it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Cr,
+    Lf,
+    Control,
+    Extend,
+    Zwj,
+    RegionalIndicator,
+    Pictographic,
+    L,
+    V,
+    T,
+    Lv,
+    Lvt,
+    Other,
+}
+
+impl Class {
+    fn of(c: char) -> Self {
+        let code = c as u32;
+        match c {
+            '\r' => Class::Cr,
+            '\n' => Class::Lf,
+            '\u{200D}' => Class::Zwj,
+            '\u{1F1E6}'..='\u{1F1FF}' => Class::RegionalIndicator,
+            '\u{1100}'..='\u{115F}' | '\u{A960}'..='\u{A97C}' => Class::L,
+            '\u{1160}'..='\u{11A7}' | '\u{D7B0}'..='\u{D7C6}' => Class::V,
+            '\u{11A8}'..='\u{11FF}' | '\u{D7CB}'..='\u{D7FB}' => Class::T,
+            '\u{AC00}'..='\u{D7A3}' if (code - 0xAC00).is_multiple_of(28) => Class::Lv,
+            '\u{AC00}'..='\u{D7A3}' => Class::Lvt,
+            '\u{2028}' | '\u{2029}' => Class::Control,
+            _ if c.is_control() => Class::Control,
+            _ if in_table(code, EXTEND) => Class::Extend,
+            _ if in_table(code, PICTOGRAPHIC) => Class::Pictographic,
+            _ => Class::Other,
+        }
+    }
+}
+
+fn in_table(code: u32, table: &[(u32, u32)]) -> bool {
+    table
+        .binary_search_by(|&(start, end)| {
+            if end < code {
+                std::cmp::Ordering::Less
+            } else if start > code {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .is_ok()
+}
+
+/// Characters extending the preceding cluster: combining and spacing
+/// marks, joiners, variation selectors, emoji modifiers and tags.
+#[rustfmt::skip]
+const EXTEND: &[(u32, u32)] = &[
+    (0x0300, 0x036F), (0x0483, 0x0489), (0x0591, 0x05BD), (0x05BF, 0x05BF),
+    (0x05C1, 0x05C2), (0x05C4, 0x05C5), (0x05C7, 0x05C7), (0x0610, 0x061A),
+    (0x064B, 0x065F), (0x0670, 0x0670), (0x06D6, 0x06DC), (0x06DF, 0x06E4),
+    (0x06E7, 0x06E8), (0x06EA, 0x06ED), (0x0711, 0x0711), (0x0730, 0x074A),
+    (0x07A6, 0x07B0), (0x07EB, 0x07F3), (0x07FD, 0x07FD), (0x0816, 0x0819),
+    (0x081B, 0x0823), (0x0825, 0x0827), (0x0829, 0x082D), (0x0859, 0x085B),
+    (0x0898, 0x089F), (0x08CA, 0x08E1), (0x08E3, 0x0903), (0x093A, 0x093C),
+    (0x093E, 0x094F), (0x0951, 0x0957), (0x0962, 0x0963), (0x0981, 0x0983),
+    (0x09BC, 0x09BC), (0x09BE, 0x09C4), (0x09C7, 0x09C8), (0x09CB, 0x09CD),
+    (0x09D7, 0x09D7), (0x09E2, 0x09E3), (0x09FE, 0x09FE), (0x0A01, 0x0A03),
+    (0x0A3C, 0x0A3C), (0x0A3E, 0x0A42), (0x0A47, 0x0A48), (0x0A4B, 0x0A4D),
+    (0x0A51, 0x0A51), (0x0A70, 0x0A71), (0x0A75, 0x0A75), (0x0A81, 0x0A83),
+    (0x0ABC, 0x0ABC), (0x0ABE, 0x0AC5), (0x0AC7, 0x0AC9), (0x0ACB, 0x0ACD),
+    (0x0AE2, 0x0AE3), (0x0AFA, 0x0AFF), (0x0B01, 0x0B03), (0x0B3C, 0x0B3C),
+    (0x0B3E, 0x0B44), (0x0B47, 0x0B48), (0x0B4B, 0x0B4D), (0x0B55, 0x0B57),
+    (0x0B62, 0x0B63), (0x0B82, 0x0B82), (0x0BBE, 0x0BC2), (0x0BC6, 0x0BC8),
+    (0x0BCA, 0x0BCD), (0x0BD7, 0x0BD7), (0x0C00, 0x0C04), (0x0C3C, 0x0C3C),
+    (0x0C3E, 0x0C44), (0x0C46, 0x0C48), (0x0C4A, 0x0C4D), (0x0C55, 0x0C56),
+    (0x0C62, 0x0C63), (0x0C81, 0x0C83), (0x0CBC, 0x0CBC), (0x0CBE, 0x0CC4),
+    (0x0CC6, 0x0CC8), (0x0CCA, 0x0CCD), (0x0CD5, 0x0CD6), (0x0CE2, 0x0CE3),
+    (0x0D00, 0x0D03), (0x0D3B, 0x0D3C), (0x0D3E, 0x0D44), (0x0D46, 0x0D48),
+    (0x0D4A, 0x0D4D), (0x0D57, 0x0D57), (0x0D62, 0x0D63), (0x0D81, 0x0D83),
+    (0x0DCA, 0x0DCA), (0x0DCF, 0x0DD4), (0x0DD6, 0x0DD6), (0x0DD8, 0x0DDF),
+    (0x0DF2, 0x0DF3), (0x0E31, 0x0E31), (0x0E34, 0x0E3A), (0x0E47, 0x0E4E),
+    (0x0EB1, 0x0EB1), (0x0EB4, 0x0EBC), (0x0EC8, 0x0ECE), (0x0F18, 0x0F19),
+    (0x0F35, 0x0F35), (0x0F37, 0x0F37), (0x0F39, 0x0F39), (0x0F3E, 0x0F3F),
+    (0x0F71, 0x0F84), (0x0F86, 0x0F87), (0x0F8D, 0x0FBC), (0x0FC6, 0x0FC6),
+    (0x102B, 0x103E), (0x1056, 0x1059), (0x105E, 0x1060), (0x1062, 0x1064),
+    (0x1067, 0x106D), (0x1071, 0x1074), (0x1082, 0x108D), (0x108F, 0x108F),
+    (0x109A, 0x109D), (0x135D, 0x135F), (0x17B4, 0x17D3), (0x17DD, 0x17DD),
+    (0x180B, 0x180D), (0x180F, 0x180F), (0x1AB0, 0x1AFF), (0x1DC0, 0x1DFF),
+    (0x200C, 0x200C), (0x20D0, 0x20F0), (0x2CEF, 0x2CF1), (0x2DE0, 0x2DFF),
+    (0x302A, 0x302F), (0x3099, 0x309A), (0xA66F, 0xA672), (0xA674, 0xA67D),
+    (0xA69E, 0xA69F), (0xA6F0, 0xA6F1), (0xFB1E, 0xFB1E), (0xFE00, 0xFE0F),
+    (0xFE20, 0xFE2F), (0xFF9E, 0xFF9F), (0x1F3FB, 0x1F3FF), (0xE0020, 0xE007F),
+    (0xE0100, 0xE01EF),
+];
+
+/// Emoji and other pictographs joinable into one cluster with U+200D.
+#[rustfmt::skip]
+const PICTOGRAPHIC: &[(u32, u32)] = &[
+    (0x00A9, 0x00A9), (0x00AE, 0x00AE), (0x203C, 0x203C), (0x2049, 0x2049),
+    (0x2122, 0x2122), (0x2139, 0x2139), (0x2194, 0x2199), (0x21A9, 0x21AA),
+    (0x231A, 0x231B), (0x2328, 0x2328), (0x23CF, 0x23CF), (0x23E9, 0x23F3),
+    (0x23F8, 0x23FA), (0x24C2, 0x24C2), (0x25AA, 0x25AB), (0x25B6, 0x25B6),
+    (0x25C0, 0x25C0), (0x25FB, 0x25FE), (0x2600, 0x27BF), (0x2934, 0x2935),
+    (0x2B05, 0x2B07), (0x2B1B, 0x2B1C), (0x2B50, 0x2B50), (0x2B55, 0x2B55),
+    (0x3030, 0x3030), (0x303D, 0x303D), (0x3297, 0x3297), (0x3299, 0x3299),
+    (0x1F000, 0x1F1E5), (0x1F200, 0x1F3FA), (0x1F400, 0x1FAFF), (0x1FC00, 0x1FFFD),
+];
@@ -0,0 +1,220 @@
+//! A lightweight walk over the tokens of raw JSON text, for checks on what
+//! parsing into a [`Value`](serde_json::Value) loses, such as the spelling
+//! of numbers, the order of keys and repeated keys.
+
+use crate::pointer;
+
+/// Receives the tokens of interest met by [`walk`].
+pub(crate) trait Visitor {
+    /// An object starts at `at`.
+    fn object(&mut self, _at: &str) {}
+
+    /// The object at `object` has a member named `key`, whose quoted name
+    /// starts at `offset`.
+    fn key(&mut self, _object: &str, _key: &str, _offset: usize) {}
+
+    /// The object at `at` ends.
+    fn object_end(&mut self, _at: &str) {}
+
+    /// The number `token` starts at `offset` and is located at `at`.
+    fn number(&mut self, _at: &str, _token: &[u8], _offset: usize) {}
+
+    /// The string `token`, quotes included, starts at `offset` and is
+    /// located at `at`. For a key, `at` is the object holding it.
+    fn string(&mut self, _at: &str, _token: &[u8], _offset: usize) {}
+}
+
+/// A container enclosing the token being read.
+struct Frame {
+    /// Location of the container.
+    at: String,
+    kind: Kind,
+}
+
+enum Kind {
+    Array(usize),
+    Object { key: String, expecting_key: bool },
+}
+
+impl Frame {
+    /// Returns the location of the current element of the container.
+    fn child(&self) -> String {
+        match &self.kind {
+            Kind::Array(index) => format!("{}/{index}", self.at),
+            Kind::Object { key, .. } => pointer::join(&self.at, key),
+        }
+    }
+}
+
+/// Walks the first JSON document of `json`, telling `visitor` about the
+/// tokens it meets.
+///
+/// Syntax errors are not reported: the walk simply stops there, leaving
+/// them to the parser.
+pub(crate) fn walk(json: &[u8], visitor: &mut impl Visitor) {
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut index = 0;
+    while let Some(&byte) = json.get(index) {
+        match byte {
+            b'{' | b'[' => {
+                let at = at(&stack);
+                let kind = if byte == b'{' {
+                    visitor.object(&at);
+                    Kind::Object {
+                        key: String::new(),
+                        expecting_key: true,
+                    }
+                } else {
+                    Kind::Array(0)
+                };
+                stack.push(Frame { at, kind });
+            }
+            b'}' | b']' => {
+                let Some(frame) = stack.pop() else {
+                    break;
+                };
+                if byte == b'}' {
+                    visitor.object_end(&frame.at);
+                }
+                if stack.is_empty() {
+                    break;
+                }
+            }
+            b',' => match stack.last_mut() {
+                Some(Frame {
+                    kind: Kind::Array(index),
+                    ..
+                }) => *index += 1,
+                Some(Frame {
+                    kind: Kind::Object { expecting_key, .. },
+                    ..
+                }) => *expecting_key = true,
+                None => break,
+            },
+            b'"' => {
+                let Some(len) = string_len(&json[index..]) else {
+                    break;
+                };
+                let token = &json[index..index + len];
+                match stack.last_mut() {
+                    Some(Frame {
+                        at,
+                        kind: Kind::Object { key, expecting_key },
+                    }) if *expecting_key => {
+                        let Some((decoded, _)) = decode(token) else {
+                            break;
+                        };
+                        *key = decoded;
+                        *expecting_key = false;
+                        visitor.string(at, token, index);
+                        visitor.key(at, key, index);
+                    }
+                    _ => visitor.string(&at(&stack), token, index),
+                }
+                index += len;
+                if stack.is_empty() {
+                    break;
+                }
+                continue;
+            }
+            b'-' | b'0'..=b'9' => {
+                let len = json[index..]
+                    .iter()
+                    .take_while(|byte| {
+                        matches!(byte, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+                    })
+                    .count();
+                visitor.number(&at(&stack), &json[index..index + len], index);
+                index += len;
+                if stack.is_empty() {
+                    break;
+                }
+                continue;
+            }
+            // A literal document holds no other token.
+            _ if stack.is_empty() && !byte.is_ascii_whitespace() => break,
+            _ => {}
+        }
+        index += 1;
+    }
+}
+
+/// Returns the location of the value being read.
+fn at(stack: &[Frame]) -> String {
+    stack.last().map_or_else(|| "#".to_owned(), Frame::child)
+}
+
+/// Returns the length of the string starting `json`, quotes included.
+fn string_len(json: &[u8]) -> Option<usize> {
+    let mut index = 1;
+    loop {
+        match json.get(index)? {
+            b'"' => return Some(index + 1),
+            b'\\' => index += 2,
+            _ => index += 1,
+        }
+    }
+}
+
+/// Decodes the string `token`, quotes included, replacing lone surrogates
+/// with U+FFFD.
+///
+/// Returns the text with the first lone surrogate met, or `None` if the
+/// string is malformed.
+pub(crate) fn decode(token: &[u8]) -> Option<(String, Option<u16>)> {
+    let body = token.strip_prefix(b"\"")?.strip_suffix(b"\"")?;
+    let mut decoded = Vec::with_capacity(body.len());
+    let mut lone = None;
+    let mut index = 0;
+    while let Some(&byte) = body.get(index) {
+        index += 1;
+        if byte != b'\\' {
+            decoded.push(byte);
+            continue;
+        }
+        let escape = *body.get(index)?;
+        index += 1;
+        let char = match escape {
+            b'"' => '"',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => {
+                let unit = hex(body.get(index..index + 4)?)?;
+                index += 4;
+                match unit {
+                    0xd800..=0xdbff if body[index..].starts_with(b"\\u") => {
+                        let low = hex(body.get(index + 2..index + 6)?)?;
+                        if (0xdc00..=0xdfff).contains(&low) {
+                            index += 6;
+                            let code = 0x10000
+                                + ((u32::from(unit) - 0xd800) << 10)
+                                + (u32::from(low) - 0xdc00);
+                            char::from_u32(code)?
+                        } else {
+                            lone.get_or_insert(unit);
+                            char::REPLACEMENT_CHARACTER
+                        }
+                    }
+                    0xd800..=0xdfff => {
+                        lone.get_or_insert(unit);
+                        char::REPLACEMENT_CHARACTER
+                    }
+                    unit => char::from_u32(u32::from(unit))?,
+                }
+            }
+            _ => return None,
+        };
+        decoded.extend_from_slice(char.encode_utf8(&mut [0; 4]).as_bytes());
+    }
+    Some((String::from_utf8(decoded).ok()?, lone))
+}
+
+/// Parses the four hex digits of a `\u` escape.
+fn hex(digits: &[u8]) -> Option<u16> {
+    u16::from_str_radix(std::str::from_utf8(digits).ok()?, 16).ok()
+}
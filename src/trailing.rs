@@ -0,0 +1,68 @@
+//! Detection of data following the JSON document.
+
+use serde::de::DeserializeOwned;
+use serde_json::Deserializer;
+
+use crate::stream::skip_whitespace;
+use crate::{InvalidParam, Severity, code};
+
+/// How data after the JSON document is treated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Trailing {
+    /// Accept the document, reporting the data with a warning.
+    pub(crate) accept: bool,
+    /// Most characters of the data quoted in the finding.
+    pub(crate) preview: usize,
+}
+
+impl Default for Trailing {
+    fn default() -> Self {
+        Self {
+            accept: false,
+            preview: 16,
+        }
+    }
+}
+
+/// Parses the JSON document at the start of `json`, with a finding about
+/// the data following it, if any.
+pub(crate) fn parse<T>(
+    json: &[u8],
+    trailing: Trailing,
+) -> serde_json::Result<(T, Option<InvalidParam>)>
+where
+    T: DeserializeOwned,
+{
+    let mut values = Deserializer::from_slice(json).into_iter::<T>();
+    let value = match values.next() {
+        Some(value) => value?,
+        // Let the parser report the missing document.
+        None => serde_json::from_slice(json)?,
+    };
+    let offset = skip_whitespace(json, values.byte_offset());
+    if offset >= json.len() {
+        return Ok((value, None));
+    }
+    Ok((value, Some(finding(&json[offset..], offset, trailing))))
+}
+
+fn finding(data: &[u8], offset: usize, trailing: Trailing) -> InvalidParam {
+    let data = String::from_utf8_lossy(data);
+    let preview: String = data.chars().take(trailing.preview).collect();
+    let ellipsis = if preview.len() < data.len() {
+        "..."
+    } else {
+        ""
+    };
+    let finding = InvalidParam::new(
+        "#",
+        code::SYNTAX_TRAILING,
+        format!("Unexpected data after the JSON document at byte {offset}: {preview:?}{ellipsis}"),
+    )
+    .offset(offset);
+    if trailing.accept {
+        finding.severity(Severity::Warning)
+    } else {
+        finding
+    }
+}
@@ -0,0 +1,249 @@
+//! Numbers with unit suffixes, such as `"10MB"` or `"250ms"`, for use with
+//! `#[serde(with)]`.
+//!
+//! A [`Table`] lists the suffixes of a quantity and how many base units each
+//! one stands for. Values are converted to the base unit exactly, then
+//! checked against the range of the field; unknown units, fractions of the
+//! base unit and values out of range are all reported at the pointer of the
+//! field. Plain numbers are accepted too, in the table's default unit.
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use serde::Deserialize;
+//!
+//! #[derive(Debug, Deserialize)]
+//! struct Cache {
+//!     #[serde(with = "serdify::units::bytes")]
+//!     capacity: u64,
+//!     #[serde(with = "serdify::units::duration")]
+//!     ttl: Duration,
+//! }
+//!
+//! let cache: Cache = serdify::from_str(r#"{"capacity": "1.5GiB", "ttl": "2h"}"#).unwrap();
+//! assert_eq!(cache.capacity, 1536 * 1024 * 1024);
+//! assert_eq!(cache.ttl, Duration::from_secs(7200));
+//!
+//! let error = serdify::from_str::<Cache>(r#"{"capacity": "10XB", "ttl": "0.5ns"}"#).unwrap_err();
+//! let pointers: Vec<_> = error.invalid_params.iter().map(|p| p.pointer.as_str()).collect();
+//! assert_eq!(pointers, ["#/capacity", "#/ttl"]);
+//! ```
+//!
+//! Other quantities only need a table and a `deserialize_with` function
+//! calling [`deserialize`].
+
+use std::fmt;
+
+use serde::Deserializer;
+use serde::de::{self, Unexpected, Visitor};
+
+/// Units of a quantity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Table {
+    /// What the quantity is, for error messages, such as `"size"`.
+    pub name: &'static str,
+    /// Suffixes with the number of base units each one stands for, in the
+    /// order they are preferred when formatting.
+    pub units: &'static [(&'static str, u128)],
+    /// Suffix assumed for numbers written without one, or `None` to require
+    /// a suffix.
+    pub default: Option<&'static str>,
+}
+
+impl Table {
+    /// Sizes in bytes, with decimal (`kB`, `MB`, …) and binary (`KiB`,
+    /// `MiB`, …) multiples.
+    pub const BYTES: Table = Table {
+        name: "size",
+        units: &[
+            ("PiB", 1 << 50),
+            ("PB", 1_000_000_000_000_000),
+            ("TiB", 1 << 40),
+            ("TB", 1_000_000_000_000),
+            ("GiB", 1 << 30),
+            ("GB", 1_000_000_000),
+            ("MiB", 1 << 20),
+            ("MB", 1_000_000),
+            ("KiB", 1 << 10),
+            ("kB", 1_000),
+            ("KB", 1_000),
+            ("B", 1),
+        ],
+        default: Some("B"),
+    };
+
+    /// Durations in nanoseconds, from `ns` to days (`d`).
+    pub const DURATION: Table = Table {
+        name: "duration",
+        units: &[
+            ("d", 86_400_000_000_000),
+            ("h", 3_600_000_000_000),
+            ("min", 60_000_000_000),
+            ("m", 60_000_000_000),
+            ("s", 1_000_000_000),
+            ("ms", 1_000_000),
+            ("us", 1_000),
+            ("µs", 1_000),
+            ("ns", 1),
+        ],
+        default: Some("s"),
+    };
+
+    /// Converts `text`, such as `"10MB"` or `"1.5 h"`, to base units.
+    pub fn parse(&self, text: &str) -> Option<u128> {
+        let text = text.trim();
+        let split = text
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(text.len());
+        let (number, suffix) = text.split_at(split);
+        let suffix = match suffix.trim_start() {
+            "" => self.default?,
+            suffix => suffix,
+        };
+        let factor = self.factor(suffix)?;
+        let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+        if whole.is_empty() || fraction.contains('.') {
+            return None;
+        }
+        let scale = 10u128.checked_pow(fraction.len().try_into().ok()?)?;
+        let digits: u128 = format!("{whole}{fraction}").parse().ok()?;
+        let value = digits.checked_mul(factor)?;
+        value.is_multiple_of(scale).then_some(value / scale)
+    }
+
+    /// Formats `value` base units with the first unit dividing it.
+    pub fn format(&self, value: u128) -> String {
+        let unit = self
+            .units
+            .iter()
+            .find(|(_, factor)| value > 0 && value.is_multiple_of(*factor));
+        match unit {
+            Some((suffix, factor)) => format!("{}{suffix}", value / factor),
+            None => value.to_string(),
+        }
+    }
+
+    fn factor(&self, suffix: &str) -> Option<u128> {
+        self.units
+            .iter()
+            .find(|(name, _)| *name == suffix)
+            .map(|(_, factor)| *factor)
+    }
+}
+
+/// What a field of `table` accepts, for error messages.
+struct Expected<'a>(&'a Table);
+
+impl de::Expected for Expected<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a whole {} in one of the units ", self.0.name)?;
+        for (index, (suffix, _)) in self.0.units.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            f.write_str(suffix)?;
+        }
+        Ok(())
+    }
+}
+
+/// Deserializes a number of base units of `table`, from a number or a
+/// string with a unit suffix, into any integer type.
+pub fn deserialize<'de, D, T>(table: &Table, deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: TryFrom<u128>,
+{
+    let value = deserializer.deserialize_any(UnitVisitor(table))?;
+    T::try_from(value).map_err(|_| {
+        let text = table.format(value);
+        let expected = format!("a {} that fits the field", table.name);
+        de::Error::invalid_value(Unexpected::Str(&text), &expected.as_str())
+    })
+}
+
+struct UnitVisitor<'a>(&'a Table);
+
+impl<'de> Visitor<'de> for UnitVisitor<'_> {
+    type Value = u128;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        de::Expected::fmt(&Expected(self.0), f)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<u128, E> {
+        self.visit_u128(value.into())
+    }
+
+    fn visit_u128<E: de::Error>(self, value: u128) -> Result<u128, E> {
+        let default = self.0.default.and_then(|suffix| self.0.factor(suffix));
+        default
+            .and_then(|factor| value.checked_mul(factor))
+            .ok_or_else(|| E::invalid_value(Unexpected::Unsigned(value as u64), &self))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<u128, E> {
+        match u64::try_from(value) {
+            Ok(value) => self.visit_u64(value),
+            Err(_) => Err(E::invalid_value(Unexpected::Signed(value), &self)),
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, text: &str) -> Result<u128, E> {
+        self.0
+            .parse(text)
+            .ok_or_else(|| E::invalid_value(Unexpected::Str(text), &self))
+    }
+}
+
+/// `#[serde(with)]` module for integer sizes in bytes written like `"10MB"`
+/// or `"512KiB"`. See [`Table::BYTES`].
+pub mod bytes {
+    use serde::{Deserializer, Serializer};
+
+    use super::Table;
+
+    /// Serializes a size with the largest unit dividing it.
+    pub fn serialize<S, T>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: Copy + Into<u128>,
+    {
+        serializer.collect_str(&Table::BYTES.format((*value).into()))
+    }
+
+    /// Deserializes a size from a number of bytes or a string with a unit.
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: TryFrom<u128>,
+    {
+        super::deserialize(&Table::BYTES, deserializer)
+    }
+}
+
+/// `#[serde(with)]` module for [`Duration`](std::time::Duration) values
+/// written like `"250ms"` or `"2h"`. See [`Table::DURATION`].
+pub mod duration {
+    use std::time::Duration;
+
+    use serde::{Deserializer, Serializer, de};
+
+    use super::Table;
+
+    /// Serializes a duration with the largest unit dividing it.
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&Table::DURATION.format(duration.as_nanos()))
+    }
+
+    /// Deserializes a duration from a number of seconds or a string with a
+    /// unit.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let nanos: u128 = super::deserialize(&Table::DURATION, deserializer)?;
+        let seconds = u64::try_from(nanos / 1_000_000_000).map_err(|_| {
+            let text = Table::DURATION.format(nanos);
+            de::Error::invalid_value(de::Unexpected::Str(&text), &"a shorter duration")
+        })?;
+        Ok(Duration::new(seconds, (nanos % 1_000_000_000) as u32))
+    }
+}
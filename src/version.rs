@@ -0,0 +1,156 @@
+use crate::Error;
+
+/// Version of the shape of the problem documents serdify produces, carried
+/// by every [`Error`] in its `schema_version` extension member.
+///
+/// The version is bumped whenever the shape gains a member. Members are
+/// only ever added, never removed or changed, so a client written against
+/// one version can read any later payload by ignoring the members it does
+/// not know; a [`VersionPolicy`] states which versions it accepts.
+///
+/// | Version | Shape |
+/// |---------|-------|
+/// | 1 | `title`, `status`, `detail`, `reference`, `schema_version` and `invalid_params`, each with `name`, `reason`, `expected`, `actual`, `pointer`, `path`, `code`, `severity` and `caused_by` |
+/// | 2 | `offset` in `invalid_params` |
+///
+/// Version 2 serializes as:
+///
+/// ```
+/// use serde_json::json;
+/// use serdify::{Error, InvalidParam, Severity, code};
+///
+/// let mut param = InvalidParam::new("#/items/0", code::TYPE_MISMATCH, "Expected u8")
+///     .severity(Severity::Warning)
+///     .offset(12);
+/// param.path = Some("items[0]".to_owned());
+/// param.caused_by = Some("#/items".to_owned());
+/// let error = Error {
+///     detail: Some("Detail.".to_owned()),
+///     reference: Some("ref".to_owned()),
+///     ..Error::new(vec![param])
+/// };
+///
+/// assert_eq!(
+///     serde_json::to_value(&error).unwrap(),
+///     json!({
+///         "title": "Your request parameters didn't validate.",
+///         "status": 400,
+///         "detail": "Detail.",
+///         "invalid_params": [{
+///             "name": "0",
+///             "reason": "Expected u8",
+///             "expected": {"type": "any", "format": "any"},
+///             "actual": {"type": "any", "format": "any"},
+///             "pointer": "#/items/0",
+///             "path": "items[0]",
+///             "code": "type.mismatch",
+///             "severity": "warning",
+///             "caused_by": "#/items",
+///             "offset": 12,
+///         }],
+///         "reference": "ref",
+///         "schema_version": 2,
+///     })
+/// );
+/// ```
+///
+/// Optional members are left out when unset:
+///
+/// ```
+/// use serde_json::json;
+/// use serdify::from_str;
+///
+/// let error = from_str::<Vec<u8>>("[300]").unwrap_err();
+///
+/// assert_eq!(
+///     serde_json::to_value(&error).unwrap(),
+///     json!({
+///         "title": "Your request parameters didn't validate.",
+///         "status": 400,
+///         "invalid_params": [{
+///             "name": "0",
+///             "reason": "Value 300 is out of range for type u8. Expected range: 0 to 255",
+///             "expected": {"type": "u8", "format": "integer"},
+///             "actual": {"type": "u64", "format": "integer"},
+///             "pointer": "#/0",
+///             "code": "range.overflow",
+///         }],
+///         "schema_version": 2,
+///     })
+/// );
+/// ```
+///
+/// Payloads of earlier versions still deserialize, lacking the later
+/// members:
+///
+/// ```
+/// use serdify::Error;
+///
+/// let version_1 = r##"{
+///     "title": "Your request parameters didn't validate.",
+///     "status": 400,
+///     "invalid_params": [{
+///         "name": "0",
+///         "expected": {"type": "u8", "format": "integer"},
+///         "actual": {"type": "u64", "format": "integer"},
+///         "pointer": "#/0",
+///         "code": "range.overflow",
+///         "caused_by": "#"
+///     }],
+///     "schema_version": 1
+/// }"##;
+///
+/// let error: Error = serde_json::from_str(version_1).unwrap();
+/// assert_eq!(error.schema_version, 1);
+/// assert_eq!(error.invalid_params[0].offset, None);
+/// ```
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Which versions of the problem document shape a client accepts, see
+/// [`SCHEMA_VERSION`].
+///
+/// ```
+/// use serdify::{Error, VersionPolicy};
+///
+/// let error: Error = serde_json::from_str(r#"{"title": "Bad", "schema_version": 3}"#).unwrap();
+///
+/// assert!(error.is_accepted_by(VersionPolicy::AtLeast(1)));
+/// assert!(!error.is_accepted_by(VersionPolicy::Exact(1)));
+/// assert!(!error.is_accepted_by(VersionPolicy::AtLeast(4)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VersionPolicy {
+    /// Accepts only this version, for clients that must see every member.
+    Exact(u32),
+    /// Accepts this version or any later one, whose extra members the
+    /// client ignores.
+    AtLeast(u32),
+}
+
+impl VersionPolicy {
+    /// Accepts the version this build of serdify produces or any later one.
+    pub fn current() -> Self {
+        Self::AtLeast(SCHEMA_VERSION)
+    }
+
+    /// Returns `true` if a payload of `version` is accepted.
+    pub fn accepts(&self, version: u32) -> bool {
+        match *self {
+            Self::Exact(expected) => version == expected,
+            Self::AtLeast(minimum) => version >= minimum,
+        }
+    }
+}
+
+impl Error {
+    /// Returns `true` if the shape of this payload is accepted by `policy`.
+    pub fn is_accepted_by(&self, policy: VersionPolicy) -> bool {
+        policy.accepts(self.schema_version)
+    }
+}
+
+/// Version assumed for payloads lacking the `schema_version` member, which
+/// were produced before it was introduced with the first version.
+pub(crate) fn unversioned() -> u32 {
+    1
+}